@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+
+use hob_protocol::packet::available_commands::{CommandEnum, CommandParamType, CommandParameter};
+use specs::prelude::*;
+
+use super::selector::Target;
+use crate::player::{components::PositionComponent, spawn::PLAYER_EYE_HEIGHT};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamKind {
+    Int,
+    Float,
+    /// A selector or player name, resolved to entities.
+    Target,
+    /// Three coordinates, each absolute or `~`-relative to the sender's feet.
+    Position,
+    /// A single word or quoted string.
+    String,
+    /// One of `values`, matched case-insensitively.
+    Enum {
+        name: String,
+        values: Vec<String>,
+    },
+    /// The rest of the line.
+    RawText,
+}
+impl ParamKind {
+    pub fn enumeration(name: &str, values: &[&str]) -> Self {
+        ParamKind::Enum {
+            name: name.to_owned(),
+            values: values.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub name: String,
+    pub kind: ParamKind,
+    pub optional: bool,
+}
+impl Param {
+    pub fn new(name: &str, kind: ParamKind) -> Self {
+        Param {
+            name: name.to_owned(),
+            kind,
+            optional: false,
+        }
+    }
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+    pub(crate) fn to_protocol(&self) -> CommandParameter {
+        let param_type = match &self.kind {
+            ParamKind::Int => CommandParamType::Int,
+            ParamKind::Float => CommandParamType::Float,
+            ParamKind::Target => CommandParamType::Target,
+            ParamKind::Position => CommandParamType::Position,
+            ParamKind::String => CommandParamType::String,
+            ParamKind::Enum { name, values } => CommandParamType::Enum(CommandEnum {
+                name: name.clone(),
+                values: values.clone(),
+            }),
+            ParamKind::RawText => CommandParamType::RawText,
+        };
+        CommandParameter {
+            name: self.name.clone(),
+            param_type,
+            optional: self.optional,
+            options: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgValue {
+    Int(i32),
+    Float(f32),
+    Targets(Vec<Entity>),
+    Position(f32, f32, f32),
+    /// Strings, enum values and raw text.
+    String(String),
+}
+
+/// Arguments of a parsed command, keyed by parameter name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommandArgs {
+    /// Index of the overload that matched.
+    pub overload: usize,
+    values: HashMap<String, ArgValue>,
+}
+impl CommandArgs {
+    pub fn get(&self, name: &str) -> Option<&ArgValue> {
+        self.values.get(name)
+    }
+    pub fn int(&self, name: &str) -> Option<i32> {
+        match self.get(name)? {
+            ArgValue::Int(v) => Some(*v),
+            _ => None,
+        }
+    }
+    pub fn float(&self, name: &str) -> Option<f32> {
+        match self.get(name)? {
+            ArgValue::Float(v) => Some(*v),
+            ArgValue::Int(v) => Some(*v as f32),
+            _ => None,
+        }
+    }
+    pub fn targets(&self, name: &str) -> Option<&[Entity]> {
+        match self.get(name)? {
+            ArgValue::Targets(v) => Some(v),
+            _ => None,
+        }
+    }
+    pub fn position(&self, name: &str) -> Option<(f32, f32, f32)> {
+        match self.get(name)? {
+            ArgValue::Position(x, y, z) => Some((*x, *y, *z)),
+            _ => None,
+        }
+    }
+    pub fn string(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            ArgValue::String(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+/// Splits a command line into `(offset, token)` pairs, honouring quotes and selector brackets.
+/// Fails on an unterminated quote or a `]` that closes nothing.
+pub(crate) fn tokenize(line: &str) -> Result<Vec<(usize, String)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut token = String::new();
+        if c == '"' {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some((_, '"')) => break,
+                    Some((_, '\\')) => token.extend(chars.next().map(|(_, c)| c)),
+                    Some((_, c)) => token.push(c),
+                    None => return Err(format!("Unterminated quote at {}", start + 1)),
+                }
+            }
+        } else {
+            let mut depth = 0;
+            while let Some(&(offset, c)) = chars.peek() {
+                if c.is_whitespace() && depth == 0 {
+                    break;
+                }
+                match c {
+                    '[' => depth += 1,
+                    ']' if depth == 0 => return Err(format!("Unexpected ] at {}", offset + 1)),
+                    ']' => depth -= 1,
+                    _ => {}
+                }
+                token.push(c);
+                chars.next();
+            }
+        }
+        tokens.push((start, token));
+    }
+    Ok(tokens)
+}
+
+/// Parses `line` against `params`, returning the number of tokens consumed on failure.
+pub(crate) fn parse_overload(
+    world: &World,
+    sender: Entity,
+    params: &[Param],
+    line: &str,
+    tokens: &[(usize, String)],
+) -> Result<HashMap<String, ArgValue>, (usize, String)> {
+    let mut values = HashMap::new();
+    let mut next = 0;
+    for param in params {
+        if next >= tokens.len() {
+            if param.optional {
+                break;
+            }
+            return Err((next, format!("Missing argument: {}", param.name)));
+        }
+        let token = tokens[next].1.as_str();
+        let value = match &param.kind {
+            ParamKind::Int => token
+                .parse()
+                .map(ArgValue::Int)
+                .map_err(|_| format!("Expected an integer for {}: {token}", param.name)),
+            ParamKind::Float => token
+                .parse()
+                .map(ArgValue::Float)
+                .map_err(|_| format!("Expected a number for {}: {token}", param.name)),
+            ParamKind::Target => Target::parse(token).and_then(|target| {
+                let entities = target.resolve(world, sender);
+                match entities.is_empty() {
+                    true => Err(format!("No targets matched {token}")),
+                    false => Ok(ArgValue::Targets(entities)),
+                }
+            }),
+            ParamKind::Position => {
+                let coords = &tokens[next..tokens.len().min(next + 3)];
+                if coords.len() < 3 {
+                    return Err((tokens.len(), format!("Incomplete position: {}", param.name)));
+                }
+                next += 2;
+                parse_position(world, sender, coords)
+            }
+            ParamKind::String => Ok(ArgValue::String(token.to_owned())),
+            ParamKind::Enum {
+                values: choices, ..
+            } => choices
+                .iter()
+                .find(|v| v.eq_ignore_ascii_case(token))
+                .map(|v| ArgValue::String(v.clone()))
+                .ok_or_else(|| {
+                    format!("Expected one of {} for {}", choices.join(", "), param.name)
+                }),
+            ParamKind::RawText => {
+                let text = line[tokens[next].0..].trim_end().to_owned();
+                next = tokens.len() - 1;
+                Ok(ArgValue::String(text))
+            }
+        };
+        let value = value.map_err(|e| (next, e))?;
+        values.insert(param.name.clone(), value);
+        next += 1;
+    }
+    if next < tokens.len() {
+        return Err((next, format!("Unexpected argument: {}", tokens[next].1)));
+    }
+    Ok(values)
+}
+
+/// Picks the first overload that parses; otherwise reports the one that got furthest.
+pub(crate) fn parse_args(
+    world: &World,
+    sender: Entity,
+    overloads: &[Vec<Param>],
+    line: &str,
+) -> Result<CommandArgs, String> {
+    let tokens = tokenize(line)?;
+    let mut best: Option<(usize, String)> = None;
+    for (overload, params) in overloads.iter().enumerate() {
+        match parse_overload(world, sender, params, line, &tokens) {
+            Ok(values) => return Ok(CommandArgs { overload, values }),
+            Err((progress, e)) => {
+                if best.as_ref().is_none_or(|(p, _)| progress > *p) {
+                    best = Some((progress, e));
+                }
+            }
+        }
+    }
+    Err(best.map_or_else(|| "Invalid arguments".to_owned(), |(_, e)| e))
+}
+
+fn parse_position(
+    world: &World,
+    sender: Entity,
+    coords: &[(usize, String)],
+) -> Result<ArgValue, String> {
+    let feet = world
+        .read_storage::<PositionComponent>()
+        .get(sender)
+        .map(|p| [p.x, p.y - PLAYER_EYE_HEIGHT, p.z]);
+    let mut out = [0.0; 3];
+    for (i, (_, token)) in coords.iter().enumerate() {
+        out[i] = match token.strip_prefix('~') {
+            Some(offset) => {
+                let base = feet.ok_or("Relative coordinates need a position")?[i];
+                let offset = match offset {
+                    "" => 0.0,
+                    v => v
+                        .parse::<f32>()
+                        .map_err(|_| format!("Invalid coordinate: {token}"))?,
+                };
+                base + offset
+            }
+            None if token.starts_with('^') => {
+                return Err("Local coordinates are not supported".to_owned())
+            }
+            None => token
+                .parse()
+                .map_err(|_| format!("Invalid coordinate: {token}"))?,
+        };
+    }
+    Ok(ArgValue::Position(out[0], out[1], out[2]))
+}
@@ -0,0 +1,241 @@
+pub mod args;
+pub mod selector;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use hob_protocol::packet::{
+    available_commands::{AvailableCommandsPacket, CommandData, CommandOverload},
+    command_output::{CommandOutputMessage, CommandOutputPacket, CommandOutputType},
+    command_request::CommandRequestPacket,
+};
+use log::info;
+use specs::prelude::*;
+
+use self::args::{parse_args, CommandArgs, Param};
+use crate::{
+    permission::has_permission, player::components::connection::ConnectionStreamComponent,
+};
+
+/// Schema of a command: its name, aliases and argument overloads.
+#[derive(Debug, Clone)]
+pub struct Command {
+    pub name: String,
+    pub description: String,
+    pub aliases: Vec<String>,
+    /// Node the sender needs; `None` lets everyone use the command.
+    pub permission: Option<String>,
+    pub overloads: Vec<Vec<Param>>,
+}
+impl Command {
+    pub fn new(name: &str, description: &str) -> Self {
+        Command {
+            name: name.to_lowercase(),
+            description: description.to_owned(),
+            aliases: Vec::new(),
+            permission: None,
+            overloads: Vec::new(),
+        }
+    }
+    pub fn alias(mut self, alias: &str) -> Self {
+        self.aliases.push(alias.to_lowercase());
+        self
+    }
+    pub fn permission(mut self, node: &str) -> Self {
+        self.permission = Some(node.to_owned());
+        self
+    }
+    pub fn overload(mut self, params: Vec<Param>) -> Self {
+        self.overloads.push(params);
+        self
+    }
+    fn to_protocol(&self) -> CommandData {
+        let overloads = match self.overloads.is_empty() {
+            true => vec![CommandOverload::default()],
+            false => self
+                .overloads
+                .iter()
+                .map(|params| CommandOverload {
+                    chaining: false,
+                    parameters: params.iter().map(Param::to_protocol).collect(),
+                })
+                .collect(),
+        };
+        CommandData {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            flags: 0,
+            permission_level: 0,
+            aliases: self.aliases.clone(),
+            overloads,
+        }
+    }
+}
+
+pub struct CommandContext {
+    pub sender: Entity,
+    /// Name the command was registered under, not the alias used.
+    pub name: String,
+    pub args: CommandArgs,
+}
+
+/// `Ok` and `Err` messages are shown to the sender as success and failure output.
+pub type CommandResult = Result<String, String>;
+
+pub trait CommandSys<'a> {
+    type SystemData: SystemData<'a>;
+    fn run(&mut self, ctx: &CommandContext, data: Self::SystemData) -> CommandResult;
+}
+
+pub trait CommandRunNow<'a> {
+    fn run_now(&mut self, ctx: &CommandContext, world: &'a World) -> CommandResult;
+}
+
+impl<'a, T> CommandRunNow<'a> for T
+where
+    T: CommandSys<'a>,
+{
+    fn run_now(&mut self, ctx: &CommandContext, world: &'a World) -> CommandResult {
+        let data = T::SystemData::fetch(world);
+        self.run(ctx, data)
+    }
+}
+
+/// Registered commands, readable by executors.
+#[derive(Default)]
+pub struct CommandRegistry {
+    commands: BTreeMap<String, Command>,
+    aliases: HashMap<String, String>,
+}
+impl CommandRegistry {
+    /// Looks a command up by name or alias, ignoring case.
+    pub fn get(&self, name: &str) -> Option<&Command> {
+        let name = name.to_lowercase();
+        let name = self.aliases.get(&name).unwrap_or(&name);
+        self.commands.get(name)
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &Command> {
+        self.commands.values()
+    }
+    /// Lists the commands `can_use` accepts, so clients only autocomplete what they may run.
+    pub fn available_commands(
+        &self,
+        can_use: impl Fn(&Command) -> bool,
+    ) -> AvailableCommandsPacket {
+        AvailableCommandsPacket {
+            commands: self
+                .commands
+                .values()
+                .filter(|command| can_use(command))
+                .map(Command::to_protocol)
+                .collect(),
+        }
+    }
+}
+
+type Executor = Arc<Mutex<dyn for<'a> CommandRunNow<'a> + Send + Sync>>;
+
+/// Shared so that [`execute`] can run one without holding the resource, and so commands
+/// can run other commands.
+#[derive(Default)]
+struct CommandExecutors(HashMap<String, Executor>);
+
+/// Requests received this tick, run once no storages are borrowed.
+#[derive(Default)]
+pub(crate) struct CommandQueue(pub Vec<(Entity, CommandRequestPacket)>);
+
+pub fn init_command(world: &mut World) {
+    world.insert(CommandRegistry::default());
+    world.insert(CommandExecutors::default());
+    world.insert(CommandQueue::default());
+}
+
+/// Registers `command`, replacing any command or alias of the same name. Executors that need
+/// the whole world, e.g. to run other commands, can implement [`CommandRunNow`] directly.
+pub fn register_command<T>(world: &World, command: Command, executor: T)
+where
+    T: for<'a> CommandRunNow<'a> + Send + Sync + 'static,
+{
+    let mut registry = world.write_resource::<CommandRegistry>();
+    for alias in command.aliases.iter() {
+        registry.aliases.insert(alias.clone(), command.name.clone());
+    }
+    world
+        .write_resource::<CommandExecutors>()
+        .0
+        .insert(command.name.clone(), Arc::new(Mutex::new(executor)));
+    registry.commands.insert(command.name.clone(), command);
+}
+
+/// Parses and runs `line` on behalf of `sender`. A leading `/` is optional.
+pub fn execute(world: &World, sender: Entity, line: &str) -> CommandResult {
+    let line = line.trim();
+    let line = line.strip_prefix('/').unwrap_or(line);
+    let (label, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let ctx = {
+        let registry = world.read_resource::<CommandRegistry>();
+        let Some(command) = registry.get(label) else {
+            return Err(format!("Unknown command: {label}"));
+        };
+        if let Some(node) = command.permission.as_ref() {
+            if !has_permission(world, sender, node) {
+                return Err(format!(
+                    "You do not have permission to use /{}",
+                    command.name
+                ));
+            }
+        }
+        let overloads = match command.overloads.is_empty() {
+            true => vec![Vec::new()],
+            false => command.overloads.clone(),
+        };
+        CommandContext {
+            sender,
+            name: command.name.clone(),
+            args: parse_args(world, sender, &overloads, rest)?,
+        }
+    };
+    let executor = world
+        .read_resource::<CommandExecutors>()
+        .0
+        .get(&ctx.name)
+        .cloned();
+    let Some(executor) = executor else {
+        return Err(format!("Unknown command: {label}"));
+    };
+    let Ok(mut executor) = executor.try_lock() else {
+        return Err(format!("/{} can't run itself", ctx.name));
+    };
+    executor.run_now(&ctx, world)
+}
+
+pub(crate) fn handle_commands(world: &World) {
+    let requests = std::mem::take(&mut world.write_resource::<CommandQueue>().0);
+    for (sender, request) in requests {
+        let result = execute(world, sender, &request.command_line);
+        info!("Command {:?}: {:?}", request.command_line, result);
+        let (success, message) = match result {
+            Ok(message) => (true, message),
+            Err(message) => (false, message),
+        };
+        let output = CommandOutputPacket {
+            origin: request.origin,
+            output_type: CommandOutputType::AllOutput,
+            success_count: success as u32,
+            messages: vec![CommandOutputMessage {
+                success,
+                message,
+                parameters: Vec::new(),
+            }],
+            data_set: String::new(),
+        };
+        if let Some(conn) = world
+            .write_storage::<ConnectionStreamComponent>()
+            .get_mut(sender)
+        {
+            conn.send_packet(output);
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use std::cmp::Ordering;
+
+use specs::prelude::*;
+
+use crate::{
+    player::{
+        components::{DisplayNameComponent, PositionComponent},
+        resources::OnlinePlayers,
+    },
+    world::components::{ActorTypeComponent, DimensionComponent},
+};
+
+const PLAYER_TYPE: &str = "minecraft:player";
+
+/// A `@x[...]` selector or a bare player name.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    Selector(Selector),
+    Name(String),
+}
+impl Target {
+    pub fn parse(token: &str) -> Result<Self, String> {
+        if token.starts_with('@') {
+            Selector::parse(token).map(Target::Selector)
+        } else {
+            Ok(Target::Name(token.to_owned()))
+        }
+    }
+    pub fn resolve(&self, world: &World, sender: Entity) -> Vec<Entity> {
+        match self {
+            Target::Selector(selector) => selector.resolve(world, sender),
+            Target::Name(name) => world
+                .read_resource::<OnlinePlayers>()
+                .by_name(name)
+                .map(|p| p.entity)
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectorKind {
+    /// `@a`
+    AllPlayers,
+    /// `@p`
+    NearestPlayer,
+    /// `@s`
+    Sender,
+    /// `@e`
+    AllEntities,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SelectorFilters {
+    /// `name=`, negated with `!`.
+    pub name: Option<(bool, String)>,
+    /// `type=`, negated with `!`.
+    pub actor_type: Option<(bool, String)>,
+    /// `r=`, maximum distance.
+    pub r: Option<f32>,
+    /// `rm=`, minimum distance.
+    pub rm: Option<f32>,
+    /// `c=`, how many; negative counts from the farthest.
+    pub c: Option<i32>,
+    /// `x=`, `y=`, `z=`, overriding the sender's position as the origin.
+    pub x: Option<f32>,
+    pub y: Option<f32>,
+    pub z: Option<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    pub kind: SelectorKind,
+    pub filters: SelectorFilters,
+}
+impl Selector {
+    pub fn parse(token: &str) -> Result<Self, String> {
+        let (head, filters) = match token.find('[') {
+            Some(i) => {
+                let Some(body) = token[i + 1..].strip_suffix(']') else {
+                    return Err(format!("Unterminated selector: {token}"));
+                };
+                (&token[..i], Some(body))
+            }
+            None => (token, None),
+        };
+        let kind = match head {
+            "@a" => SelectorKind::AllPlayers,
+            "@p" => SelectorKind::NearestPlayer,
+            "@s" => SelectorKind::Sender,
+            "@e" => SelectorKind::AllEntities,
+            _ => return Err(format!("Unknown selector: {head}")),
+        };
+        let mut selector = Selector {
+            kind,
+            filters: SelectorFilters::default(),
+        };
+        for filter in filters.into_iter().flat_map(|v| v.split(',')) {
+            let filter = filter.trim();
+            if filter.is_empty() {
+                continue;
+            }
+            let Some((key, value)) = filter.split_once('=') else {
+                return Err(format!("Invalid selector argument: {filter}"));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            let number = || {
+                value
+                    .parse::<f32>()
+                    .map_err(|_| format!("Invalid number for {key}: {value}"))
+            };
+            let negatable = || match value.strip_prefix('!') {
+                Some(v) => (true, v.to_owned()),
+                None => (false, value.to_owned()),
+            };
+            let f = &mut selector.filters;
+            match key {
+                "name" => f.name = Some(negatable()),
+                "type" => f.actor_type = Some(negatable()),
+                "r" => f.r = Some(number()?),
+                "rm" => f.rm = Some(number()?),
+                "c" => {
+                    f.c = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("Invalid count: {value}"))?,
+                    )
+                }
+                "x" => f.x = Some(number()?),
+                "y" => f.y = Some(number()?),
+                "z" => f.z = Some(number()?),
+                _ => return Err(format!("Unknown selector argument: {key}")),
+            }
+        }
+        Ok(selector)
+    }
+
+    pub fn resolve(&self, world: &World, sender: Entity) -> Vec<Entity> {
+        let entities = world.entities();
+        let positions = world.read_storage::<PositionComponent>();
+        let dimensions = world.read_storage::<DimensionComponent>();
+        let actor_types = world.read_storage::<ActorTypeComponent>();
+        let names = world.read_storage::<DisplayNameComponent>();
+        let online = world.read_resource::<OnlinePlayers>();
+        let f = &self.filters;
+
+        let sender_pos = positions.get(sender).map(|p| (p.x, p.y, p.z));
+        let origin = (
+            f.x.or(sender_pos.map(|p| p.0)),
+            f.y.or(sender_pos.map(|p| p.1)),
+            f.z.or(sender_pos.map(|p| p.2)),
+        );
+        let sender_dim = dimensions.get(sender).map(|d| d.0);
+
+        let mut matched: Vec<(Entity, f32)> = (&entities, &positions)
+            .join()
+            .filter_map(|(ent, pos)| {
+                let player = online.get(ent);
+                let actor_type = match player {
+                    Some(_) => PLAYER_TYPE,
+                    None => actor_types.get(ent)?.0.as_str(),
+                };
+                let players_only = matches!(
+                    self.kind,
+                    SelectorKind::AllPlayers | SelectorKind::NearestPlayer
+                );
+                if (players_only && player.is_none())
+                    || (self.kind == SelectorKind::Sender && ent != sender)
+                {
+                    return None;
+                }
+                if let Some((negated, expected)) = &f.actor_type {
+                    let expected = match expected.contains(':') {
+                        true => expected.clone(),
+                        false => format!("minecraft:{expected}"),
+                    };
+                    if (actor_type == expected) == *negated {
+                        return None;
+                    }
+                }
+                if let Some((negated, expected)) = &f.name {
+                    let name = player
+                        .map(|p| p.name.as_str())
+                        .or(names.get(ent).map(|n| n.0.as_str()));
+                    if name.is_some_and(|n| n.eq_ignore_ascii_case(expected)) == *negated {
+                        return None;
+                    }
+                }
+                let distance = match origin {
+                    (Some(x), Some(y), Some(z)) => {
+                        let (dx, dy, dz) = (pos.x - x, pos.y - y, pos.z - z);
+                        (dx * dx + dy * dy + dz * dz).sqrt()
+                    }
+                    _ => 0.0,
+                };
+                let ranged = f.r.is_some() || f.rm.is_some();
+                if ranged && sender_dim.is_some() && dimensions.get(ent).map(|d| d.0) != sender_dim
+                {
+                    return None;
+                }
+                if f.r.is_some_and(|r| distance > r) || f.rm.is_some_and(|rm| distance < rm) {
+                    return None;
+                }
+                Some((ent, distance))
+            })
+            .collect();
+
+        let count = match (self.kind, f.c) {
+            (_, Some(c)) => Some(c),
+            (SelectorKind::NearestPlayer, None) => Some(1),
+            _ => None,
+        };
+        let by_distance =
+            |a: &(Entity, f32), b: &(Entity, f32)| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal);
+        match count {
+            Some(c) if c < 0 => {
+                matched.sort_by(|a, b| by_distance(b, a));
+                matched.truncate(c.unsigned_abs() as usize);
+            }
+            Some(c) => {
+                matched.sort_by(by_distance);
+                matched.truncate(c as usize);
+            }
+            None => matched.sort_by_key(|(ent, _)| ent.id()),
+        }
+        matched.into_iter().map(|(ent, _)| ent).collect()
+    }
+}
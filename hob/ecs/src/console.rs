@@ -0,0 +1,54 @@
+use hob_server::console::{CompletionSet, ConsoleInput};
+use log::{info, warn};
+use specs::prelude::*;
+
+use crate::{
+    command::{execute, CommandRegistry},
+    player::resources::OnlinePlayers,
+};
+
+/// Lines run per tick, so pasting a long script doesn't stall the game.
+const MAX_LINES_PER_TICK: usize = 16;
+
+/// The entity console commands are sent by. It isn't a player, so it may run anything.
+pub struct ConsoleSender(pub Entity);
+
+pub fn init_console(world: &mut World, input: ConsoleInput) {
+    let sender = world.create_entity().build();
+    world.insert(ConsoleSender(sender));
+    world.insert(input);
+}
+
+/// Runs the commands typed since the last tick and refreshes what tab completion offers.
+pub fn handle_console(world: &World) {
+    let (lines, completions) = {
+        let Some(mut input) = world.try_fetch_mut::<ConsoleInput>() else {
+            return;
+        };
+        let lines: Vec<_> = std::iter::from_fn(|| input.lines.try_recv().ok())
+            .take(MAX_LINES_PER_TICK)
+            .collect();
+        (lines, input.completions.clone())
+    };
+    let sender = world.read_resource::<ConsoleSender>().0;
+    for line in lines {
+        match execute(world, sender, &line) {
+            Ok(output) if output.is_empty() => {}
+            Ok(output) => info!("{output}"),
+            Err(output) => warn!("{output}"),
+        }
+    }
+
+    let registry = world.read_resource::<CommandRegistry>();
+    let online = world.read_resource::<OnlinePlayers>();
+    let mut players: Vec<_> = online.iter().map(|player| player.name.clone()).collect();
+    players.sort();
+    completions.update(CompletionSet {
+        commands: registry
+            .iter()
+            .flat_map(|command| std::iter::once(&command.name).chain(&command.aliases))
+            .cloned()
+            .collect(),
+        players,
+    });
+}
@@ -0,0 +1,85 @@
+use hob_protocol::packet::{
+    disconnect::{DisconnectFailReason, DisconnectPacket},
+    login::ExtraUserdata,
+};
+use log::info;
+use specs::prelude::*;
+
+pub use hob_server::config::DuplicateLoginPolicy;
+use hob_server::outbound::OutboundSender;
+
+use crate::{
+    player::{components::connection::ConnectionStreamComponent, resources::OnlinePlayers},
+    plugin::Plugin,
+};
+
+/// Raised when `display_name` logs in with the XUID of the online player `existing`.
+/// `policy` is applied unless a plugin cancels the event, in which case its inverse is.
+pub struct DuplicateLoginEvent {
+    pub existing: Entity,
+    pub xuid: String,
+    pub display_name: String,
+    pub policy: DuplicateLoginPolicy,
+}
+
+/// Returns whether the login of `user` may go ahead.
+pub fn check_duplicate_login(
+    world: &World,
+    user: &ExtraUserdata,
+    packet_to_client: &OutboundSender,
+) -> bool {
+    // Offline-mode accounts have no XUID to compare.
+    if user.xuid.is_empty() {
+        return true;
+    }
+    let Some(existing) = world
+        .read_resource::<OnlinePlayers>()
+        .by_xuid(&user.xuid)
+        .map(|p| p.entity)
+    else {
+        return true;
+    };
+    // The old session is already on its way out.
+    if world
+        .read_storage::<ConnectionStreamComponent>()
+        .get(existing)
+        .is_some_and(|conn| conn.is_closing())
+    {
+        return true;
+    }
+    let ev = DuplicateLoginEvent {
+        existing,
+        xuid: user.xuid.clone(),
+        display_name: user.display_name.clone(),
+        policy: *world.read_resource::<DuplicateLoginPolicy>(),
+    };
+    let policy = if world
+        .write_resource::<Plugin<DuplicateLoginEvent>>()
+        .run(&ev, world)
+    {
+        ev.policy.inverse()
+    } else {
+        ev.policy
+    };
+
+    let mut conns = world.write_storage::<ConnectionStreamComponent>();
+    // A session accepted earlier in the same batch has no connection yet and can't be kicked.
+    let policy = match conns.get_mut(existing) {
+        Some(conn) if policy == DuplicateLoginPolicy::KickOld => {
+            conn.kick(DisconnectFailReason::LoggedInOtherLocation);
+            policy
+        }
+        _ => DuplicateLoginPolicy::RejectNew,
+    };
+    drop(conns);
+    info!(
+        "Duplicate login for {}, xuid:{}: {:?}",
+        ev.display_name, ev.xuid, policy
+    );
+    if policy == DuplicateLoginPolicy::RejectNew {
+        let packet = DisconnectPacket::from(DisconnectFailReason::LoggedInOtherLocation);
+        let _ = packet_to_client.send(packet.into());
+        return false;
+    }
+    true
+}
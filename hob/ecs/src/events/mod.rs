@@ -1,15 +1,34 @@
-use self::{packet_recv::PacketRecvEvent, player_join::PlayerJoinEvent};
+use self::{
+    duplicate_login::{DuplicateLoginEvent, DuplicateLoginPolicy},
+    movement_violation::{MovementViolationEvent, MovementViolationQueue},
+    packet_recv::PacketRecvEvent,
+    player_join::PlayerJoinEvent,
+    player_quit::PlayerQuitEvent,
+    server_shutdown::ServerShutdownEvent,
+};
 use crate::plugin::Plugin;
 use specs::prelude::*;
+pub mod duplicate_login;
+pub mod movement_violation;
 pub mod packet_recv;
 pub mod player_join;
+pub mod player_quit;
+pub mod server_shutdown;
 
 pub fn init_events(world: &mut specs::World, dispatcher: &mut specs::DispatcherBuilder) {
     world.insert::<Plugin<PlayerJoinEvent>>(Plugin::new());
+    world.insert::<Plugin<PlayerQuitEvent>>(Plugin::new());
+    world.insert::<Plugin<ServerShutdownEvent>>(Plugin::new());
+    world.insert::<Plugin<DuplicateLoginEvent>>(Plugin::new());
+    world.insert(DuplicateLoginPolicy::default());
     world.insert::<Plugin<PacketRecvEvent>>(Plugin::new());
+    world.insert::<Plugin<MovementViolationEvent>>(Plugin::new());
+    world.insert(MovementViolationQueue::default());
 }
 
 pub(super) fn handle_events(world: &World) {
     player_join::accept_new_player(world);
+    player_quit::handle_player_quit(world);
     packet_recv::recv_packet(world);
+    movement_violation::handle_movement_violations(world);
 }
@@ -0,0 +1,117 @@
+use hob_protocol::packet::{
+    correct_player_move_prediction::CorrectPlayerMovePredictionPacket,
+    move_player::{MoveMode, MovePlayerPacket},
+    start_game::MovementAuthority,
+};
+use log::info;
+use specs::prelude::*;
+
+use crate::{
+    player::{
+        components::{
+            connection::ConnectionStreamComponent, MovementHistoryComponent,
+            MovementStateComponent, PositionComponent, RotationComponent,
+        },
+        validation::MovementViolation,
+    },
+    plugin::Plugin,
+    world::{components::RuntimeIdComponent, resources::LevelResource},
+};
+
+/// Raised when server-authoritative input fails validation.
+/// Cancelling it accepts the move instead of rewinding the player.
+pub struct MovementViolationEvent {
+    pub entity: Entity,
+    pub violation: MovementViolation,
+    pub from: (f32, f32, f32),
+    pub to: (f32, f32, f32),
+    pub tick: u64,
+}
+
+/// Violations found while handling packets, run once the packet storages are released.
+#[derive(Default)]
+pub struct MovementViolationQueue(pub Vec<MovementViolationEvent>);
+
+pub(super) fn handle_movement_violations(world: &World) {
+    let evs = std::mem::take(&mut world.write_resource::<MovementViolationQueue>().0);
+    if evs.is_empty() {
+        return;
+    }
+    let mut violation_ev = world.write_resource::<Plugin<MovementViolationEvent>>();
+    for ev in evs {
+        let cancelled = violation_ev.run(&ev, world);
+        if !world.entities().is_alive(ev.entity) {
+            continue;
+        }
+        if cancelled {
+            accept(world, &ev);
+        } else {
+            info!("Rewinding entity {:?}: {:?}", ev.entity, ev.violation);
+            rewind(world, &ev);
+        }
+    }
+}
+
+fn accept(world: &World, ev: &MovementViolationEvent) {
+    let mut positions = world.write_storage::<PositionComponent>();
+    let mut states = world.write_storage::<MovementStateComponent>();
+    let mut histories = world.write_storage::<MovementHistoryComponent>();
+    let (Some(pos), Some(state), Some(history)) = (
+        positions.get_mut(ev.entity),
+        states.get_mut(ev.entity),
+        histories.get_mut(ev.entity),
+    ) else {
+        return;
+    };
+    let (x, y, z) = ev.to;
+    *pos = PositionComponent::new(x, y, z);
+    history.push(ev.tick, ev.to);
+    state.awaiting_correction = false;
+    state.moved = true;
+}
+
+/// Puts the client back where the server last accepted it: through the rewind
+/// history when the client keeps one, otherwise with a `MovePlayer` reset.
+fn rewind(world: &World, ev: &MovementViolationEvent) {
+    let authority = world.read_resource::<LevelResource>().movement_authority;
+    let mut conns = world.write_storage::<ConnectionStreamComponent>();
+    let positions = world.read_storage::<PositionComponent>();
+    let rotations = world.read_storage::<RotationComponent>();
+    let runtime_ids = world.read_storage::<RuntimeIdComponent>();
+    let histories = world.read_storage::<MovementHistoryComponent>();
+    let mut states = world.write_storage::<MovementStateComponent>();
+    let (Some(conn), Some(pos), Some(rot), Some(runtime_id), Some(history), Some(state)) = (
+        conns.get_mut(ev.entity),
+        positions.get(ev.entity),
+        rotations.get(ev.entity),
+        runtime_ids.get(ev.entity),
+        histories.get(ev.entity),
+        states.get_mut(ev.entity),
+    ) else {
+        return;
+    };
+    state.awaiting_correction = false;
+    match (authority, history.before(ev.tick)) {
+        (MovementAuthority::ServerWithRewind, Some((_, position))) => {
+            conn.send_packet(CorrectPlayerMovePredictionPacket {
+                position,
+                delta: (0.0, 0.0, 0.0),
+                on_ground: state.on_ground,
+                tick: ev.tick,
+            });
+        }
+        _ => {
+            conn.send_packet(MovePlayerPacket {
+                runtime_id: runtime_id.0,
+                position: (pos.x, pos.y, pos.z),
+                pitch: rot.pitch,
+                yaw: rot.yaw,
+                head_yaw: rot.head_yaw,
+                mode: MoveMode::Reset,
+                on_ground: state.on_ground,
+                ridden_runtime_id: 0,
+                tick: ev.tick,
+            });
+        }
+    }
+}
@@ -1,12 +1,19 @@
 use hob_protocol::packet::PacketKind;
-use log::info;
+use hob_server::initial_handler::LoginState;
 use specs::prelude::*;
-use tokio::sync::mpsc::error::TryRecvError;
 
 use crate::{
-    player::components::connection::ConnectionStreamComponent,
+    command::CommandQueue,
+    player::{
+        components::{
+            chunk::ChunkRadiusComponent,
+            connection::{ConnectionStreamComponent, LoginStateComponent},
+        },
+        movement::{handle_auth_input, handle_move_player},
+        spawn::start_game,
+    },
     plugin::Plugin,
-    world::components::RuntimeIdComponent,
+    world::{components::RuntimeIdComponent, resources::LevelResource},
 };
 
 pub struct PacketRecvEvent {
@@ -23,23 +30,20 @@ pub(super) fn recv_packet(world: &World) {
     let mut conns = world.write_storage::<ConnectionStreamComponent>();
     let mut packet_ev = world.write_resource::<Plugin<PacketRecvEvent>>();
     let entities = world.entities();
-    let mut evs:Vec<PacketRecvEvent> = Vec::new();
-    (&mut conns, &entities)
-        .join()
-        .for_each(|(conn, ent)| {
-            let packets = conn.try_recv_many_packets(32);
-            if let Err(e) = packets {
-                if e == TryRecvError::Disconnected {
-                    info!("Client disconnected: {}", conn.name);
-                    entities.delete(ent).unwrap();
-                }
-                return;
-            }
-            for packet in packets.unwrap() {
-                let ev = PacketRecvEvent::new(ent, packet);
-                evs.push(ev);
-            }
-        });
+    let mut evs: Vec<PacketRecvEvent> = Vec::new();
+    (&mut conns, &entities).join().for_each(|(conn, ent)| {
+        if conn.is_closing() {
+            return;
+        }
+        // A closed channel is reported through `PlayerQuitEvent` instead.
+        let Ok(packets) = conn.try_recv_many_packets(32) else {
+            return;
+        };
+        for packet in packets {
+            let ev = PacketRecvEvent::new(ent, packet);
+            evs.push(ev);
+        }
+    });
     drop(conns);
 
     for ev in evs {
@@ -50,24 +54,14 @@ pub(super) fn recv_packet(world: &World) {
     }
 }
 
-fn handle_packets(
-    packet: PacketKind,
-    world: &World,
-    ent: Entity,
-) {
+fn handle_packets(packet: PacketKind, world: &World, ent: Entity) {
     use hob_protocol::packet::{
-        play_status::PlayStatusPacket, resource_pack_info::ResourcePacksInfoPacket,
-        resource_pack_response::ResponseStatus, resource_pack_stack::ResourcePacksStackPacket,
+        chunk_radius_update::ChunkRadiusUpdatePacket, resource_pack_response::ResponseStatus,
+        resource_pack_stack::ResourcePacksStackPacket,
     };
     let mut conns = world.write_storage::<ConnectionStreamComponent>();
     let conn = conns.get_mut(ent).unwrap();
     match packet {
-        PacketKind::ClientToServerHandshake(_) => {
-            conn.send_packet(PlayStatusPacket::LoginSuccess);
-            let resource_info = ResourcePacksInfoPacket::default();
-            conn.send_packet(resource_info);
-        }
-
         PacketKind::ResourcePackClientResponse(v) => match v.response_status {
             ResponseStatus::None | ResponseStatus::Refused => {}
             ResponseStatus::SendPacks => {}
@@ -83,8 +77,26 @@ fn handle_packets(
                     "Resource pack response completed for entity {}",
                     runtime_id.0
                 );
+                start_game(world, ent, conn);
+                if let Some(login) = world.write_storage::<LoginStateComponent>().get_mut(ent) {
+                    login.advance(LoginState::Spawning);
+                }
             }
         },
+        PacketKind::MovePlayer(v) => handle_move_player(world, ent, v),
+        PacketKind::PlayerAuthInput(v) => handle_auth_input(world, ent, conn, v),
+        PacketKind::CommandRequest(v) => world.write_resource::<CommandQueue>().0.push((ent, v)),
+        PacketKind::RequestChunkRadius(v) => {
+            let max = world.read_resource::<LevelResource>().view_distance;
+            let radius = v.chunk_radius.clamp(1, max);
+            conn.send_packet(ChunkRadiusUpdatePacket {
+                chunk_radius: radius,
+            });
+            world
+                .write_storage::<ChunkRadiusComponent>()
+                .insert(ent, ChunkRadiusComponent(radius))
+                .unwrap();
+        }
         _ => {}
     }
 }
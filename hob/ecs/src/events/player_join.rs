@@ -1,15 +1,22 @@
-use hob_protocol::packet::login::ExtraUserdata;
-use hob_server::{player_registry::PlayerRegistry, Server};
-use log::info;
+use hob_protocol::packet::{login::ExtraUserdata, player_list::Skin};
+use hob_server::{initial_handler::LoginState, player_registry::PlayerRegistry, Server};
+use log::{info, warn};
 use specs::prelude::*;
+use uuid::Uuid;
 
+use super::duplicate_login::check_duplicate_login;
 use crate::{
-    player::components::{
-        connection::{ConnectionAddressComponent, ConnectionStreamComponent},
-        DisplayNameComponent, XUIDComponent,
+    player::{
+        components::{
+            connection::{
+                ConnectionAddressComponent, ConnectionStreamComponent, LoginStateComponent,
+            },
+            DisplayNameComponent, XUIDComponent,
+        },
+        resources::{OnlinePlayer, OnlinePlayers},
     },
     plugin::Plugin,
-    world::{components::RuntimeIdComponent, resources::EntityCountResource},
+    world::resources::EntityIdAllocator,
 };
 
 pub struct PlayerJoinEvent {
@@ -23,7 +30,7 @@ impl PlayerJoinEvent {
 
 pub(super) fn accept_new_player(world: &World) {
     let mut server = world.write_resource::<Server>();
-    let mut count = world.write_resource::<EntityCountResource>();
+    let mut ids = world.write_resource::<EntityIdAllocator>();
     let mut join_ev = world.write_resource::<Plugin<PlayerJoinEvent>>();
     let updater = world.read_resource::<LazyUpdate>();
     let entities = world.entities();
@@ -32,9 +39,13 @@ pub(super) fn accept_new_player(world: &World) {
         packet_from_client,
         packet_to_client,
         user,
-        ..
+        skin,
+        disconnect,
     } in server.accept_players(32)
     {
+        if !check_duplicate_login(world, &user, &packet_to_client) {
+            continue;
+        }
         let entity = updater.create_entity(&entities).build();
         let ev = PlayerJoinEvent::new(user);
         if join_ev.run(&ev, world) {
@@ -43,17 +54,49 @@ pub(super) fn accept_new_player(world: &World) {
         }
 
         let ExtraUserdata {
-            xuid, display_name, ..
+            xuid,
+            display_name,
+            identity,
+            ..
         } = ev.user;
-        count.0 += 1;
+        let uuid = Uuid::parse_str(&identity).unwrap_or_else(|e| {
+            warn!("Invalid identity {identity:?} for {display_name}: {e}");
+            Uuid::nil()
+        });
+        let (runtime_id, unique_id) = ids.allocate();
+        let player_skin = Skin::try_from(skin.as_ref()).unwrap_or_else(|e| {
+            warn!("Invalid skin for {display_name}: {e}");
+            Skin::default()
+        });
+        // Fetched per player so join plugins can still read the registry.
+        world
+            .write_resource::<OnlinePlayers>()
+            .insert(OnlinePlayer {
+                entity,
+                xuid: xuid.clone(),
+                uuid,
+                name: display_name.clone(),
+                runtime_id: runtime_id.0,
+                unique_id: unique_id.0,
+                skin: player_skin,
+                device_id: skin.device_id.clone(),
+                build_platform: skin.device_os as i32,
+            });
         info!("Player connected: {display_name}, xuid:{xuid}");
         updater.insert(
             entity,
-            ConnectionStreamComponent::new(packet_from_client, packet_to_client, &display_name),
+            ConnectionStreamComponent::new(
+                packet_from_client,
+                packet_to_client,
+                disconnect,
+                &display_name,
+            ),
         );
+        updater.insert(entity, LoginStateComponent::new(LoginState::ResourcePacks));
         updater.insert(entity, ConnectionAddressComponent(address));
         updater.insert(entity, DisplayNameComponent(display_name));
         updater.insert(entity, XUIDComponent(xuid));
-        updater.insert(entity, RuntimeIdComponent(count.0));
+        updater.insert(entity, runtime_id);
+        updater.insert(entity, unique_id);
     }
 }
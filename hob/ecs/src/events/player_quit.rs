@@ -0,0 +1,56 @@
+use hob_protocol::packet::disconnect::DisconnectPacket;
+use log::info;
+use specs::prelude::*;
+
+use crate::{
+    player::{
+        components::connection::{ConnectionStreamComponent, QuitReason},
+        resources::OnlinePlayers,
+    },
+    plugin::Plugin,
+};
+
+/// Raised once per player when they leave, before the entity is deleted.
+pub struct PlayerQuitEvent {
+    pub entity: Entity,
+    pub reason: QuitReason,
+}
+impl PlayerQuitEvent {
+    pub fn new(entity: Entity, reason: QuitReason) -> Self {
+        PlayerQuitEvent { entity, reason }
+    }
+}
+
+/// Disconnects `entity` with `reason`. The quit is processed on the next tick.
+pub fn kick(world: &World, entity: Entity, reason: impl Into<DisconnectPacket>) {
+    if let Some(conn) = world
+        .write_storage::<ConnectionStreamComponent>()
+        .get_mut(entity)
+    {
+        conn.kick(reason);
+    }
+}
+
+pub(crate) fn handle_player_quit(world: &World) {
+    let mut conns = world.write_storage::<ConnectionStreamComponent>();
+    let entities = world.entities();
+    let evs: Vec<(PlayerQuitEvent, String)> = (&mut conns, &entities)
+        .join()
+        .filter_map(|(conn, ent)| {
+            let reason = conn.poll_quit()?;
+            Some((PlayerQuitEvent::new(ent, reason), conn.name.clone()))
+        })
+        .collect();
+    drop(conns);
+    if evs.is_empty() {
+        return;
+    }
+
+    let mut quit_ev = world.write_resource::<Plugin<PlayerQuitEvent>>();
+    for (ev, name) in evs {
+        quit_ev.run(&ev, world);
+        info!("Player disconnected: {name}, reason:{:?}", ev.reason);
+        world.write_resource::<OnlinePlayers>().remove(ev.entity);
+        entities.delete(ev.entity).unwrap();
+    }
+}
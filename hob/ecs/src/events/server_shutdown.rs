@@ -0,0 +1,6 @@
+/// Raised once when the server shuts down, after everyone has been disconnected and before
+/// anything is saved. Cancelling it has no effect.
+pub struct ServerShutdownEvent {
+    /// Shown to the players that were disconnected, if not the default.
+    pub message: Option<String>,
+}
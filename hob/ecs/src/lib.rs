@@ -1,28 +1,50 @@
+pub mod command;
+pub mod console;
 pub mod events;
+pub mod permission;
 pub mod player;
 pub mod plugin;
+mod query;
+pub mod rcon;
+pub mod shutdown;
 pub mod world;
 
 use plugin::{Plugin, PluginSys};
 pub use specs::prelude::*;
 
-use events::{handle_events, init_events};
-use hob_server::Server;
-use player::{handle_player, init_player};
-use world::{handle_world, init_world};
+use command::{handle_commands, init_command, Command, CommandRunNow};
+use console::{handle_console, init_console};
+use events::{duplicate_login::DuplicateLoginPolicy, handle_events, init_events};
+use hob_server::{config::ServerConfig, console::ConsoleInput, rcon::RconCommands, Server};
+use permission::init_permission;
+use player::{handle_player, init_player, netstat::init_netstat};
+use query::{add_plugin_name, handle_query};
+use rcon::{handle_rcon, init_rcon};
+use shutdown::{init_shutdown, ShutdownHandle, ShutdownRequest};
+use world::{handle_world, init_world, insert_level, resources::LevelResource};
 
 pub struct Game {
     world: World,
     dispatcher: Dispatcher<'static, 'static>,
 }
 impl Game {
-    pub fn new(server: Server) -> Self {
+    pub fn new(server: Server, config: &ServerConfig) -> Self {
         let mut world = World::new();
         world.insert(server);
         let mut dispatcher = DispatcherBuilder::new();
         init_player(&mut world, &mut dispatcher);
-        init_world(&mut world, &mut dispatcher);
+        init_world(
+            &mut world,
+            &mut dispatcher,
+            &config.level,
+            &config.files.level,
+        );
         init_events(&mut world, &mut dispatcher);
+        init_command(&mut world);
+        init_shutdown(&mut world);
+        init_netstat(&mut world);
+        init_permission(&mut world, &config.files.permissions);
+        world.insert(config.players.duplicate_login);
         Game {
             world,
             dispatcher: dispatcher.build(),
@@ -33,13 +55,58 @@ impl Game {
         handle_player(&self.world);
         handle_world(&self.world);
         handle_events(&self.world);
+        handle_commands(&self.world);
+        handle_console(&self.world);
+        handle_rcon(&self.world);
+        handle_query(&self.world);
         self.world.maintain();
     }
 
+    /// Runs lines from `input` as commands of the server console.
+    pub fn attach_console(&mut self, input: ConsoleInput) {
+        init_console(&mut self.world, input);
+    }
+
+    /// Runs commands received over RCON.
+    pub fn attach_rcon(&mut self, commands: RconCommands) {
+        init_rcon(&mut self.world, commands);
+    }
+
+    /// Lets other tasks, e.g. a signal handler, ask the game loop to stop.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle::clone(&self.world.read_resource())
+    }
+
+    pub fn shutdown_requested(&self) -> Option<ShutdownRequest> {
+        self.world.read_resource::<ShutdownHandle>().requested()
+    }
+
+    /// See [`shutdown::shutdown`].
+    pub fn shutdown(&mut self, message: Option<&str>) {
+        shutdown::shutdown(&self.world, message);
+        self.world.maintain();
+    }
+
+    pub fn set_level(&mut self, level: LevelResource) {
+        insert_level(&mut self.world, level);
+    }
+
+    pub fn set_duplicate_login_policy(&mut self, policy: DuplicateLoginPolicy) {
+        self.world.insert(policy);
+    }
+
     pub fn add_plugin<T, E: Send + Sync + 'static>(&mut self, plugin: T)
     where
         T: for<'a> PluginSys<'a, E> + Send + Sync + 'static,
     {
         self.world.write_resource::<Plugin<E>>().add_plugin(plugin);
+        add_plugin_name::<T>(&self.world);
+    }
+
+    pub fn register_command<T>(&mut self, command: Command, executor: T)
+    where
+        T: for<'a> CommandRunNow<'a> + Send + Sync + 'static,
+    {
+        command::register_command(&self.world, command, executor);
     }
 }
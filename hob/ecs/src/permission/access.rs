@@ -0,0 +1,260 @@
+use hob_server::{
+    access::{parse_duration, unix_now, AccessControl, BanEntry, BanTarget, IpNet},
+    Server,
+};
+use specs::prelude::*;
+
+use crate::{
+    command::{
+        args::{Param, ParamKind},
+        register_command, Command, CommandContext, CommandResult, CommandSys,
+    },
+    player::{
+        components::connection::{ConnectionAddressComponent, ConnectionStreamComponent},
+        resources::OnlinePlayers,
+    },
+};
+
+type AccessData<'a> = (
+    ReadExpect<'a, Server>,
+    ReadExpect<'a, OnlinePlayers>,
+    ReadStorage<'a, ConnectionAddressComponent>,
+    WriteStorage<'a, ConnectionStreamComponent>,
+);
+
+/// Kicks online players the lists no longer admit. Returns their names.
+fn enforce(
+    access: &AccessControl,
+    online: &OnlinePlayers,
+    addrs: &ReadStorage<ConnectionAddressComponent>,
+    conns: &mut WriteStorage<ConnectionStreamComponent>,
+) -> Vec<String> {
+    let mut kicked = Vec::new();
+    for player in online.iter() {
+        let (Some(addr), Some(conn)) = (addrs.get(player.entity), conns.get_mut(player.entity))
+        else {
+            continue;
+        };
+        if let Some(packet) = access.check(&player.xuid, &player.name, addr.0.ip()) {
+            conn.kick(packet);
+            kicked.push(player.name.clone());
+        }
+    }
+    kicked
+}
+
+fn with_kicked(message: String, kicked: Vec<String>) -> String {
+    match kicked.is_empty() {
+        true => message,
+        false => format!("{message}; kicked {}", kicked.join(", ")),
+    }
+}
+
+struct WhitelistCommand;
+impl<'a> CommandSys<'a> for WhitelistCommand {
+    type SystemData = AccessData<'a>;
+
+    fn run(
+        &mut self,
+        ctx: &CommandContext,
+        (server, online, addrs, mut conns): Self::SystemData,
+    ) -> CommandResult {
+        let access = &server.access;
+        let error = |e: anyhow::Error| format!("{e:#}");
+        let message = match ctx.args.string("action").unwrap_or_default() {
+            "on" => {
+                access.set_whitelist_enabled(true).map_err(error)?;
+                "Whitelist enabled".to_owned()
+            }
+            "off" => {
+                access.set_whitelist_enabled(false).map_err(error)?;
+                return Ok("Whitelist disabled".to_owned());
+            }
+            "reload" => {
+                access.reload().map_err(error)?;
+                "Reloaded the whitelist and ban list".to_owned()
+            }
+            "list" => {
+                let names: Vec<_> = access.whitelist().into_iter().map(|e| e.name).collect();
+                return Ok(format!(
+                    "{} whitelisted player(s): {}",
+                    names.len(),
+                    names.join(", ")
+                ));
+            }
+            "add" => {
+                let name = ctx.args.string("player").unwrap_or_default();
+                let (name, xuid) = online
+                    .by_name(name)
+                    .map_or((name, ""), |p| (p.name.as_str(), p.xuid.as_str()));
+                return match access.whitelist_add(name, xuid).map_err(error)? {
+                    true => Ok(format!("Added {name} to the whitelist")),
+                    false => Err(format!("{name} is already whitelisted")),
+                };
+            }
+            "remove" => {
+                let name = ctx.args.string("player").unwrap_or_default();
+                if !access.whitelist_remove(name).map_err(error)? {
+                    return Err(format!("{name} is not whitelisted"));
+                }
+                format!("Removed {name} from the whitelist")
+            }
+            action => return Err(format!("Unknown action {action}")),
+        };
+        let kicked = enforce(access, &online, &addrs, &mut conns);
+        Ok(with_kicked(message, kicked))
+    }
+}
+
+/// `/ban`, `/tempban` and `/ban-ip`.
+struct BanCommand;
+impl<'a> CommandSys<'a> for BanCommand {
+    type SystemData = AccessData<'a>;
+
+    fn run(
+        &mut self,
+        ctx: &CommandContext,
+        (server, online, addrs, mut conns): Self::SystemData,
+    ) -> CommandResult {
+        let expires = match ctx.args.string("duration") {
+            Some(duration) => {
+                let secs = parse_duration(duration).map_err(|e| e.to_string())?;
+                let expires = unix_now().checked_add(secs);
+                Some(expires.ok_or_else(|| format!("Duration too long: {duration}"))?)
+            }
+            None => None,
+        };
+        let reason = ctx.args.string("reason").unwrap_or_default().to_owned();
+        let (target, name) = if let Some(address) = ctx.args.string("address") {
+            // An online player's name bans the address they are connected from.
+            let net = match online.by_name(address).and_then(|p| addrs.get(p.entity)) {
+                Some(addr) => addr.0.ip().to_canonical().to_string().parse(),
+                None => address.parse::<IpNet>(),
+            };
+            (
+                BanTarget::Ip(net.map_err(|e| format!("{e:#}"))?),
+                String::new(),
+            )
+        } else {
+            let name = ctx.args.string("player").unwrap_or_default();
+            match online.by_name(name) {
+                Some(p) if !p.xuid.is_empty() => (BanTarget::Xuid(p.xuid.clone()), p.name.clone()),
+                Some(p) => (BanTarget::Name(p.name.clone()), p.name.clone()),
+                None => (BanTarget::Name(name.to_owned()), name.to_owned()),
+            }
+        };
+        let message = format!(
+            "Banned {}",
+            if name.is_empty() {
+                target.to_string()
+            } else {
+                name.clone()
+            }
+        );
+        server
+            .access
+            .ban(BanEntry {
+                target,
+                name,
+                reason,
+                expires,
+            })
+            .map_err(|e| format!("{e:#}"))?;
+        let kicked = enforce(&server.access, &online, &addrs, &mut conns);
+        Ok(with_kicked(message, kicked))
+    }
+}
+
+/// `/unban` and `/unban-ip`.
+struct UnbanCommand;
+impl<'a> CommandSys<'a> for UnbanCommand {
+    type SystemData = ReadExpect<'a, Server>;
+
+    fn run(&mut self, ctx: &CommandContext, server: Self::SystemData) -> CommandResult {
+        let error = |e: anyhow::Error| format!("{e:#}");
+        let (lifted, label) = match ctx.args.string("address") {
+            Some(address) => {
+                let net = address.parse::<IpNet>().map_err(error)?;
+                (
+                    server.access.unban(&BanTarget::Ip(net)).map_err(error)?,
+                    address,
+                )
+            }
+            None => {
+                let name = ctx.args.string("player").unwrap_or_default();
+                (server.access.unban_player(name).map_err(error)?, name)
+            }
+        };
+        match lifted {
+            true => Ok(format!("Unbanned {label}")),
+            false => Err(format!("{label} is not banned")),
+        }
+    }
+}
+
+pub(super) fn register(world: &World) {
+    let player = || Param::new("player", ParamKind::String);
+    let address = || Param::new("address", ParamKind::String);
+    let reason = || Param::new("reason", ParamKind::RawText).optional();
+    register_command(
+        world,
+        Command::new("whitelist", "Manages the server whitelist")
+            .permission("hob.command.whitelist")
+            .overload(vec![Param::new(
+                "action",
+                ParamKind::enumeration("WhitelistAction", &["on", "off", "list", "reload"]),
+            )])
+            .overload(vec![
+                Param::new(
+                    "action",
+                    ParamKind::enumeration("WhitelistEdit", &["add", "remove"]),
+                ),
+                player(),
+            ]),
+        WhitelistCommand,
+    );
+    register_command(
+        world,
+        Command::new("ban", "Bans a player from the server")
+            .permission("hob.command.ban")
+            .overload(vec![player(), reason()]),
+        BanCommand,
+    );
+    register_command(
+        world,
+        Command::new("tempban", "Bans a player for a while, e.g. 30m, 12h or 7d")
+            .permission("hob.command.ban")
+            .overload(vec![
+                player(),
+                Param::new("duration", ParamKind::String),
+                reason(),
+            ]),
+        BanCommand,
+    );
+    register_command(
+        world,
+        Command::new(
+            "ban-ip",
+            "Bans an address, CIDR block or a player's address",
+        )
+        .permission("hob.command.ban")
+        .overload(vec![address(), reason()]),
+        BanCommand,
+    );
+    register_command(
+        world,
+        Command::new("unban", "Lifts a player's ban")
+            .alias("pardon")
+            .permission("hob.command.unban")
+            .overload(vec![player()]),
+        UnbanCommand,
+    );
+    register_command(
+        world,
+        Command::new("unban-ip", "Lifts an address ban")
+            .alias("pardon-ip")
+            .permission("hob.command.unban")
+            .overload(vec![address()]),
+        UnbanCommand,
+    );
+}
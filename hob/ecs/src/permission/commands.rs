@@ -0,0 +1,68 @@
+use hob_protocol::packet::start_game::PermissionLevel;
+use specs::prelude::*;
+
+use super::Permissions;
+use crate::{
+    command::{
+        args::{Param, ParamKind},
+        register_command, Command, CommandContext, CommandResult, CommandSys,
+    },
+    player::resources::OnlinePlayers,
+};
+
+/// Sets the permission level of the targeted players.
+struct SetLevelCommand {
+    level: PermissionLevel,
+    verb: &'static str,
+}
+impl<'a> CommandSys<'a> for SetLevelCommand {
+    type SystemData = (WriteExpect<'a, Permissions>, ReadExpect<'a, OnlinePlayers>);
+
+    fn run(
+        &mut self,
+        ctx: &CommandContext,
+        (mut permissions, online): Self::SystemData,
+    ) -> CommandResult {
+        let players: Vec<_> = ctx
+            .args
+            .targets("player")
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|ent| online.get(*ent))
+            .collect();
+        if players.is_empty() {
+            return Err("No players matched".to_owned());
+        }
+        let mut names = Vec::new();
+        for player in players {
+            permissions
+                .set_level(&player.xuid, &player.name, self.level)
+                .map_err(|e| format!("{e:#}"))?;
+            names.push(player.name.as_str());
+        }
+        Ok(format!("{} {}", self.verb, names.join(", ")))
+    }
+}
+
+pub(super) fn register(world: &World) {
+    register_command(
+        world,
+        Command::new("op", "Grants operator status to a player")
+            .permission("hob.command.op")
+            .overload(vec![Param::new("player", ParamKind::Target)]),
+        SetLevelCommand {
+            level: PermissionLevel::Operator,
+            verb: "Opped",
+        },
+    );
+    register_command(
+        world,
+        Command::new("deop", "Revokes operator status from a player")
+            .permission("hob.command.deop")
+            .overload(vec![Param::new("player", ParamKind::Target)]),
+        SetLevelCommand {
+            level: PermissionLevel::Member,
+            verb: "De-opped",
+        },
+    );
+}
@@ -0,0 +1,355 @@
+pub mod access;
+pub mod commands;
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context};
+use hob_protocol::packet::{
+    add_player::{ability, command_permission, AbilityData, AbilityLayer},
+    start_game::{GameMode, PermissionLevel},
+    update_abilities::UpdateAbilitiesPacket,
+};
+use hob_server::{slots::PlayerSlots, Server};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use crate::{
+    command::CommandRegistry,
+    console::ConsoleSender,
+    player::{
+        components::connection::ConnectionStreamComponent,
+        resources::{OnlinePlayer, OnlinePlayers},
+    },
+    rcon::RconSender,
+};
+
+pub use hob_server::config::DEFAULT_PERMISSIONS_PATH;
+
+/// One line of the permissions file. Matched by XUID, or by name when the XUID is empty.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PermissionEntry {
+    #[serde(with = "level_name")]
+    pub permission: PermissionLevel,
+    #[serde(default)]
+    pub xuid: String,
+    #[serde(default)]
+    pub name: String,
+    /// Extra permission nodes on top of those of `permission`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nodes: Vec<String>,
+}
+impl PermissionEntry {
+    fn matches(&self, xuid: &str, name: &str) -> bool {
+        if self.xuid.is_empty() {
+            self.name.eq_ignore_ascii_case(name)
+        } else {
+            self.xuid == xuid
+        }
+    }
+}
+
+/// Permission levels and nodes of every player, backed by the ops file.
+pub struct Permissions {
+    path: Option<PathBuf>,
+    entries: Vec<PermissionEntry>,
+    default_level: PermissionLevel,
+    level_nodes: HashMap<PermissionLevel, Vec<String>>,
+    changed: bool,
+}
+impl Default for Permissions {
+    fn default() -> Self {
+        Permissions {
+            path: None,
+            entries: Vec::new(),
+            default_level: PermissionLevel::Member,
+            level_nodes: HashMap::from([(PermissionLevel::Operator, vec!["*".to_owned()])]),
+            changed: false,
+        }
+    }
+}
+impl Permissions {
+    /// Reads `path`, or starts empty if it doesn't exist yet. Changes are written back to it.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let entries = match std::fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text)
+                .with_context(|| format!("Invalid permissions file {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e).context(format!("Failed to read {}", path.display())),
+        };
+        Ok(Permissions {
+            path: Some(path.to_owned()),
+            entries,
+            ..Default::default()
+        })
+    }
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        let text = serde_json::to_string_pretty(&self.entries)?;
+        std::fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))
+    }
+    pub fn entries(&self) -> &[PermissionEntry] {
+        &self.entries
+    }
+    pub fn set_default_level(&mut self, level: PermissionLevel) {
+        self.default_level = level;
+        self.changed = true;
+    }
+    /// Nodes every player at `level` has.
+    pub fn set_level_nodes(&mut self, level: PermissionLevel, nodes: Vec<String>) {
+        self.level_nodes.insert(level, nodes);
+        self.changed = true;
+    }
+
+    pub fn level(&self, xuid: &str, name: &str) -> PermissionLevel {
+        self.entry(xuid, name)
+            .map_or(self.default_level, |e| e.permission)
+    }
+    pub fn player_level(&self, player: &OnlinePlayer) -> PermissionLevel {
+        self.level(&player.xuid, &player.name)
+    }
+    /// Whether `node` is granted, directly or by a `prefix.*` or `*` wildcard.
+    pub fn has(&self, xuid: &str, name: &str, node: &str) -> bool {
+        let entry_nodes = self.entry(xuid, name).map(|e| e.nodes.as_slice());
+        self.level_has(self.level(xuid, name), node)
+            || entry_nodes
+                .into_iter()
+                .flatten()
+                .any(|grant| node_matches(grant, node))
+    }
+    /// Whether every player at `level` has `node`.
+    pub fn level_has(&self, level: PermissionLevel, node: &str) -> bool {
+        self.level_nodes
+            .get(&level)
+            .into_iter()
+            .flatten()
+            .any(|grant| node_matches(grant, node))
+    }
+    pub fn player_has(&self, player: &OnlinePlayer, node: &str) -> bool {
+        self.has(&player.xuid, &player.name, node)
+    }
+
+    pub fn set_level(
+        &mut self,
+        xuid: &str,
+        name: &str,
+        level: PermissionLevel,
+    ) -> anyhow::Result<()> {
+        if level == PermissionLevel::Custom {
+            bail!("Custom is not a level players can be given");
+        }
+        match self.entries.iter_mut().find(|e| e.matches(xuid, name)) {
+            Some(entry) => entry.permission = level,
+            None => self.entries.push(PermissionEntry {
+                permission: level,
+                xuid: xuid.to_owned(),
+                name: name.to_owned(),
+                nodes: Vec::new(),
+            }),
+        }
+        self.prune();
+        self.commit()
+    }
+    pub fn grant(&mut self, xuid: &str, name: &str, node: &str) -> anyhow::Result<()> {
+        let default_level = self.default_level;
+        let entry = match self.entries.iter().position(|e| e.matches(xuid, name)) {
+            Some(i) => &mut self.entries[i],
+            None => {
+                self.entries.push(PermissionEntry {
+                    permission: default_level,
+                    xuid: xuid.to_owned(),
+                    name: name.to_owned(),
+                    nodes: Vec::new(),
+                });
+                self.entries.last_mut().unwrap()
+            }
+        };
+        if !entry.nodes.iter().any(|n| n == node) {
+            entry.nodes.push(node.to_owned());
+        }
+        self.commit()
+    }
+    pub fn revoke(&mut self, xuid: &str, name: &str, node: &str) -> anyhow::Result<()> {
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.matches(xuid, name)) {
+            entry.nodes.retain(|n| n != node);
+        }
+        self.prune();
+        self.commit()
+    }
+
+    /// Returns whether anything changed since the last call.
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    fn entry(&self, xuid: &str, name: &str) -> Option<&PermissionEntry> {
+        self.entries.iter().find(|e| e.matches(xuid, name))
+    }
+    /// Drops entries that say nothing beyond the defaults.
+    fn prune(&mut self) {
+        let default_level = self.default_level;
+        self.entries
+            .retain(|e| e.permission != default_level || !e.nodes.is_empty());
+    }
+    fn commit(&mut self) -> anyhow::Result<()> {
+        self.changed = true;
+        self.save()
+    }
+}
+
+fn node_matches(grant: &str, node: &str) -> bool {
+    grant == "*"
+        || grant == node
+        || grant.strip_suffix(".*").is_some_and(|prefix| {
+            node.strip_prefix(prefix)
+                .is_some_and(|v| v.starts_with('.'))
+        })
+}
+
+/// Whether `entity` may use `node`. The console may use anything and RCON what operators may;
+/// any other sender that isn't an online player, e.g. a player who is leaving, may use nothing.
+pub fn has_permission(world: &World, entity: Entity, node: &str) -> bool {
+    let online = world.read_resource::<OnlinePlayers>();
+    if let Some(player) = online.get(entity) {
+        return world
+            .read_resource::<Permissions>()
+            .player_has(player, node);
+    }
+    if world
+        .try_fetch::<RconSender>()
+        .is_some_and(|rcon| rcon.0 == entity)
+    {
+        return world
+            .read_resource::<Permissions>()
+            .level_has(RconSender::LEVEL, node);
+    }
+    world
+        .try_fetch::<ConsoleSender>()
+        .is_some_and(|console| console.0 == entity)
+}
+
+/// Abilities a player gets from their permission level and game mode.
+pub fn player_abilities(
+    unique_id: i64,
+    level: PermissionLevel,
+    game_mode: GameMode,
+) -> AbilityData {
+    let mut values = match level {
+        PermissionLevel::Visitor => 0,
+        _ => {
+            ability::BUILD
+                | ability::MINE
+                | ability::DOORS_AND_SWITCHES
+                | ability::OPEN_CONTAINERS
+                | ability::ATTACK_PLAYERS
+                | ability::ATTACK_MOBS
+        }
+    };
+    if level == PermissionLevel::Operator {
+        values |= ability::OPERATOR_COMMANDS | ability::TELEPORT;
+    }
+    match game_mode {
+        GameMode::Creative => {
+            values |= ability::MAY_FLY | ability::INSTANT_BUILD | ability::INVULNERABLE
+        }
+        GameMode::Adventure => values &= !(ability::BUILD | ability::MINE),
+        GameMode::Spectator | GameMode::SurvivalSpectator | GameMode::CreativeSpectator => {
+            values &=
+                !(ability::BUILD | ability::MINE | ability::ATTACK_PLAYERS | ability::ATTACK_MOBS);
+            values |= ability::MAY_FLY | ability::FLYING | ability::NO_CLIP | ability::INVULNERABLE;
+        }
+        _ => {}
+    }
+    AbilityData {
+        unique_id,
+        player_permission: level,
+        command_permission: match level {
+            PermissionLevel::Operator => command_permission::GAME_DIRECTORS,
+            _ => command_permission::NORMAL,
+        },
+        layers: vec![AbilityLayer {
+            layer_type: AbilityLayer::BASE,
+            abilities: AbilityLayer::ALL,
+            values,
+            fly_speed: AbilityLayer::DEFAULT_FLY_SPEED,
+            walk_speed: AbilityLayer::DEFAULT_WALK_SPEED,
+        }],
+    }
+}
+
+/// Sends `player` their abilities and the commands they may use.
+pub(crate) fn send_permissions(
+    conn: &mut ConnectionStreamComponent,
+    permissions: &Permissions,
+    registry: &CommandRegistry,
+    player: &OnlinePlayer,
+    game_mode: GameMode,
+) {
+    let level = permissions.player_level(player);
+    conn.send_packet(UpdateAbilitiesPacket {
+        ability_data: player_abilities(player.unique_id, level, game_mode),
+    });
+    conn.send_packet(registry.available_commands(|command| {
+        command
+            .permission
+            .as_ref()
+            .is_none_or(|node| permissions.player_has(player, node))
+    }));
+}
+
+/// Lets operators into the server's reserved slots.
+pub(crate) fn sync_reserved_slots(slots: &PlayerSlots, permissions: &Permissions) {
+    slots.set_privileged(
+        permissions
+            .entries()
+            .iter()
+            .filter(|e| e.permission == PermissionLevel::Operator)
+            .map(|e| (e.xuid.as_str(), e.name.as_str())),
+    );
+}
+
+pub fn init_permission(world: &mut World, path: impl AsRef<Path>) {
+    let permissions = Permissions::load(path).unwrap_or_else(|e| {
+        // Keep the broken file untouched rather than overwriting it on the next change.
+        error!("{e:#}; permissions will not be saved");
+        Permissions::default()
+    });
+    info!("Loaded {} permission entries", permissions.entries().len());
+    if let Some(server) = world.try_fetch::<Server>() {
+        sync_reserved_slots(&server.slots, &permissions);
+    }
+    world.insert(permissions);
+    commands::register(world);
+    access::register(world);
+}
+
+mod level_name {
+    use hob_protocol::packet::start_game::PermissionLevel;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(level: &PermissionLevel, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(match level {
+            PermissionLevel::Visitor => "visitor",
+            PermissionLevel::Member => "member",
+            PermissionLevel::Operator => "operator",
+            PermissionLevel::Custom => "custom",
+        })
+    }
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<PermissionLevel, D::Error> {
+        let name = String::deserialize(d)?;
+        match name.to_lowercase().as_str() {
+            "visitor" => Ok(PermissionLevel::Visitor),
+            "member" => Ok(PermissionLevel::Member),
+            "operator" => Ok(PermissionLevel::Operator),
+            _ => Err(D::Error::custom(format!(
+                "unknown permission level {name:?}"
+            ))),
+        }
+    }
+}
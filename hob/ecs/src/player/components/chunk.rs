@@ -0,0 +1,36 @@
+use std::collections::HashSet;
+
+use specs::Component;
+
+use crate::world::chunk::ChunkPos;
+
+/// View distance in chunks, as negotiated by `RequestChunkRadius`.
+pub struct ChunkRadiusComponent(pub i32);
+impl Component for ChunkRadiusComponent {
+    type Storage = specs::VecStorage<Self>;
+}
+
+/// Chunks already sent to the client and the center they were sent around.
+#[derive(Default)]
+pub struct ChunkViewComponent {
+    pub center: Option<ChunkPos>,
+    pub radius: i32,
+    pub sent: HashSet<ChunkPos>,
+    pub spawned: bool,
+}
+impl ChunkViewComponent {
+    pub fn in_radius(&self, pos: ChunkPos) -> bool {
+        match self.center {
+            Some(center) => in_radius(center, pos, self.radius),
+            None => false,
+        }
+    }
+}
+impl Component for ChunkViewComponent {
+    type Storage = specs::VecStorage<Self>;
+}
+
+pub fn in_radius(center: ChunkPos, pos: ChunkPos, radius: i32) -> bool {
+    let (dx, dz) = (pos.x - center.x, pos.z - center.z);
+    dx * dx + dz * dz <= radius * radius
+}
@@ -1,36 +1,144 @@
-use std::net::SocketAddr;
+use std::{net::SocketAddr, time::Instant};
 
-use hob_protocol::packet::PacketKind;
+use hob_protocol::packet::{
+    disconnect::{DisconnectFailReason, DisconnectPacket},
+    PacketKind,
+};
+use hob_server::{
+    connection_client::DisconnectReason,
+    initial_handler::LoginState,
+    outbound::{OutboundError, OutboundSender, OutboundStats},
+};
 use specs::Component;
-use tokio::sync::mpsc::{error::TryRecvError, Receiver, Sender};
+use tokio::sync::{
+    mpsc::{error::TryRecvError, Receiver},
+    oneshot,
+};
 
 pub struct ConnectionAddressComponent(pub SocketAddr);
 impl Component for ConnectionAddressComponent {
     type Storage = specs::VecStorage<Self>;
 }
 
+/// The login step a player is on once the game has them, until they have spawned.
+pub struct LoginStateComponent {
+    pub state: LoginState,
+    pub since: Instant,
+}
+impl LoginStateComponent {
+    pub fn new(state: LoginState) -> Self {
+        LoginStateComponent {
+            state,
+            since: Instant::now(),
+        }
+    }
+    /// Moves on to `state`, which gets a timeout of its own.
+    pub fn advance(&mut self, state: LoginState) {
+        *self = Self::new(state);
+    }
+    pub fn timed_out(&self) -> bool {
+        self.since.elapsed() > self.state.timeout()
+    }
+}
+impl Component for LoginStateComponent {
+    type Storage = specs::VecStorage<Self>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuitReason {
+    /// Removed by the server with this message.
+    Kicked(String),
+    /// The connection ended on its own.
+    Disconnected(DisconnectReason),
+}
+
 pub struct ConnectionStreamComponent {
     pub name: String,
     pub packet_from_client: Receiver<PacketKind>,
-    pub packet_to_client: Sender<PacketKind>,
+    /// `None` once the connection is closing; the writer flushes and closes the socket.
+    pub packet_to_client: Option<OutboundSender>,
+    disconnect: oneshot::Receiver<DisconnectReason>,
+    kicked: Option<String>,
 }
 impl ConnectionStreamComponent {
     pub fn new(
         packet_from_client: Receiver<PacketKind>,
-        packet_to_client: Sender<PacketKind>,
+        packet_to_client: OutboundSender,
+        disconnect: oneshot::Receiver<DisconnectReason>,
         name: &str,
     ) -> Self {
         ConnectionStreamComponent {
             packet_from_client,
-            packet_to_client,
+            packet_to_client: Some(packet_to_client),
+            disconnect,
+            kicked: None,
             name: name.to_owned(),
         }
     }
     pub fn send_packet(&mut self, packet: impl Into<PacketKind>) {
         let packet = packet.into();
+        let Some(sender) = self.packet_to_client.as_ref() else {
+            log::debug!("[{}] Dropped packet after close: {}", self.name, packet);
+            return;
+        };
         log::debug!("[{}] Send packet: {}", self.name, packet);
-        if let Err(e) = self.packet_to_client.try_send(packet) {
-            log::error!("Error sending packet: {:?}", e);
+        match sender.send(packet) {
+            Ok(()) => {}
+            Err(OutboundError::TooSlow) => self.kick_too_slow(),
+            Err(OutboundError::Closed) => log::debug!("[{}] Connection closed", self.name),
+        }
+    }
+    /// Kicks the player if packets have been backing up for too long.
+    pub fn check_too_slow(&mut self) -> bool {
+        let too_slow = self
+            .packet_to_client
+            .as_ref()
+            .is_some_and(|sender| sender.is_too_slow());
+        if too_slow {
+            self.kick_too_slow();
+        }
+        too_slow
+    }
+    fn kick_too_slow(&mut self) {
+        log::warn!("[{}] Disconnected for falling behind", self.name);
+        // What is queued would only hold the disconnect up.
+        if let Some(sender) = self.packet_to_client.as_ref() {
+            sender.clear();
+        }
+        self.kick(DisconnectPacket::new(
+            DisconnectFailReason::Timeout,
+            "Your connection is too slow",
+        ));
+    }
+    /// Depth of the queue to the client, `None` once closing.
+    pub fn outbound_stats(&self) -> Option<OutboundStats> {
+        self.packet_to_client.as_ref().map(|sender| sender.stats())
+    }
+    /// Sends `Disconnect` and closes the connection once it has been delivered.
+    pub fn kick(&mut self, packet: impl Into<DisconnectPacket>) {
+        let packet = packet.into();
+        let message = packet
+            .message
+            .clone()
+            .unwrap_or_else(|| format!("{:?}", packet.reason));
+        self.send_packet(packet);
+        self.packet_to_client = None;
+        self.kicked.get_or_insert(message);
+    }
+    pub fn is_closing(&self) -> bool {
+        self.packet_to_client.is_none()
+    }
+    /// Why the player left, once it is known.
+    pub fn poll_quit(&mut self) -> Option<QuitReason> {
+        if let Some(message) = self.kicked.take() {
+            return Some(QuitReason::Kicked(message));
+        }
+        match self.disconnect.try_recv() {
+            Ok(reason) => Some(QuitReason::Disconnected(reason)),
+            Err(oneshot::error::TryRecvError::Empty) => None,
+            Err(oneshot::error::TryRecvError::Closed) => Some(QuitReason::Disconnected(
+                DisconnectReason::Error("connection task ended".to_owned()),
+            )),
         }
     }
     pub fn try_recv_packet(&mut self) -> Result<PacketKind, TryRecvError> {
@@ -1,6 +1,9 @@
+pub mod chunk;
 pub mod connection;
 
-use specs::Component;
+use std::collections::{HashMap, VecDeque};
+
+use specs::{Component, Entity};
 
 pub struct DisplayNameComponent(pub String);
 impl Component for DisplayNameComponent {
@@ -11,3 +14,104 @@ pub struct XUIDComponent(pub String);
 impl Component for XUIDComponent {
     type Storage = specs::VecStorage<Self>;
 }
+
+pub struct PositionComponent {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+impl PositionComponent {
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        PositionComponent { x, y, z }
+    }
+    pub fn block_pos(&self) -> (i32, i32, i32) {
+        (
+            self.x.floor() as i32,
+            self.y.floor() as i32,
+            self.z.floor() as i32,
+        )
+    }
+}
+impl Component for PositionComponent {
+    type Storage = specs::VecStorage<Self>;
+}
+
+#[derive(Default)]
+pub struct RotationComponent {
+    pub pitch: f32,
+    pub yaw: f32,
+    pub head_yaw: f32,
+}
+impl Component for RotationComponent {
+    type Storage = specs::VecStorage<Self>;
+}
+
+#[derive(Default)]
+pub struct VelocityComponent {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+impl Component for VelocityComponent {
+    type Storage = specs::VecStorage<Self>;
+}
+
+/// Last movement reported by the client. `moved` is cleared once it has been broadcast.
+#[derive(Default)]
+pub struct MovementStateComponent {
+    pub on_ground: bool,
+    pub tick: u64,
+    pub moved: bool,
+    /// Set while a rejected input waits for `MovementViolationEvent` to be handled.
+    pub awaiting_correction: bool,
+    /// Tick of the last `PlayerAuthInput`, whether it moved the player or not.
+    pub input_tick: Option<u64>,
+}
+impl MovementStateComponent {
+    /// Records the tick of an input and returns how many ticks passed since the previous
+    /// one, so that standing still doesn't save up allowance for a later move.
+    pub fn input_ticks(&mut self, tick: u64) -> u64 {
+        self.input_tick
+            .replace(tick)
+            .map_or(1, |last| tick.saturating_sub(last))
+    }
+}
+impl Component for MovementStateComponent {
+    type Storage = specs::VecStorage<Self>;
+}
+
+/// Server-side positions of the last accepted inputs, used to rewind the client.
+pub struct MovementHistoryComponent {
+    pub entries: VecDeque<(u64, (f32, f32, f32))>,
+    pub capacity: usize,
+}
+impl MovementHistoryComponent {
+    pub fn new(capacity: usize) -> Self {
+        MovementHistoryComponent {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+    pub fn push(&mut self, tick: u64, position: (f32, f32, f32)) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((tick, position));
+    }
+    /// Latest accepted position from before `tick`.
+    pub fn before(&self, tick: u64) -> Option<(u64, (f32, f32, f32))> {
+        self.entries.iter().rev().find(|(t, _)| *t < tick).copied()
+    }
+}
+impl Component for MovementHistoryComponent {
+    type Storage = specs::VecStorage<Self>;
+}
+
+/// Entities spawned on this player's client, with the unique ID needed to remove them.
+#[derive(Default)]
+pub struct VisibleEntitiesComponent {
+    pub entities: HashMap<Entity, i64>,
+}
+impl Component for VisibleEntitiesComponent {
+    type Storage = specs::VecStorage<Self>;
+}
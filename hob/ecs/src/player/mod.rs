@@ -1,10 +1,23 @@
 pub mod components;
+pub mod movement;
+pub mod netstat;
+pub mod resources;
+pub mod spawn;
+pub mod systems;
+pub mod validation;
 
 use specs::{world, WorldExt};
 
 use self::components::{
-    connection::{ConnectionAddressComponent, ConnectionStreamComponent},
-    DisplayNameComponent, XUIDComponent,
+    chunk::{ChunkRadiusComponent, ChunkViewComponent},
+    connection::{ConnectionAddressComponent, ConnectionStreamComponent, LoginStateComponent},
+    DisplayNameComponent, MovementHistoryComponent, MovementStateComponent, PositionComponent,
+    RotationComponent, VelocityComponent, VisibleEntitiesComponent, XUIDComponent,
+};
+use self::resources::OnlinePlayers;
+use self::systems::{
+    login::LoginTimeoutSystem, movement::MovementBroadcastSystem, outbound::SlowClientSystem,
+    permission::PermissionSyncSystem, player_list::PlayerListSystem,
 };
 
 pub(crate) fn init_player(world: &mut world::World, dispatcher: &mut specs::DispatcherBuilder) {
@@ -12,6 +25,21 @@ pub(crate) fn init_player(world: &mut world::World, dispatcher: &mut specs::Disp
     world.register::<XUIDComponent>();
     world.register::<ConnectionStreamComponent>();
     world.register::<ConnectionAddressComponent>();
+    world.register::<LoginStateComponent>();
+    world.register::<PositionComponent>();
+    world.register::<ChunkRadiusComponent>();
+    world.register::<ChunkViewComponent>();
+    world.register::<RotationComponent>();
+    world.register::<VelocityComponent>();
+    world.register::<MovementStateComponent>();
+    world.register::<MovementHistoryComponent>();
+    world.register::<VisibleEntitiesComponent>();
+    world.insert(OnlinePlayers::default());
+    dispatcher.add(MovementBroadcastSystem, "movement_broadcast", &[]);
+    dispatcher.add(PlayerListSystem::default(), "player_list", &[]);
+    dispatcher.add(PermissionSyncSystem, "permission_sync", &[]);
+    dispatcher.add(LoginTimeoutSystem, "login_timeout", &[]);
+    dispatcher.add(SlowClientSystem, "slow_client", &[]);
 }
 
 pub(crate) fn handle_player(world: &world::World) {}
@@ -0,0 +1,189 @@
+use hob_protocol::packet::{
+    correct_player_move_prediction::CorrectPlayerMovePredictionPacket,
+    move_player::{MoveMode, MovePlayerPacket},
+    player_auth_input::{input_flag, PlayerAuthInputPacket},
+    set_actor_data::actor_flag,
+    start_game::MovementAuthority,
+};
+use specs::prelude::*;
+
+use super::{
+    components::{
+        chunk::ChunkViewComponent, connection::ConnectionStreamComponent, MovementHistoryComponent,
+        MovementStateComponent, PositionComponent, RotationComponent, VelocityComponent,
+    },
+    validation::{can_fly, validate_move, MoveContext, MAX_TICK_GAP},
+};
+use crate::{
+    events::movement_violation::{MovementViolationEvent, MovementViolationQueue},
+    world::{
+        chunk::ChunkPos,
+        components::{ActorMetadataComponent, RuntimeIdComponent},
+        resources::{ChunkStorageResource, LevelResource},
+    },
+};
+
+/// Client-authoritative movement: the reported position is taken as is.
+pub(crate) fn handle_move_player(world: &World, ent: Entity, packet: MovePlayerPacket) {
+    let authority = world.read_resource::<LevelResource>().movement_authority;
+    if !matches!(authority, MovementAuthority::Client) {
+        log::debug!("Ignoring MovePlayer under {:?} movement", authority);
+        return;
+    }
+    let mut positions = world.write_storage::<PositionComponent>();
+    let mut rotations = world.write_storage::<RotationComponent>();
+    let mut states = world.write_storage::<MovementStateComponent>();
+    let (Some(pos), Some(rot), Some(state)) = (
+        positions.get_mut(ent),
+        rotations.get_mut(ent),
+        states.get_mut(ent),
+    ) else {
+        return;
+    };
+    let (x, y, z) = packet.position;
+    *pos = PositionComponent::new(x, y, z);
+    *rot = RotationComponent {
+        pitch: packet.pitch,
+        yaw: packet.yaw,
+        head_yaw: packet.head_yaw,
+    };
+    state.on_ground = packet.on_ground;
+    state.tick = packet.tick;
+    state.moved = true;
+}
+
+/// Server-authoritative movement: input is validated against the server's view of the
+/// world. Moves into terrain the client has not been sent are corrected right away;
+/// other violations raise `MovementViolationEvent` and further input is ignored until
+/// it has been handled.
+pub(crate) fn handle_auth_input(
+    world: &World,
+    ent: Entity,
+    conn: &mut ConnectionStreamComponent,
+    packet: PlayerAuthInputPacket,
+) {
+    let level = world.read_resource::<LevelResource>();
+    if matches!(level.movement_authority, MovementAuthority::Client) {
+        return;
+    }
+    let mut positions = world.write_storage::<PositionComponent>();
+    let mut rotations = world.write_storage::<RotationComponent>();
+    let mut velocities = world.write_storage::<VelocityComponent>();
+    let mut states = world.write_storage::<MovementStateComponent>();
+    let mut histories = world.write_storage::<MovementHistoryComponent>();
+    let views = world.read_storage::<ChunkViewComponent>();
+    let (Some(pos), Some(rot), Some(vel), Some(state), Some(history), Some(view)) = (
+        positions.get_mut(ent),
+        rotations.get_mut(ent),
+        velocities.get_mut(ent),
+        states.get_mut(ent),
+        histories.get_mut(ent),
+        views.get(ent),
+    ) else {
+        return;
+    };
+    let ticks = state.input_ticks(packet.tick);
+    if state.awaiting_correction {
+        return;
+    }
+    state.tick = packet.tick;
+
+    let rotation_changed =
+        (rot.pitch, rot.yaw, rot.head_yaw) != (packet.pitch, packet.yaw, packet.head_yaw);
+    *rot = RotationComponent {
+        pitch: packet.pitch,
+        yaw: packet.yaw,
+        head_yaw: packet.head_yaw,
+    };
+    state.moved |= rotation_changed;
+    if let Some(metadata) = world.write_storage::<ActorMetadataComponent>().get_mut(ent) {
+        metadata.set_flag(actor_flag::SNEAKING, packet.has_flag(input_flag::SNEAKING));
+        metadata.set_flag(
+            actor_flag::SPRINTING,
+            packet.has_flag(input_flag::SPRINTING),
+        );
+    }
+
+    let (x, y, z) = packet.position;
+    let target = ChunkPos::from_block(x.floor() as i32, z.floor() as i32);
+    if !view.sent.contains(&target) {
+        if let Some(runtime_id) = world.read_storage::<RuntimeIdComponent>().get(ent) {
+            correct_movement(conn, level.movement_authority, runtime_id, pos, rot, state);
+        }
+        return;
+    }
+    let from = (pos.x, pos.y, pos.z);
+    if from == packet.position {
+        return;
+    }
+    let ctx = MoveContext {
+        from,
+        velocity_y: vel.y,
+        on_ground: state.on_ground,
+        ticks,
+        can_fly: can_fly(level.game_mode),
+    };
+    let storage = world.read_resource::<ChunkStorageResource>();
+    match validate_move(&storage, &ctx, packet.position) {
+        Ok(on_ground) => {
+            let ticks = ticks.clamp(1, MAX_TICK_GAP) as f32;
+            *vel = VelocityComponent {
+                x: (x - from.0) / ticks,
+                y: (y - from.1) / ticks,
+                z: (z - from.2) / ticks,
+            };
+            *pos = PositionComponent::new(x, y, z);
+            state.on_ground = on_ground;
+            state.moved = true;
+            history.push(packet.tick, packet.position);
+        }
+        Err(violation) => {
+            state.awaiting_correction = true;
+            world
+                .write_resource::<MovementViolationQueue>()
+                .0
+                .push(MovementViolationEvent {
+                    entity: ent,
+                    violation,
+                    from,
+                    to: packet.position,
+                    tick: packet.tick,
+                });
+        }
+    }
+}
+
+/// Tells the client to snap back to the server-side position. Only clients that keep a
+/// rewind history understand `CorrectPlayerMovePrediction`; the others are reset with
+/// `MovePlayer`.
+pub fn correct_movement(
+    conn: &mut ConnectionStreamComponent,
+    authority: MovementAuthority,
+    runtime_id: &RuntimeIdComponent,
+    pos: &PositionComponent,
+    rot: &RotationComponent,
+    state: &MovementStateComponent,
+) {
+    let position = (pos.x, pos.y, pos.z);
+    match authority {
+        MovementAuthority::ServerWithRewind => {
+            conn.send_packet(CorrectPlayerMovePredictionPacket {
+                position,
+                delta: (0.0, 0.0, 0.0),
+                on_ground: state.on_ground,
+                tick: state.tick,
+            })
+        }
+        _ => conn.send_packet(MovePlayerPacket {
+            runtime_id: runtime_id.0,
+            position,
+            pitch: rot.pitch,
+            yaw: rot.yaw,
+            head_yaw: rot.head_yaw,
+            mode: MoveMode::Reset,
+            on_ground: state.on_ground,
+            ridden_runtime_id: 0,
+            tick: state.tick,
+        }),
+    }
+}
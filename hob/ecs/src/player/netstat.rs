@@ -0,0 +1,45 @@
+use specs::prelude::*;
+
+use crate::{
+    command::{register_command, Command, CommandContext, CommandResult, CommandSys},
+    player::components::connection::ConnectionStreamComponent,
+};
+
+struct NetstatCommand;
+impl<'a> CommandSys<'a> for NetstatCommand {
+    type SystemData = ReadStorage<'a, ConnectionStreamComponent>;
+
+    fn run(&mut self, _ctx: &CommandContext, conns: Self::SystemData) -> CommandResult {
+        let mut lines: Vec<_> = conns
+            .join()
+            .filter_map(|conn| Some((conn.name.as_str(), conn.outbound_stats()?)))
+            .map(|(name, stats)| {
+                format!(
+                    "{name}: {} packets ({:.1} KiB) queued, peak {:.1} KiB, {} sent, {} dropped",
+                    stats.queued_packets,
+                    stats.queued_bytes as f64 / 1024.0,
+                    stats.peak_bytes as f64 / 1024.0,
+                    stats.sent_packets,
+                    stats.dropped_packets,
+                )
+            })
+            .collect();
+        if lines.is_empty() {
+            return Ok("No players are connected".to_owned());
+        }
+        lines.sort();
+        Ok(format!("Outbound queues:\n{}", lines.join("\n")))
+    }
+}
+
+pub fn init_netstat(world: &mut World) {
+    register_command(
+        world,
+        Command::new(
+            "netstat",
+            "Shows how far behind each player's connection is",
+        )
+        .permission("hob.command.netstat"),
+        NetstatCommand,
+    );
+}
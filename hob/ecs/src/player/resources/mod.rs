@@ -0,0 +1,86 @@
+use std::{collections::HashMap, hash::Hash};
+
+use hob_protocol::packet::player_list::Skin;
+use specs::Entity;
+use uuid::Uuid;
+
+pub struct OnlinePlayer {
+    pub entity: Entity,
+    pub xuid: String,
+    pub uuid: Uuid,
+    pub name: String,
+    pub runtime_id: u64,
+    pub unique_id: i64,
+    pub skin: Skin,
+    pub device_id: String,
+    pub build_platform: i32,
+}
+
+/// Players that have joined and not yet left, indexed by every ID the protocol uses.
+#[derive(Default)]
+pub struct OnlinePlayers {
+    players: HashMap<Entity, OnlinePlayer>,
+    by_xuid: HashMap<String, Entity>,
+    by_uuid: HashMap<Uuid, Entity>,
+    by_name: HashMap<String, Entity>,
+    by_runtime_id: HashMap<u64, Entity>,
+}
+impl OnlinePlayers {
+    pub fn insert(&mut self, player: OnlinePlayer) {
+        self.remove(player.entity);
+        let entity = player.entity;
+        self.by_xuid.insert(player.xuid.clone(), entity);
+        self.by_uuid.insert(player.uuid, entity);
+        self.by_name.insert(player.name.to_lowercase(), entity);
+        self.by_runtime_id.insert(player.runtime_id, entity);
+        self.players.insert(entity, player);
+    }
+    pub fn remove(&mut self, entity: Entity) -> Option<OnlinePlayer> {
+        let player = self.players.remove(&entity)?;
+        unlink(&mut self.by_xuid, &player.xuid, entity);
+        unlink(&mut self.by_uuid, &player.uuid, entity);
+        unlink(&mut self.by_name, &player.name.to_lowercase(), entity);
+        unlink(&mut self.by_runtime_id, &player.runtime_id, entity);
+        Some(player)
+    }
+    pub fn get(&self, entity: Entity) -> Option<&OnlinePlayer> {
+        self.players.get(&entity)
+    }
+    pub fn by_xuid(&self, xuid: &str) -> Option<&OnlinePlayer> {
+        self.get(*self.by_xuid.get(xuid)?)
+    }
+    pub fn by_uuid(&self, uuid: &Uuid) -> Option<&OnlinePlayer> {
+        self.get(*self.by_uuid.get(uuid)?)
+    }
+    /// Case-insensitive exact match on the display name.
+    pub fn by_name(&self, name: &str) -> Option<&OnlinePlayer> {
+        self.get(*self.by_name.get(&name.to_lowercase())?)
+    }
+    pub fn by_runtime_id(&self, runtime_id: u64) -> Option<&OnlinePlayer> {
+        self.get(*self.by_runtime_id.get(&runtime_id)?)
+    }
+    /// Players whose name starts with `prefix`, ignoring case.
+    pub fn search<'a>(&'a self, prefix: &str) -> impl Iterator<Item = &'a OnlinePlayer> + 'a {
+        let prefix = prefix.to_lowercase();
+        self.by_name
+            .iter()
+            .filter(move |(name, _)| name.starts_with(&prefix))
+            .filter_map(|(_, entity)| self.players.get(entity))
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &OnlinePlayer> {
+        self.players.values()
+    }
+    pub fn len(&self) -> usize {
+        self.players.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.players.is_empty()
+    }
+}
+
+/// Drops an index entry unless another player has since taken the key.
+fn unlink<K: Eq + Hash>(map: &mut HashMap<K, Entity>, key: &K, entity: Entity) {
+    if map.get(key) == Some(&entity) {
+        map.remove(key);
+    }
+}
@@ -0,0 +1,156 @@
+use hob_protocol::packet::{
+    set_actor_data::{actor_flag, metadata_key, ActorMetadata, MetadataValue},
+    start_game::StartGamePacket,
+};
+use specs::prelude::*;
+
+use super::{
+    components::{
+        chunk::{ChunkRadiusComponent, ChunkViewComponent},
+        connection::ConnectionStreamComponent,
+        DisplayNameComponent, MovementHistoryComponent, MovementStateComponent, PositionComponent,
+        RotationComponent, VelocityComponent, VisibleEntitiesComponent,
+    },
+    resources::OnlinePlayers,
+    validation::{PLAYER_HEIGHT, PLAYER_WIDTH},
+};
+use crate::{
+    command::CommandRegistry,
+    permission::{send_permissions, Permissions},
+    world::{
+        components::{
+            ActorMetadataComponent, DimensionComponent, RuntimeIdComponent, UniqueIdComponent,
+        },
+        resources::{ChunkStorageResource, LevelResource, SPAWN_Y_SURFACE},
+    },
+};
+
+/// Eye height above the feet, which is what the client reports as its position.
+pub const PLAYER_EYE_HEIGHT: f32 = 1.62;
+
+/// Actor data every player starts with.
+pub fn player_metadata(name: &str) -> ActorMetadata {
+    let mut metadata = ActorMetadata::new();
+    for flag in [
+        actor_flag::HAS_COLLISION,
+        actor_flag::HAS_GRAVITY,
+        actor_flag::CAN_SHOW_NAME_TAG,
+        actor_flag::ALWAYS_SHOW_NAME_TAG,
+        actor_flag::CAN_CLIMB,
+        actor_flag::BREATHING,
+    ] {
+        metadata.set_flag(flag, true);
+    }
+    metadata.set(
+        metadata_key::NAME_TAG,
+        MetadataValue::String(name.to_owned()),
+    );
+    metadata.set(metadata_key::SCALE, MetadataValue::Float(1.0));
+    metadata.set(
+        metadata_key::BOUNDING_BOX_WIDTH,
+        MetadataValue::Float(PLAYER_WIDTH),
+    );
+    metadata.set(
+        metadata_key::BOUNDING_BOX_HEIGHT,
+        MetadataValue::Float(PLAYER_HEIGHT),
+    );
+    metadata.set(metadata_key::AIR, MetadataValue::Short(300));
+    metadata.set(metadata_key::MAX_AIR, MetadataValue::Short(300));
+    metadata
+}
+
+/// Sends `StartGame` and attaches the components the chunk loader needs.
+pub(crate) fn start_game(world: &World, ent: Entity, conn: &mut ConnectionStreamComponent) {
+    let level = world.read_resource::<LevelResource>();
+    let runtime_id = world
+        .read_storage::<RuntimeIdComponent>()
+        .get(ent)
+        .unwrap()
+        .0;
+    let unique_id = world
+        .read_storage::<UniqueIdComponent>()
+        .get(ent)
+        .unwrap()
+        .0;
+    let (x, mut y, z) = level.spawn_position;
+    if y == SPAWN_Y_SURFACE {
+        // The spawn area is pinned, so this finds the chunk loaded instead of generating it.
+        let mut storage = world.write_resource::<ChunkStorageResource>();
+        y = storage.highest_block(x, z).map_or(0, |v| v + 1);
+    }
+    let position =
+        PositionComponent::new(x as f32 + 0.5, y as f32 + PLAYER_EYE_HEIGHT, z as f32 + 0.5);
+
+    let mut start_game = StartGamePacket::new(runtime_id, level.game_mode);
+    start_game.entity_id = unique_id;
+    start_game.seed = level.seed;
+    start_game.dimension = level.dimension;
+    start_game.generator = level.generator as i32;
+    start_game.spawn_position = (x, y, z);
+    start_game.player_position = (position.x, position.y, position.z);
+    start_game.block_network_ids_are_hashes = true;
+    start_game.movement_authority = level.movement_authority;
+    start_game.rewind_history_size = level.rewind_history_size;
+    start_game.only_spawn_v1_villagers = level.only_spawn_v1_villagers;
+    let online = world.read_resource::<OnlinePlayers>();
+    let permissions = world.read_resource::<Permissions>();
+    let player = online.get(ent).unwrap();
+    start_game.permission_level = permissions.player_level(player);
+    start_game.enable_commands = true;
+    conn.send_packet(start_game);
+    send_permissions(
+        conn,
+        &permissions,
+        &world.read_resource::<CommandRegistry>(),
+        player,
+        level.game_mode,
+    );
+
+    world
+        .write_storage::<PositionComponent>()
+        .insert(ent, position)
+        .unwrap();
+    world
+        .write_storage::<RotationComponent>()
+        .insert(ent, RotationComponent::default())
+        .unwrap();
+    world
+        .write_storage::<VelocityComponent>()
+        .insert(ent, VelocityComponent::default())
+        .unwrap();
+    world
+        .write_storage::<MovementStateComponent>()
+        .insert(ent, MovementStateComponent::default())
+        .unwrap();
+    world
+        .write_storage::<MovementHistoryComponent>()
+        .insert(
+            ent,
+            MovementHistoryComponent::new(level.rewind_history_size.max(1) as usize),
+        )
+        .unwrap();
+    world
+        .write_storage::<ChunkRadiusComponent>()
+        .insert(ent, ChunkRadiusComponent(level.view_distance))
+        .unwrap();
+    let name = world
+        .read_storage::<DisplayNameComponent>()
+        .get(ent)
+        .map_or_else(String::new, |v| v.0.clone());
+    world
+        .write_storage::<ActorMetadataComponent>()
+        .insert(ent, ActorMetadataComponent::new(player_metadata(&name)))
+        .unwrap();
+    world
+        .write_storage::<DimensionComponent>()
+        .insert(ent, DimensionComponent(level.dimension))
+        .unwrap();
+    world
+        .write_storage::<VisibleEntitiesComponent>()
+        .insert(ent, VisibleEntitiesComponent::default())
+        .unwrap();
+    world
+        .write_storage::<ChunkViewComponent>()
+        .insert(ent, ChunkViewComponent::default())
+        .unwrap();
+}
@@ -0,0 +1,39 @@
+use hob_protocol::packet::disconnect::DisconnectPacket;
+use hob_server::initial_handler::LoginState;
+use log::info;
+use specs::prelude::*;
+
+use crate::player::components::{
+    chunk::ChunkViewComponent,
+    connection::{ConnectionStreamComponent, LoginStateComponent},
+};
+
+/// Kicks players stuck on resource packs or spawning for longer than the step allows, and
+/// stops watching them once they have spawned.
+pub struct LoginTimeoutSystem;
+
+impl<'a> System<'a> for LoginTimeoutSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, LoginStateComponent>,
+        ReadStorage<'a, ChunkViewComponent>,
+        WriteStorage<'a, ConnectionStreamComponent>,
+    );
+
+    fn run(&mut self, (entities, mut logins, views, mut conns): Self::SystemData) {
+        let mut done = Vec::new();
+        for (ent, login, conn) in (&entities, &logins, &mut conns).join() {
+            let spawned = views.get(ent).is_some_and(|view| view.spawned);
+            if login.state == LoginState::Spawning && spawned {
+                done.push(ent);
+            } else if login.timed_out() && !conn.is_closing() {
+                info!("[{}] Timed out during {:?}", conn.name, login.state);
+                conn.kick(DisconnectPacket::from(login.state.timeout_reason()));
+                done.push(ent);
+            }
+        }
+        for ent in done {
+            logins.remove(ent);
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod login;
+pub mod movement;
+pub mod outbound;
+pub mod permission;
+pub mod player_list;
@@ -0,0 +1,62 @@
+use hob_protocol::packet::move_player::{MoveMode, MovePlayerPacket};
+use specs::prelude::*;
+
+use crate::{
+    player::components::{
+        connection::ConnectionStreamComponent, MovementStateComponent, PositionComponent,
+        RotationComponent, VisibleEntitiesComponent,
+    },
+    world::components::RuntimeIdComponent,
+};
+
+/// Sends `MovePlayer` for every player that moved this tick to the players that have it spawned.
+pub struct MovementBroadcastSystem;
+
+impl<'a> System<'a> for MovementBroadcastSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, RuntimeIdComponent>,
+        ReadStorage<'a, PositionComponent>,
+        ReadStorage<'a, RotationComponent>,
+        WriteStorage<'a, MovementStateComponent>,
+        ReadStorage<'a, VisibleEntitiesComponent>,
+        WriteStorage<'a, ConnectionStreamComponent>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, runtime_ids, positions, rotations, mut states, visible, mut conns): Self::SystemData,
+    ) {
+        let mut moved = Vec::new();
+        for (ent, runtime_id, pos, rot, state) in
+            (&entities, &runtime_ids, &positions, &rotations, &mut states).join()
+        {
+            if !state.moved {
+                continue;
+            }
+            state.moved = false;
+            let packet = MovePlayerPacket {
+                runtime_id: runtime_id.0,
+                position: (pos.x, pos.y, pos.z),
+                pitch: rot.pitch,
+                yaw: rot.yaw,
+                head_yaw: rot.head_yaw,
+                mode: MoveMode::Normal,
+                on_ground: state.on_ground,
+                ridden_runtime_id: 0,
+                tick: state.tick,
+            };
+            moved.push((ent, packet));
+        }
+        if moved.is_empty() {
+            return;
+        }
+        for (visible, conn) in (&visible, &mut conns).join() {
+            for (mover, packet) in moved.iter() {
+                if visible.entities.contains_key(mover) {
+                    conn.send_packet(packet.clone());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use specs::prelude::*;
+
+use crate::player::components::connection::ConnectionStreamComponent;
+
+/// Disconnects players whose outbound queue has stayed over budget for too long.
+pub struct SlowClientSystem;
+
+impl<'a> System<'a> for SlowClientSystem {
+    type SystemData = WriteStorage<'a, ConnectionStreamComponent>;
+
+    fn run(&mut self, mut conns: Self::SystemData) {
+        for conn in (&mut conns).join() {
+            conn.check_too_slow();
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use hob_server::Server;
+use specs::prelude::*;
+
+use crate::{
+    command::CommandRegistry,
+    permission::{send_permissions, sync_reserved_slots, Permissions},
+    player::{
+        components::{chunk::ChunkViewComponent, connection::ConnectionStreamComponent},
+        resources::OnlinePlayers,
+    },
+    world::resources::LevelResource,
+};
+
+/// Resends abilities and commands to everyone in game after permissions change, and
+/// updates who may take a reserved slot.
+pub struct PermissionSyncSystem;
+
+impl<'a> System<'a> for PermissionSyncSystem {
+    type SystemData = (
+        WriteExpect<'a, Permissions>,
+        Option<ReadExpect<'a, Server>>,
+        ReadExpect<'a, CommandRegistry>,
+        ReadExpect<'a, OnlinePlayers>,
+        ReadExpect<'a, LevelResource>,
+        ReadStorage<'a, ChunkViewComponent>,
+        WriteStorage<'a, ConnectionStreamComponent>,
+    );
+
+    fn run(
+        &mut self,
+        (mut permissions, server, registry, online, level, views, mut conns): Self::SystemData,
+    ) {
+        if !permissions.take_changed() {
+            return;
+        }
+        if let Some(server) = server {
+            sync_reserved_slots(&server.slots, &permissions);
+        }
+        // Players without a view haven't been sent `StartGame` yet.
+        for player in online.iter().filter(|p| views.contains(p.entity)) {
+            if let Some(conn) = conns.get_mut(player.entity) {
+                send_permissions(conn, &permissions, &registry, player, level.game_mode);
+            }
+        }
+    }
+}
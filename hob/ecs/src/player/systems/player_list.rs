@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use hob_protocol::packet::player_list::{PlayerListEntry, PlayerListPacket};
+use specs::prelude::*;
+use uuid::Uuid;
+
+use crate::player::{
+    components::{chunk::ChunkViewComponent, connection::ConnectionStreamComponent},
+    resources::{OnlinePlayer, OnlinePlayers},
+};
+
+/// Mirrors [`OnlinePlayers`] into every spawned client's player list.
+#[derive(Default)]
+pub struct PlayerListSystem {
+    listed: HashMap<Entity, Uuid>,
+}
+
+impl<'a> System<'a> for PlayerListSystem {
+    type SystemData = (
+        ReadExpect<'a, OnlinePlayers>,
+        ReadStorage<'a, ChunkViewComponent>,
+        WriteStorage<'a, ConnectionStreamComponent>,
+    );
+
+    fn run(&mut self, (online, views, mut conns): Self::SystemData) {
+        let players: HashMap<Entity, Uuid> = online
+            .iter()
+            .filter(|p| views.get(p.entity).is_some_and(|v| v.spawned))
+            .map(|p| (p.entity, p.uuid))
+            .collect();
+        let removed: Vec<Uuid> = self
+            .listed
+            .iter()
+            .filter(|(ent, _)| !players.contains_key(ent))
+            .map(|(_, uuid)| *uuid)
+            .collect();
+        let added: Vec<Entity> = players
+            .keys()
+            .filter(|ent| !self.listed.contains_key(ent))
+            .copied()
+            .collect();
+        self.listed = players;
+
+        let mut broadcast = |packet: PlayerListPacket, except: &[Entity]| {
+            for ent in self.listed.keys().filter(|ent| !except.contains(ent)) {
+                if let Some(conn) = conns.get_mut(*ent) {
+                    conn.send_packet(packet.clone());
+                }
+            }
+        };
+        if !removed.is_empty() {
+            broadcast(PlayerListPacket::Remove(removed), &[]);
+        }
+        if added.is_empty() {
+            return;
+        }
+        let entries = |ents: &mut dyn Iterator<Item = &Entity>| -> Vec<PlayerListEntry> {
+            ents.filter_map(|ent| online.get(*ent))
+                .map(list_entry)
+                .collect()
+        };
+        broadcast(PlayerListPacket::Add(entries(&mut added.iter())), &added);
+        let everyone = entries(&mut self.listed.keys());
+        for ent in added {
+            if let Some(conn) = conns.get_mut(ent) {
+                conn.send_packet(PlayerListPacket::Add(everyone.clone()));
+            }
+        }
+    }
+}
+
+fn list_entry(player: &OnlinePlayer) -> PlayerListEntry {
+    PlayerListEntry {
+        uuid: player.uuid,
+        unique_id: player.unique_id,
+        username: player.name.clone(),
+        xuid: player.xuid.clone(),
+        platform_chat_id: "".to_string(),
+        build_platform: player.build_platform,
+        skin: player.skin.clone(),
+        is_teacher: false,
+        is_host: false,
+        is_sub_client: false,
+    }
+}
@@ -0,0 +1,200 @@
+use hob_protocol::packet::start_game::GameMode;
+
+use super::spawn::PLAYER_EYE_HEIGHT;
+use crate::world::{chunk::BlockState, resources::ChunkStorageResource};
+
+pub const PLAYER_WIDTH: f32 = 0.6;
+pub const PLAYER_HEIGHT: f32 = 1.8;
+/// Horizontal blocks per tick; a sprint-jump peaks at roughly 0.62.
+pub const MAX_WALK_SPEED: f32 = 1.0;
+pub const MAX_FLY_SPEED: f32 = 2.5;
+pub const JUMP_VELOCITY: f32 = 0.42;
+pub const STEP_HEIGHT: f32 = 0.6;
+pub const GRAVITY: f32 = 0.08;
+pub const DRAG: f32 = 0.98;
+/// Slack for float error and client/server tick drift.
+pub const TOLERANCE: f32 = 0.05;
+/// Longest gap between two inputs that still counts towards the allowance.
+pub const MAX_TICK_GAP: u64 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MovementViolation {
+    TooFast { distance: f32, allowed: f32 },
+    Flying { rise: f32, allowed: f32 },
+    Collision { block: (i32, i32, i32) },
+}
+
+/// What the server knew about the player before the input.
+#[derive(Debug, Clone, Copy)]
+pub struct MoveContext {
+    /// Eye position of the last accepted input.
+    pub from: (f32, f32, f32),
+    /// Vertical displacement per tick of the last accepted input.
+    pub velocity_y: f32,
+    pub on_ground: bool,
+    /// Ticks since the previous input, moving or not.
+    pub ticks: u64,
+    pub can_fly: bool,
+}
+
+pub fn can_fly(game_mode: GameMode) -> bool {
+    matches!(
+        game_mode,
+        GameMode::Creative | GameMode::Spectator | GameMode::CreativeSpectator
+    )
+}
+
+/// Checks a move to the eye position `to` and returns whether the player ends on the ground.
+pub fn validate_move(
+    storage: &ChunkStorageResource,
+    ctx: &MoveContext,
+    to: (f32, f32, f32),
+) -> Result<bool, MovementViolation> {
+    let ticks = ctx.ticks.clamp(1, MAX_TICK_GAP);
+    let (dx, dy, dz) = (to.0 - ctx.from.0, to.1 - ctx.from.1, to.2 - ctx.from.2);
+
+    let distance = (dx * dx + dz * dz).sqrt();
+    let speed = if ctx.can_fly {
+        MAX_FLY_SPEED
+    } else {
+        MAX_WALK_SPEED
+    };
+    let allowed = speed * ticks as f32 + TOLERANCE;
+    if distance > allowed {
+        return Err(MovementViolation::TooFast { distance, allowed });
+    }
+
+    if let Some(block) = find_collision(storage, ctx.from, to) {
+        return Err(MovementViolation::Collision { block });
+    }
+
+    let weightless = ctx.can_fly
+        || find_block(storage, ctx.from, 0.0, is_weightless).is_some()
+        || find_block(storage, to, 0.0, is_weightless).is_some();
+    if !weightless {
+        let allowed = max_rise(ctx.velocity_y, ctx.on_ground, ticks) + TOLERANCE;
+        if dy > allowed {
+            return Err(MovementViolation::Flying { rise: dy, allowed });
+        }
+    }
+
+    Ok(find_block(storage, to, -0.01, BlockState::is_solid).is_some())
+}
+
+/// Highest rise reachable in `ticks` from the given vertical velocity.
+fn max_rise(velocity_y: f32, on_ground: bool, ticks: u64) -> f32 {
+    let mut velocity = if on_ground {
+        velocity_y.max(JUMP_VELOCITY)
+    } else {
+        velocity_y
+    };
+    let mut rise: f32 = 0.0;
+    for _ in 0..ticks {
+        rise += velocity.max(0.0);
+        velocity = (velocity - GRAVITY) * DRAG;
+    }
+    if on_ground {
+        rise = rise.max(STEP_HEIGHT);
+    }
+    rise
+}
+
+/// First solid block the player's bounding box runs into on the way from `from` to `to`.
+///
+/// The client resolves collisions one axis at a time, so the move is split the same way:
+/// rising moves go up before across, as when stepping or jumping onto a block, and falling
+/// moves go across before down, as when walking off a ledge.
+fn find_collision(
+    storage: &ChunkStorageResource,
+    from: (f32, f32, f32),
+    to: (f32, f32, f32),
+) -> Option<(i32, i32, i32)> {
+    let corner = if to.1 > from.1 {
+        (from.0, to.1, from.2)
+    } else {
+        (to.0, from.1, to.2)
+    };
+    sweep(storage, from, corner)
+        .or_else(|| sweep(storage, corner, to))
+        .or_else(|| find_block(storage, to, 0.0, BlockState::is_solid))
+}
+
+/// Walks the bounding box from `from` towards `to` one block boundary at a time (a DDA over
+/// the cells its leading faces enter) and checks each cell it enters before `to`.
+fn sweep(
+    storage: &ChunkStorageResource,
+    from: (f32, f32, f32),
+    to: (f32, f32, f32),
+) -> Option<(i32, i32, i32)> {
+    /// How far past a boundary the box is checked, beyond `find_block`'s own shrink.
+    const CROSSING: f32 = 0.002;
+    let from = [from.0, from.1, from.2];
+    let delta = [to.0 - from[0], to.1 - from[1], to.2 - from[2]];
+    let mut t_next = [f32::INFINITY; 3];
+    let mut t_step = [f32::INFINITY; 3];
+    for axis in 0..3 {
+        let d = delta[axis];
+        if d == 0.0 {
+            continue;
+        }
+        let face = from[axis]
+            + match (axis, d > 0.0) {
+                (1, true) => PLAYER_HEIGHT - PLAYER_EYE_HEIGHT,
+                (1, false) => -PLAYER_EYE_HEIGHT,
+                (_, true) => PLAYER_WIDTH / 2.0,
+                (_, false) => -PLAYER_WIDTH / 2.0,
+            };
+        let plane = if d > 0.0 { face.ceil() } else { face.floor() };
+        t_next[axis] = (plane - face) / d;
+        t_step[axis] = 1.0 / d.abs();
+    }
+    loop {
+        let axis = (0..3).min_by(|&a, &b| t_next[a].total_cmp(&t_next[b]))?;
+        let t = t_next[axis];
+        if t >= 1.0 {
+            return None;
+        }
+        t_next[axis] += t_step[axis];
+        // Boundaries the move only grazes are left to the check at `to`.
+        if (1.0 - t) * delta[axis].abs() <= CROSSING {
+            continue;
+        }
+        let mut at: [f32; 3] = std::array::from_fn(|i| from[i] + delta[i] * t);
+        at[axis] += CROSSING.copysign(delta[axis]);
+        if let Some(block) = find_block(storage, (at[0], at[1], at[2]), 0.0, BlockState::is_solid) {
+            return Some(block);
+        }
+    }
+}
+
+fn is_weightless(block: &BlockState) -> bool {
+    block.is_liquid() || matches!(block.name.as_str(), "minecraft:ladder" | "minecraft:vine")
+}
+
+/// First block overlapping the player's bounding box at `eye` (or the slice just below
+/// the feet when `below` is negative) that matches `f`.
+fn find_block(
+    storage: &ChunkStorageResource,
+    eye: (f32, f32, f32),
+    below: f32,
+    f: impl Fn(&BlockState) -> bool,
+) -> Option<(i32, i32, i32)> {
+    const EPSILON: f32 = 0.001;
+    let half = PLAYER_WIDTH / 2.0 - EPSILON;
+    let feet = eye.1 - PLAYER_EYE_HEIGHT;
+    let (min_y, max_y) = if below < 0.0 {
+        (feet + below, feet - EPSILON)
+    } else {
+        (feet + EPSILON, feet + PLAYER_HEIGHT - EPSILON)
+    };
+    for x in (eye.0 - half).floor() as i32..=(eye.0 + half).floor() as i32 {
+        for z in (eye.2 - half).floor() as i32..=(eye.2 + half).floor() as i32 {
+            for y in min_y.floor() as i32..=max_y.floor() as i32 {
+                if storage.block_at(x, y, z).is_some_and(&f) {
+                    return Some((x, y, z));
+                }
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,33 @@
+use hob_server::Server;
+use specs::prelude::*;
+
+use crate::player::resources::OnlinePlayers;
+
+/// Keeps the player list reported to queries in line with the players online.
+pub(crate) fn handle_query(world: &World) {
+    let Some(server) = world.try_fetch::<Server>() else {
+        return;
+    };
+    let mut players: Vec<_> = world
+        .read_resource::<OnlinePlayers>()
+        .iter()
+        .map(|player| player.name.clone())
+        .collect();
+    players.sort();
+    if server.query.read().unwrap().players != players {
+        server.query.write().unwrap().players = players;
+    }
+}
+
+/// Lists a plugin in query replies by its type name, once however many events it handles.
+pub(crate) fn add_plugin_name<T>(world: &World) {
+    let Some(server) = world.try_fetch::<Server>() else {
+        return;
+    };
+    let name = std::any::type_name::<T>();
+    let name = name.rsplit("::").next().unwrap_or(name).to_owned();
+    let plugins = &mut server.query.write().unwrap().plugins;
+    if !plugins.contains(&name) {
+        plugins.push(name);
+    }
+}
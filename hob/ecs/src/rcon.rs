@@ -0,0 +1,38 @@
+use hob_protocol::packet::start_game::PermissionLevel;
+use hob_server::rcon::RconCommands;
+use specs::prelude::*;
+
+use crate::command::execute;
+
+/// Commands answered per tick; the rest wait for the next one.
+const MAX_COMMANDS_PER_TICK: usize = 16;
+
+/// The entity RCON commands are sent by.
+pub struct RconSender(pub Entity);
+impl RconSender {
+    /// Whoever knows the RCON password gets the rights of an operator.
+    pub const LEVEL: PermissionLevel = PermissionLevel::Operator;
+}
+
+pub fn init_rcon(world: &mut World, commands: RconCommands) {
+    let sender = world.create_entity().build();
+    world.insert(RconSender(sender));
+    world.insert(commands);
+}
+
+/// Runs the commands received since the last tick, replying with each one's output.
+pub fn handle_rcon(world: &World) {
+    let commands: Vec<_> = {
+        let Some(mut queue) = world.try_fetch_mut::<RconCommands>() else {
+            return;
+        };
+        std::iter::from_fn(|| queue.0.try_recv().ok())
+            .take(MAX_COMMANDS_PER_TICK)
+            .collect()
+    };
+    let sender = world.read_resource::<RconSender>().0;
+    for command in commands {
+        let output = execute(world, sender, &command.line).unwrap_or_else(|e| e);
+        let _ = command.reply.send(output);
+    }
+}
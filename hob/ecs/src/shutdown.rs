@@ -0,0 +1,99 @@
+use std::sync::{Arc, Mutex};
+
+use hob_protocol::packet::disconnect::{DisconnectFailReason, DisconnectPacket};
+use hob_server::Server;
+use log::{error, info};
+use specs::prelude::*;
+
+use crate::{
+    command::{
+        args::{Param, ParamKind},
+        register_command, Command, CommandContext, CommandResult, CommandSys,
+    },
+    events::{player_quit::handle_player_quit, server_shutdown::ServerShutdownEvent},
+    permission::Permissions,
+    player::components::connection::ConnectionStreamComponent,
+    plugin::Plugin,
+    world::resources::LevelResource,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShutdownRequest {
+    /// Shown to players instead of the default shutdown screen.
+    pub message: Option<String>,
+}
+
+/// Asks the game to shut down from anywhere, e.g. a signal handler. The game loop checks
+/// it every tick; the first request wins.
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownHandle(Arc<Mutex<Option<ShutdownRequest>>>);
+impl ShutdownHandle {
+    pub fn request(&self, message: Option<String>) {
+        self.0
+            .lock()
+            .unwrap()
+            .get_or_insert(ShutdownRequest { message });
+    }
+    pub fn requested(&self) -> Option<ShutdownRequest> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+struct StopCommand;
+impl<'a> CommandSys<'a> for StopCommand {
+    type SystemData = ReadExpect<'a, ShutdownHandle>;
+
+    fn run(&mut self, ctx: &CommandContext, handle: Self::SystemData) -> CommandResult {
+        let message = ctx.args.string("message").map(str::to_owned);
+        handle.request(message);
+        Ok("Stopping the server".to_owned())
+    }
+}
+
+pub fn init_shutdown(world: &mut World) {
+    world.insert(ShutdownHandle::default());
+    register_command(
+        world,
+        Command::new("stop", "Disconnects everyone and stops the server")
+            .permission("hob.command.stop")
+            .overload(vec![Param::new("message", ParamKind::RawText).optional()]),
+        StopCommand,
+    );
+}
+
+/// Stops accepting players, disconnects everyone with `message` or the default shutdown
+/// screen, runs [`ServerShutdownEvent`] and saves. Connections flush in the background;
+/// wait on the server's slots before exiting.
+pub fn shutdown(world: &World, message: Option<&str>) {
+    info!("Shutting down");
+    if let Some(server) = world.try_fetch::<Server>() {
+        server.stop_accepting();
+    }
+    for conn in (&mut world.write_storage::<ConnectionStreamComponent>()).join() {
+        conn.kick(match message {
+            Some(message) => DisconnectPacket::new(DisconnectFailReason::Shutdown, message),
+            None => DisconnectFailReason::Shutdown.into(),
+        });
+    }
+    handle_player_quit(world);
+
+    let event = ServerShutdownEvent {
+        message: message.map(str::to_owned),
+    };
+    world
+        .write_resource::<Plugin<ServerShutdownEvent>>()
+        .run(&event, world);
+
+    // Chunks are regenerated from the seed saved with the level; the whitelist and ban
+    // list are written on every change.
+    if let Some(level) = world.try_fetch::<LevelResource>() {
+        if let Err(e) = level.save() {
+            error!("{e:#}");
+        }
+    }
+    if let Some(permissions) = world.try_fetch::<Permissions>() {
+        if let Err(e) = permissions.save() {
+            error!("{e:#}");
+        }
+    }
+}
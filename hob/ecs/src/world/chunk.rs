@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+
+use hob_nbt::LittleEndian;
+use hob_protocol::packet::{level_chunk::LevelChunkPacket, start_game::Dimension};
+use proto_bytes::{BufMut, ConditionalBufMut};
+use serde::{Serialize, Serializer};
+
+pub const SUB_CHUNK_SIZE: usize = 16 * 16 * 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChunkPos {
+    pub x: i32,
+    pub z: i32,
+}
+impl ChunkPos {
+    pub fn new(x: i32, z: i32) -> Self {
+        ChunkPos { x, z }
+    }
+    pub fn from_block(x: i32, z: i32) -> Self {
+        ChunkPos {
+            x: x >> 4,
+            z: z >> 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum BlockStateValue {
+    Bool(bool),
+    Int(i32),
+    String(String),
+}
+
+impl Serialize for BlockStateValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            BlockStateValue::Bool(v) => serializer.serialize_i8(*v as i8),
+            BlockStateValue::Int(v) => serializer.serialize_i32(*v),
+            BlockStateValue::String(v) => serializer.serialize_str(v),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct BlockState {
+    pub name: String,
+    pub states: BTreeMap<String, BlockStateValue>,
+}
+impl BlockState {
+    pub fn new(name: &str) -> Self {
+        BlockState {
+            name: name.to_owned(),
+            states: BTreeMap::new(),
+        }
+    }
+    pub fn air() -> Self {
+        Self::new("minecraft:air")
+    }
+    pub fn with_state(mut self, key: &str, value: BlockStateValue) -> Self {
+        self.states.insert(key.to_owned(), value);
+        self
+    }
+    pub fn is_air(&self) -> bool {
+        self.name == "minecraft:air"
+    }
+    pub fn is_liquid(&self) -> bool {
+        matches!(
+            self.name.as_str(),
+            "minecraft:water"
+                | "minecraft:flowing_water"
+                | "minecraft:lava"
+                | "minecraft:flowing_lava"
+        )
+    }
+    /// Whether the block is treated as a full cube for collision.
+    pub fn is_solid(&self) -> bool {
+        !self.is_air()
+            && !self.is_liquid()
+            && !matches!(
+                self.name.as_str(),
+                "minecraft:tallgrass"
+                    | "minecraft:short_grass"
+                    | "minecraft:red_flower"
+                    | "minecraft:yellow_flower"
+                    | "minecraft:torch"
+                    | "minecraft:snow_layer"
+                    | "minecraft:vine"
+                    | "minecraft:ladder"
+            )
+    }
+    /// FNV-1a hash of the little-endian NBT `{name, states}`, used as the runtime id
+    /// when `StartGamePacket::block_network_ids_are_hashes` is set.
+    pub fn network_id(&self) -> u32 {
+        let nbt = LittleEndian::to_vec(self).unwrap();
+        nbt.iter().fold(0x811c_9dc5, |hash: u32, b| {
+            (hash ^ *b as u32).wrapping_mul(0x0100_0193)
+        })
+    }
+}
+
+/// 16x16x16 blocks, stored as indices into a per-subchunk palette.
+/// Index order is `(x << 8) | (z << 4) | y`, matching the network layout.
+#[derive(Debug, Clone)]
+pub struct SubChunk {
+    pub palette: Vec<BlockState>,
+    pub blocks: Box<[u16; SUB_CHUNK_SIZE]>,
+}
+impl Default for SubChunk {
+    fn default() -> Self {
+        SubChunk {
+            palette: vec![BlockState::air()],
+            blocks: Box::new([0; SUB_CHUNK_SIZE]),
+        }
+    }
+}
+impl SubChunk {
+    #[inline]
+    fn index(x: usize, y: usize, z: usize) -> usize {
+        (x << 8) | (z << 4) | y
+    }
+    pub fn get_block(&self, x: usize, y: usize, z: usize) -> &BlockState {
+        &self.palette[self.blocks[Self::index(x, y, z)] as usize]
+    }
+    pub fn set_block(&mut self, x: usize, y: usize, z: usize, block: &BlockState) {
+        let id = match self.palette.iter().position(|v| v == block) {
+            Some(id) => id,
+            None => {
+                self.palette.push(block.clone());
+                self.palette.len() - 1
+            }
+        };
+        self.blocks[Self::index(x, y, z)] = id as u16;
+    }
+    pub fn is_empty(&self) -> bool {
+        self.palette.iter().all(BlockState::is_air)
+    }
+    fn encode(&self, bytes: &mut Vec<u8>, y_index: i8) {
+        bytes.put_u8(9); // version
+        bytes.put_u8(1); // storage layers
+        bytes.put_i8(y_index);
+        let palette: Vec<u32> = self.palette.iter().map(BlockState::network_id).collect();
+        encode_paletted_storage(bytes, &self.blocks[..], &palette);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub pos: ChunkPos,
+    pub dimension: Dimension,
+    pub sub_chunks: Vec<SubChunk>,
+    /// Biome ids per column, indexed by `(x << 4) | z`.
+    pub biomes: Box<[u32; 256]>,
+}
+impl Chunk {
+    pub fn new(pos: ChunkPos, dimension: Dimension) -> Self {
+        let (min_y, max_y) = height_range(dimension);
+        let count = ((max_y - min_y) >> 4) as usize;
+        Chunk {
+            pos,
+            dimension,
+            sub_chunks: vec![SubChunk::default(); count],
+            biomes: Box::new([0; 256]),
+        }
+    }
+    pub fn min_y(&self) -> i32 {
+        height_range(self.dimension).0
+    }
+    pub fn max_y(&self) -> i32 {
+        height_range(self.dimension).1
+    }
+    pub fn get_block(&self, x: usize, y: i32, z: usize) -> Option<&BlockState> {
+        let (index, local_y) = self.sub_chunk_index(y)?;
+        Some(self.sub_chunks[index].get_block(x, local_y, z))
+    }
+    pub fn set_block(&mut self, x: usize, y: i32, z: usize, block: &BlockState) {
+        if let Some((index, local_y)) = self.sub_chunk_index(y) {
+            self.sub_chunks[index].set_block(x, local_y, z, block);
+        }
+    }
+    pub fn highest_block(&self, x: usize, z: usize) -> Option<i32> {
+        (self.min_y()..self.max_y())
+            .rev()
+            .find(|y| self.get_block(x, *y, z).is_some_and(|b| !b.is_air()))
+    }
+    pub fn set_biome(&mut self, x: usize, z: usize, biome: u32) {
+        self.biomes[(x << 4) | z] = biome;
+    }
+    pub fn get_biome(&self, x: usize, z: usize) -> u32 {
+        self.biomes[(x << 4) | z]
+    }
+    pub fn to_packet(&self) -> LevelChunkPacket {
+        let count = self
+            .sub_chunks
+            .iter()
+            .rposition(|v| !v.is_empty())
+            .map_or(0, |i| i + 1);
+        let mut payload = Vec::new();
+        let base_index = (self.min_y() >> 4) as i8;
+        for (i, sub_chunk) in self.sub_chunks[..count].iter().enumerate() {
+            sub_chunk.encode(&mut payload, base_index + i as i8);
+        }
+        // Biomes are stored per column, so every section shares the same storage.
+        let mut biome_palette: Vec<u32> = Vec::new();
+        let mut biome_indices = vec![0u16; SUB_CHUNK_SIZE];
+        for (column, biome) in self.biomes.iter().enumerate() {
+            let id = match biome_palette.iter().position(|v| v == biome) {
+                Some(id) => id,
+                None => {
+                    biome_palette.push(*biome);
+                    biome_palette.len() - 1
+                }
+            };
+            for y in 0..16 {
+                biome_indices[(column << 4) | y] = id as u16;
+            }
+        }
+        for _ in 0..self.sub_chunks.len() {
+            encode_paletted_storage(&mut payload, &biome_indices, &biome_palette);
+        }
+        payload.put_u8(0); // border blocks
+        LevelChunkPacket {
+            chunk_x: self.pos.x,
+            chunk_z: self.pos.z,
+            dimension: self.dimension,
+            sub_chunk_count: count as u32,
+            cache_enabled: false,
+            payload,
+        }
+    }
+    fn sub_chunk_index(&self, y: i32) -> Option<(usize, usize)> {
+        let min_y = self.min_y();
+        if y < min_y || y >= self.max_y() {
+            return None;
+        }
+        let offset = (y - min_y) as usize;
+        Some((offset >> 4, offset & 0xf))
+    }
+}
+
+/// Returns `(min_y, max_y)` of the buildable area, `max_y` being exclusive.
+pub fn height_range(dimension: Dimension) -> (i32, i32) {
+    match dimension {
+        Dimension::OverWorld => (-64, 320),
+        Dimension::Nether => (0, 128),
+        Dimension::End => (0, 256),
+    }
+}
+
+fn encode_paletted_storage(bytes: &mut Vec<u8>, indices: &[u16], palette: &[u32]) {
+    let bits = match palette.len() {
+        0..=1 => 0,
+        2 => 1,
+        3..=4 => 2,
+        5..=8 => 3,
+        9..=16 => 4,
+        17..=32 => 5,
+        33..=64 => 6,
+        65..=256 => 8,
+        _ => 16,
+    };
+    bytes.put_u8((bits << 1) | 1); // runtime ids
+    if bits != 0 {
+        let per_word = 32 / bits as usize;
+        for chunk in indices.chunks(per_word) {
+            let word = chunk.iter().enumerate().fold(0u32, |word, (i, v)| {
+                word | (*v as u32) << (i * bits as usize)
+            });
+            bytes.put_u32_le(word);
+        }
+        bytes.put_zigzag32(palette.len() as i32);
+    }
+    for id in palette {
+        bytes.put_zigzag32(*id as i32);
+    }
+}
@@ -1,6 +1,78 @@
+use std::collections::BTreeSet;
+
+use hob_protocol::packet::{
+    set_actor_data::{ActorMetadata, MetadataValue},
+    start_game::Dimension,
+};
 use specs::Component;
 
 pub struct RuntimeIdComponent(pub u64);
 impl Component for RuntimeIdComponent {
     type Storage = specs::VecStorage<Self>;
 }
+
+pub struct UniqueIdComponent(pub i64);
+impl Component for UniqueIdComponent {
+    type Storage = specs::VecStorage<Self>;
+}
+
+pub struct DimensionComponent(pub Dimension);
+impl Component for DimensionComponent {
+    type Storage = specs::VecStorage<Self>;
+}
+
+/// Identifier sent in `AddActor` for entities that are not players, e.g. `minecraft:pig`.
+pub struct ActorTypeComponent(pub String);
+impl Component for ActorTypeComponent {
+    type Storage = specs::VecStorage<Self>;
+}
+
+/// Actor data with the keys changed since the last `SetActorData`.
+#[derive(Default)]
+pub struct ActorMetadataComponent {
+    metadata: ActorMetadata,
+    dirty: BTreeSet<u32>,
+}
+impl ActorMetadataComponent {
+    pub fn new(metadata: ActorMetadata) -> Self {
+        ActorMetadataComponent {
+            metadata,
+            dirty: BTreeSet::new(),
+        }
+    }
+    pub fn metadata(&self) -> &ActorMetadata {
+        &self.metadata
+    }
+    pub fn get(&self, key: u32) -> Option<&MetadataValue> {
+        self.metadata.get(key)
+    }
+    pub fn set(&mut self, key: u32, value: MetadataValue) {
+        if self.metadata.set(key, value) {
+            self.dirty.insert(key);
+        }
+    }
+    pub fn get_flag(&self, flag: u32) -> bool {
+        self.metadata.get_flag(flag)
+    }
+    pub fn set_flag(&mut self, flag: u32, value: bool) {
+        if self.metadata.set_flag(flag, value) {
+            self.dirty.insert(ActorMetadata::flag_key(flag));
+        }
+    }
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+    /// Changed entries, clearing the dirty set.
+    pub fn take_dirty(&mut self) -> ActorMetadata {
+        let dirty = std::mem::take(&mut self.dirty);
+        ActorMetadata(
+            dirty
+                .into_iter()
+                .filter_map(|key| Some((key, self.metadata.get(key)?.clone())))
+                .collect(),
+        )
+    }
+}
+impl Component for ActorMetadataComponent {
+    type Storage = specs::VecStorage<Self>;
+}
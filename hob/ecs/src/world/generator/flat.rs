@@ -0,0 +1,73 @@
+use anyhow::Result;
+use hob_protocol::packet::start_game::Dimension;
+use serde::Deserialize;
+
+use super::WorldGenerator;
+use crate::world::chunk::{BlockState, Chunk, ChunkPos};
+
+pub const DEFAULT_FLAT_WORLD_LAYERS: &str = r#"{"biome_id":1,"block_layers":[{"block_name":"minecraft:bedrock","count":1},{"block_name":"minecraft:dirt","count":2},{"block_name":"minecraft:grass","count":1}],"encoding_version":6,"structure_options":null,"world_version":"version.post_1_18"}"#;
+
+/// `FlatWorldLayers` string stored in `level.dat`.
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct FlatWorldLayers {
+    pub biome_id: u32,
+    pub block_layers: Vec<BlockLayer>,
+    pub encoding_version: i32,
+    pub structure_options: Option<serde_json::Value>,
+    pub world_version: Option<String>,
+}
+impl FlatWorldLayers {
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+impl Default for FlatWorldLayers {
+    fn default() -> Self {
+        Self::from_json(DEFAULT_FLAT_WORLD_LAYERS).unwrap()
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct BlockLayer {
+    pub block_name: String,
+    pub count: u32,
+}
+
+pub struct FlatGenerator {
+    layers: Vec<BlockState>,
+    biome_id: u32,
+    post_1_18: bool,
+}
+impl FlatGenerator {
+    pub fn new(layers: &FlatWorldLayers) -> Self {
+        let blocks = layers
+            .block_layers
+            .iter()
+            .flat_map(|layer| {
+                std::iter::repeat_n(BlockState::new(&layer.block_name), layer.count as usize)
+            })
+            .collect();
+        FlatGenerator {
+            layers: blocks,
+            biome_id: layers.biome_id,
+            post_1_18: layers.world_version.as_deref() == Some("version.post_1_18"),
+        }
+    }
+}
+
+impl WorldGenerator for FlatGenerator {
+    fn generate(&self, pos: ChunkPos, dimension: Dimension) -> Chunk {
+        let mut chunk = Chunk::new(pos, dimension);
+        // Pre-1.18 flat worlds start at y=0 even though the overworld now extends below it.
+        let base_y = if self.post_1_18 { chunk.min_y() } else { 0 };
+        for x in 0..16 {
+            for z in 0..16 {
+                chunk.set_biome(x, z, self.biome_id);
+                for (i, block) in self.layers.iter().enumerate() {
+                    chunk.set_block(x, base_y + i as i32, z, block);
+                }
+            }
+        }
+        chunk
+    }
+}
@@ -0,0 +1,67 @@
+pub mod flat;
+pub mod noise;
+pub mod overworld;
+pub mod void;
+
+use std::sync::Arc;
+
+use hob_protocol::packet::start_game::Dimension;
+
+use self::{
+    flat::{FlatGenerator, FlatWorldLayers},
+    overworld::OverworldGenerator,
+    void::VoidGenerator,
+};
+use super::{
+    chunk::{Chunk, ChunkPos},
+    resources::LevelResource,
+};
+
+pub trait WorldGenerator: Send + Sync {
+    fn generate(&self, pos: ChunkPos, dimension: Dimension) -> Chunk;
+}
+
+/// Values of `StartGamePacket::generator`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeneratorType {
+    Legacy,
+    Overworld,
+    Flat,
+    Nether,
+    TheEnd,
+    Void,
+}
+impl TryFrom<i32> for GeneratorType {
+    type Error = anyhow::Error;
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::Legacy,
+            1 => Self::Overworld,
+            2 => Self::Flat,
+            3 => Self::Nether,
+            4 => Self::TheEnd,
+            5 => Self::Void,
+            n => anyhow::bail!("Unknown generator type:{}", n),
+        })
+    }
+}
+
+pub fn create_generator(level: &LevelResource) -> Arc<dyn WorldGenerator> {
+    match level.generator {
+        GeneratorType::Flat => match FlatWorldLayers::from_json(&level.flat_world_layers) {
+            Ok(layers) => Arc::new(FlatGenerator::new(&layers)),
+            Err(e) => {
+                log::warn!("Invalid FlatWorldLayers, using default layers: {e}");
+                Arc::new(FlatGenerator::new(&FlatWorldLayers::default()))
+            }
+        },
+        GeneratorType::Overworld | GeneratorType::Legacy => {
+            Arc::new(OverworldGenerator::new(level.seed))
+        }
+        GeneratorType::Void => Arc::new(VoidGenerator),
+        kind => {
+            log::warn!("{kind:?} generator is not supported yet, using Void");
+            Arc::new(VoidGenerator)
+        }
+    }
+}
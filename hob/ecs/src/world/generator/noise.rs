@@ -0,0 +1,138 @@
+/// SplitMix64, used wherever generation needs reproducible randomness.
+#[derive(Debug, Clone)]
+pub struct Random(u64);
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Random(seed)
+    }
+    /// Mixes a chunk position into a world seed.
+    pub fn for_chunk(seed: u64, x: i32, z: i32, salt: u64) -> Self {
+        let mut rng = Random(
+            seed ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+                ^ (z as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+                ^ salt,
+        );
+        rng.next_u64();
+        rng
+    }
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+    /// Returns a value in `0..bound`.
+    pub fn next_bounded(&mut self, bound: u32) -> u32 {
+        (((self.next_u64() >> 32) * bound as u64) >> 32) as u32
+    }
+    pub fn next_range(&mut self, min: i32, max: i32) -> i32 {
+        min + self.next_bounded((max - min) as u32) as i32
+    }
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Improved Perlin noise with a seeded permutation table.
+#[derive(Debug, Clone)]
+pub struct Perlin {
+    perm: [u8; 512],
+}
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Random::new(seed);
+        let mut table: [u8; 256] = std::array::from_fn(|i| i as u8);
+        for i in (1..256).rev() {
+            let j = rng.next_bounded(i as u32 + 1) as usize;
+            table.swap(i, j);
+        }
+        let mut perm = [0; 512];
+        for (i, p) in perm.iter_mut().enumerate() {
+            *p = table[i & 255];
+        }
+        Perlin { perm }
+    }
+
+    pub fn noise2(&self, x: f64, z: f64) -> f64 {
+        self.noise3(x, 0.0, z)
+    }
+
+    pub fn noise3(&self, x: f64, y: f64, z: f64) -> f64 {
+        let (xi, yi, zi) = (
+            x.floor() as i32 & 255,
+            y.floor() as i32 & 255,
+            z.floor() as i32 & 255,
+        );
+        let (x, y, z) = (x - x.floor(), y - y.floor(), z - z.floor());
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+        let p = &self.perm;
+        let (xi, yi, zi) = (xi as usize, yi as usize, zi as usize);
+        let a = p[xi] as usize + yi;
+        let aa = p[a] as usize + zi;
+        let ab = p[a + 1] as usize + zi;
+        let b = p[xi + 1] as usize + yi;
+        let ba = p[b] as usize + zi;
+        let bb = p[b + 1] as usize + zi;
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, grad(p[aa], x, y, z), grad(p[ba], x - 1.0, y, z)),
+                lerp(
+                    u,
+                    grad(p[ab], x, y - 1.0, z),
+                    grad(p[bb], x - 1.0, y - 1.0, z),
+                ),
+            ),
+            lerp(
+                v,
+                lerp(
+                    u,
+                    grad(p[aa + 1], x, y, z - 1.0),
+                    grad(p[ba + 1], x - 1.0, y, z - 1.0),
+                ),
+                lerp(
+                    u,
+                    grad(p[ab + 1], x, y - 1.0, z - 1.0),
+                    grad(p[bb + 1], x - 1.0, y - 1.0, z - 1.0),
+                ),
+            ),
+        )
+    }
+
+    /// Fractal sum of `octaves` layers, normalized to roughly `-1.0..1.0`.
+    pub fn fbm2(&self, x: f64, z: f64, octaves: u32) -> f64 {
+        let (mut sum, mut amplitude, mut frequency, mut max) = (0.0, 1.0, 1.0, 0.0);
+        for _ in 0..octaves {
+            sum += self.noise2(x * frequency, z * frequency) * amplitude;
+            max += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        sum / max
+    }
+}
+
+#[inline]
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+#[inline]
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+#[inline]
+fn grad(hash: u8, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = match h {
+        0..=3 => y,
+        12 | 14 => x,
+        _ => z,
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
@@ -0,0 +1,310 @@
+use hob_protocol::packet::start_game::Dimension;
+
+use super::{
+    noise::{Perlin, Random},
+    WorldGenerator,
+};
+use crate::world::chunk::{BlockState, Chunk, ChunkPos};
+
+const SEA_LEVEL: i32 = 62;
+const DEEPSLATE_LEVEL: i32 = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Biome {
+    Ocean,
+    Beach,
+    Plains,
+    Desert,
+    Forest,
+    Taiga,
+    ExtremeHills,
+}
+impl Biome {
+    pub fn id(&self) -> u32 {
+        match self {
+            Biome::Ocean => 0,
+            Biome::Plains => 1,
+            Biome::Desert => 2,
+            Biome::ExtremeHills => 3,
+            Biome::Forest => 4,
+            Biome::Taiga => 5,
+            Biome::Beach => 16,
+        }
+    }
+    fn surface(&self) -> (&'static str, &'static str) {
+        match self {
+            Biome::Desert | Biome::Beach => ("minecraft:sand", "minecraft:sand"),
+            Biome::Ocean => ("minecraft:gravel", "minecraft:gravel"),
+            _ => ("minecraft:grass", "minecraft:dirt"),
+        }
+    }
+    /// Number of tree attempts per chunk.
+    fn trees(&self) -> u32 {
+        match self {
+            Biome::Forest => 8,
+            Biome::Taiga => 5,
+            Biome::Plains | Biome::ExtremeHills => 1,
+            _ => 0,
+        }
+    }
+}
+
+struct Ore {
+    block: &'static str,
+    attempts: u32,
+    size: u32,
+    min_y: i32,
+    max_y: i32,
+}
+
+const ORES: &[Ore] = &[
+    Ore {
+        block: "minecraft:coal_ore",
+        attempts: 20,
+        size: 12,
+        min_y: 0,
+        max_y: 128,
+    },
+    Ore {
+        block: "minecraft:iron_ore",
+        attempts: 16,
+        size: 8,
+        min_y: -24,
+        max_y: 56,
+    },
+    Ore {
+        block: "minecraft:copper_ore",
+        attempts: 12,
+        size: 8,
+        min_y: -16,
+        max_y: 112,
+    },
+    Ore {
+        block: "minecraft:gold_ore",
+        attempts: 4,
+        size: 7,
+        min_y: -64,
+        max_y: 32,
+    },
+    Ore {
+        block: "minecraft:redstone_ore",
+        attempts: 6,
+        size: 6,
+        min_y: -64,
+        max_y: 16,
+    },
+    Ore {
+        block: "minecraft:lapis_ore",
+        attempts: 2,
+        size: 6,
+        min_y: -64,
+        max_y: 64,
+    },
+    Ore {
+        block: "minecraft:diamond_ore",
+        attempts: 2,
+        size: 4,
+        min_y: -64,
+        max_y: 16,
+    },
+];
+
+/// Seeded terrain: heightmap, biomes, caves, ores and trees.
+/// Every chunk depends only on the seed and its own position.
+pub struct OverworldGenerator {
+    seed: u64,
+    height: Perlin,
+    roughness: Perlin,
+    temperature: Perlin,
+    humidity: Perlin,
+    cave: Perlin,
+}
+impl OverworldGenerator {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Random::new(seed);
+        OverworldGenerator {
+            seed,
+            height: Perlin::new(rng.next_u64()),
+            roughness: Perlin::new(rng.next_u64()),
+            temperature: Perlin::new(rng.next_u64()),
+            humidity: Perlin::new(rng.next_u64()),
+            cave: Perlin::new(rng.next_u64()),
+        }
+    }
+
+    pub fn biome_at(&self, x: i32, z: i32) -> Biome {
+        let height = self.height_at(x, z);
+        if height < SEA_LEVEL - 3 {
+            return Biome::Ocean;
+        }
+        if height <= SEA_LEVEL + 1 {
+            return Biome::Beach;
+        }
+        if height > 100 {
+            return Biome::ExtremeHills;
+        }
+        let (x, z) = (x as f64 / 384.0, z as f64 / 384.0);
+        let temperature = self.temperature.fbm2(x, z, 2);
+        let humidity = self.humidity.fbm2(x, z, 2);
+        match (temperature, humidity) {
+            (t, h) if t > 0.25 && h < 0.0 => Biome::Desert,
+            (t, _) if t < -0.25 => Biome::Taiga,
+            (_, h) if h > 0.1 => Biome::Forest,
+            _ => Biome::Plains,
+        }
+    }
+
+    pub fn height_at(&self, x: i32, z: i32) -> i32 {
+        let (x, z) = (x as f64, z as f64);
+        let base = self.height.fbm2(x / 256.0, z / 256.0, 4);
+        let rough = (self.roughness.fbm2(x / 128.0, z / 128.0, 2) + 1.0) * 0.5;
+        SEA_LEVEL + (base * (16.0 + 48.0 * rough * rough)) as i32
+    }
+
+    fn is_cave(&self, x: i32, y: i32, z: i32) -> bool {
+        let n = self
+            .cave
+            .noise3(x as f64 / 32.0, y as f64 / 16.0, z as f64 / 32.0);
+        n.abs() < 0.06
+    }
+
+    fn fill_terrain(
+        &self,
+        chunk: &mut Chunk,
+        heights: &[[i32; 16]; 16],
+        biomes: &[[Biome; 16]; 16],
+    ) {
+        let (bedrock, stone, deepslate, water) = (
+            BlockState::new("minecraft:bedrock"),
+            BlockState::new("minecraft:stone"),
+            BlockState::new("minecraft:deepslate"),
+            BlockState::new("minecraft:water"),
+        );
+        let min_y = chunk.min_y();
+        let (base_x, base_z) = (chunk.pos.x << 4, chunk.pos.z << 4);
+        for x in 0..16 {
+            for z in 0..16 {
+                let (height, biome) = (heights[x][z], biomes[x][z]);
+                chunk.set_biome(x, z, biome.id());
+                let (top, filler) = biome.surface();
+                let (top, filler) = (BlockState::new(top), BlockState::new(filler));
+                chunk.set_block(x, min_y, z, &bedrock);
+                for y in min_y + 1..=height.max(SEA_LEVEL) {
+                    let (wx, wz) = (base_x + x as i32, base_z + z as i32);
+                    let block = if y > height {
+                        &water
+                    } else if y > min_y + 4 && y < height - 4 && self.is_cave(wx, y, wz) {
+                        continue;
+                    } else if y == height && height >= SEA_LEVEL {
+                        &top
+                    } else if y > height - 4 {
+                        &filler
+                    } else if y < DEEPSLATE_LEVEL {
+                        &deepslate
+                    } else {
+                        &stone
+                    };
+                    chunk.set_block(x, y, z, block);
+                }
+            }
+        }
+    }
+
+    fn place_ores(&self, chunk: &mut Chunk) {
+        let mut rng = Random::for_chunk(self.seed, chunk.pos.x, chunk.pos.z, 0x6F72_6573);
+        for ore in ORES {
+            let block = BlockState::new(ore.block);
+            for _ in 0..ore.attempts {
+                let (mut x, mut y, mut z) = (
+                    rng.next_bounded(16) as i32,
+                    rng.next_range(ore.min_y, ore.max_y),
+                    rng.next_bounded(16) as i32,
+                );
+                for _ in 0..ore.size {
+                    if (0..16).contains(&x) && (0..16).contains(&z) {
+                        let replaceable = matches!(
+                            chunk
+                                .get_block(x as usize, y, z as usize)
+                                .map(|b| b.name.as_str()),
+                            Some("minecraft:stone" | "minecraft:deepslate")
+                        );
+                        if replaceable {
+                            chunk.set_block(x as usize, y, z as usize, &block);
+                        }
+                    }
+                    match rng.next_bounded(3) {
+                        0 => x += rng.next_range(-1, 2),
+                        1 => y += rng.next_range(-1, 2),
+                        _ => z += rng.next_range(-1, 2),
+                    }
+                }
+            }
+        }
+    }
+
+    fn place_trees(
+        &self,
+        chunk: &mut Chunk,
+        heights: &[[i32; 16]; 16],
+        biomes: &[[Biome; 16]; 16],
+    ) {
+        let mut rng = Random::for_chunk(self.seed, chunk.pos.x, chunk.pos.z, 0x7472_6565);
+        let (log, leaves) = (
+            BlockState::new("minecraft:oak_log"),
+            BlockState::new("minecraft:oak_leaves"),
+        );
+        let attempts = biomes[8][8].trees();
+        for _ in 0..attempts {
+            // Trees stay inside the chunk so neighbours never need to be generated first.
+            let (x, z) = (
+                rng.next_range(2, 14) as usize,
+                rng.next_range(2, 14) as usize,
+            );
+            let ground = heights[x][z];
+            let on_grass = chunk
+                .get_block(x, ground, z)
+                .is_some_and(|b| b.name == "minecraft:grass");
+            if !on_grass || biomes[x][z].trees() == 0 {
+                continue;
+            }
+            let trunk = rng.next_range(4, 7);
+            let top = ground + trunk;
+            for y in top - 2..=top + 1 {
+                let radius: i32 = if y > top - 1 { 1 } else { 2 };
+                for dx in -radius..=radius {
+                    for dz in -radius..=radius {
+                        if dx.abs() == radius && dz.abs() == radius && rng.next_bounded(2) == 0 {
+                            continue;
+                        }
+                        let (lx, lz) = ((x as i32 + dx) as usize, (z as i32 + dz) as usize);
+                        chunk.set_block(lx, y, lz, &leaves);
+                    }
+                }
+            }
+            chunk.set_block(x, ground, z, &BlockState::new("minecraft:dirt"));
+            for y in ground + 1..top {
+                chunk.set_block(x, y, z, &log);
+            }
+        }
+    }
+}
+
+impl WorldGenerator for OverworldGenerator {
+    fn generate(&self, pos: ChunkPos, dimension: Dimension) -> Chunk {
+        let mut chunk = Chunk::new(pos, dimension);
+        let (base_x, base_z) = (pos.x << 4, pos.z << 4);
+        let mut heights = [[0; 16]; 16];
+        let mut biomes = [[Biome::Plains; 16]; 16];
+        for x in 0..16 {
+            for z in 0..16 {
+                let (wx, wz) = (base_x + x as i32, base_z + z as i32);
+                heights[x][z] = self.height_at(wx, wz);
+                biomes[x][z] = self.biome_at(wx, wz);
+            }
+        }
+        self.fill_terrain(&mut chunk, &heights, &biomes);
+        self.place_ores(&mut chunk);
+        self.place_trees(&mut chunk, &heights, &biomes);
+        chunk
+    }
+}
@@ -0,0 +1,13 @@
+use hob_protocol::packet::start_game::Dimension;
+
+use super::WorldGenerator;
+use crate::world::chunk::{Chunk, ChunkPos};
+
+#[derive(Debug, Default)]
+pub struct VoidGenerator;
+
+impl WorldGenerator for VoidGenerator {
+    fn generate(&self, pos: ChunkPos, dimension: Dimension) -> Chunk {
+        Chunk::new(pos, dimension)
+    }
+}
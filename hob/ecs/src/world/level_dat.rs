@@ -0,0 +1,39 @@
+use std::{collections::HashMap, path::Path};
+
+use anyhow::{bail, Context, Result};
+use hob_nbt::{value::Value, LittleEndian};
+
+/// Storage version written in the header, that of the current Bedrock releases.
+pub const STORAGE_VERSION: i32 = 10;
+
+/// Reads Bedrock's level.dat: the storage version and the length of the rest, both
+/// little-endian `u32`s, followed by a little-endian NBT compound. `None` if there is
+/// no file at `path`.
+pub fn read_level_dat(path: &Path) -> Result<Option<HashMap<String, Value>>> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).context(format!("Failed to read {}", path.display())),
+    };
+    let invalid = || format!("Invalid level.dat {}", path.display());
+    let (Some(header), Some(body)) = (bytes.get(..8), bytes.get(8..)) else {
+        bail!("{} is too short", invalid());
+    };
+    let len = u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+    if len != body.len() {
+        bail!("{}: {} bytes instead of {len}", invalid(), body.len());
+    }
+    match LittleEndian::from_slice(body).with_context(invalid)? {
+        Value::Compound(level_dat) => Ok(Some(level_dat)),
+        _ => bail!("{} is not a compound", invalid()),
+    }
+}
+
+pub fn write_level_dat(path: &Path, level_dat: &HashMap<String, Value>) -> Result<()> {
+    let nbt = LittleEndian::to_vec(level_dat)?;
+    let mut bytes = Vec::with_capacity(nbt.len() + 8);
+    bytes.extend_from_slice(&STORAGE_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&(nbt.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&nbt);
+    std::fs::write(path, bytes).with_context(|| format!("Failed to write {}", path.display()))
+}
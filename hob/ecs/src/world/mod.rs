@@ -1,13 +1,69 @@
+use std::path::Path;
+
+use hob_server::config::LevelConfig;
+use log::{error, info};
 use specs::{world, WorldExt};
 
-use self::components::RuntimeIdComponent;
+use self::{
+    components::{
+        ActorMetadataComponent, ActorTypeComponent, DimensionComponent, RuntimeIdComponent,
+        UniqueIdComponent,
+    },
+    resources::{ChunkStorageResource, EntityIdAllocator, LevelResource},
+    systems::{
+        chunk_loader::ChunkLoaderSystem, entity_tracker::EntityTrackerSystem,
+        metadata::MetadataSyncSystem,
+    },
+};
 
+pub mod chunk;
 pub mod components;
+pub mod generator;
+pub mod level_dat;
 pub mod resources;
+pub mod systems;
 
-pub fn init_world(world: &mut specs::World, dispatcher: &mut specs::DispatcherBuilder) {
+pub fn init_world(
+    world: &mut specs::World,
+    dispatcher: &mut specs::DispatcherBuilder,
+    config: &LevelConfig,
+    path: impl AsRef<Path>,
+) {
     world.register::<RuntimeIdComponent>();
-    world.insert(resources::EntityCountResource::default());
+    world.register::<UniqueIdComponent>();
+    world.register::<DimensionComponent>();
+    world.register::<ActorTypeComponent>();
+    world.register::<ActorMetadataComponent>();
+    world.insert(EntityIdAllocator::default());
+    let level = LevelResource::load(config, path).unwrap_or_else(|e| {
+        // Keep the broken file untouched rather than overwriting it on shutdown.
+        error!("{e:#}; the world will not be saved");
+        LevelResource::from_config(config)
+    });
+    info!(
+        "Loaded a {:?} world with seed {}",
+        level.generator, level.seed as i64
+    );
+    insert_level(world, level);
+    dispatcher.add(ChunkLoaderSystem::default(), "chunk_loader", &[]);
+    dispatcher.add(
+        EntityTrackerSystem,
+        "entity_tracker",
+        &["chunk_loader", "movement_broadcast", "player_list"],
+    );
+    dispatcher.add(MetadataSyncSystem, "metadata_sync", &["entity_tracker"]);
+}
+
+/// Replaces the level and its chunks, loading the spawn area of the new one.
+pub(crate) fn insert_level(world: &mut specs::World, level: LevelResource) {
+    let mut storage = ChunkStorageResource::new(&level);
+    storage.pin_spawn_area(&level);
+    world.insert(storage);
+    world.insert(level);
 }
 
-pub(crate) fn handle_world(world: &world::World) {}
+pub(crate) fn handle_world(world: &world::World) {
+    world
+        .write_resource::<ChunkStorageResource>()
+        .collect_generated();
+}
@@ -1,2 +1,302 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use hob_nbt::value::Value;
+use hob_protocol::packet::start_game::{Dimension, GameMode, MovementAuthority};
+use hob_server::config::{LevelConfig, LevelGameMode, LevelGenerator, LevelMovementAuthority};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use super::{
+    chunk::{BlockState, Chunk, ChunkPos},
+    components::{RuntimeIdComponent, UniqueIdComponent},
+    generator::{create_generator, flat::DEFAULT_FLAT_WORLD_LAYERS, GeneratorType, WorldGenerator},
+    level_dat::{read_level_dat, write_level_dat},
+};
+use crate::player::components::chunk::in_radius;
+
+/// Hands out runtime IDs, which only live for this session, and unique IDs.
 #[derive(Debug, Default)]
-pub struct EntityCountResource(pub u64);
+pub struct EntityIdAllocator {
+    runtime_id: u64,
+    unique_id: i64,
+}
+impl EntityIdAllocator {
+    pub fn allocate(&mut self) -> (RuntimeIdComponent, UniqueIdComponent) {
+        self.runtime_id += 1;
+        self.unique_id += 1;
+        (
+            RuntimeIdComponent(self.runtime_id),
+            UniqueIdComponent(self.unique_id),
+        )
+    }
+}
+
+/// Spawn Y in `level.dat` meaning "on top of the highest block".
+pub const SPAWN_Y_SURFACE: i32 = 32767;
+/// Chunks around the spawn point that are generated up front and never unloaded, so that
+/// joining players don't wait for the generator.
+pub const SPAWN_CHUNK_RADIUS: i32 = 2;
+
+/// Level settings shared with `StartGamePacket`.
+#[derive(Debug, Clone)]
+pub struct LevelResource {
+    pub seed: u64,
+    pub dimension: Dimension,
+    pub generator: GeneratorType,
+    pub flat_world_layers: String,
+    pub game_mode: GameMode,
+    pub spawn_position: (i32, i32, i32),
+    /// Upper bound for `RequestChunkRadius`.
+    pub view_distance: i32,
+    pub movement_authority: MovementAuthority,
+    /// Ticks of input the client keeps for `ServerWithRewind` corrections.
+    pub rewind_history_size: i32,
+    /// `SpawnV1Villagers` in level.dat: villagers keep their pre-1.14 trades.
+    pub only_spawn_v1_villagers: bool,
+    /// Where [`Self::save`] writes level.dat; `None` for levels that only live in memory.
+    pub path: Option<PathBuf>,
+    /// level.dat as last read, so that the keys hob doesn't use survive a save.
+    pub level_dat: HashMap<String, Value>,
+}
+impl Default for LevelResource {
+    fn default() -> Self {
+        LevelResource {
+            seed: 0,
+            dimension: Dimension::OverWorld,
+            generator: GeneratorType::Flat,
+            flat_world_layers: DEFAULT_FLAT_WORLD_LAYERS.to_owned(),
+            game_mode: GameMode::Survival,
+            spawn_position: (0, SPAWN_Y_SURFACE, 0),
+            view_distance: 10,
+            movement_authority: MovementAuthority::Client,
+            rewind_history_size: 40,
+            only_spawn_v1_villagers: false,
+            path: None,
+            level_dat: HashMap::new(),
+        }
+    }
+}
+impl LevelResource {
+    /// A new world as `config` describes it.
+    pub fn from_config(config: &LevelConfig) -> Self {
+        let mut level = LevelResource {
+            seed: config.seed.unwrap_or_else(rand::random) as u64,
+            generator: match config.generator {
+                LevelGenerator::Flat => GeneratorType::Flat,
+                LevelGenerator::Overworld => GeneratorType::Overworld,
+                LevelGenerator::Void => GeneratorType::Void,
+            },
+            ..Default::default()
+        };
+        if let Some(layers) = &config.flat_world_layers {
+            level.flat_world_layers = layers.clone();
+        }
+        level.apply_settings(config);
+        level
+    }
+
+    /// Reads the world saved at `path`, or creates it from `config` and saves it there if
+    /// there is none yet. Game mode, view distance and movement authority always come from
+    /// `config`.
+    pub fn load(config: &LevelConfig, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let Some(level_dat) = read_level_dat(path)? else {
+            let level = LevelResource {
+                path: Some(path.to_owned()),
+                ..Self::from_config(config)
+            };
+            level.save()?;
+            return Ok(level);
+        };
+        let get = |key: &str| {
+            level_dat
+                .get(key)
+                .with_context(|| format!("{} has no {key}", path.display()))
+        };
+        let int = |key: &str| -> Result<i32> {
+            get(key)?
+                .as_int()
+                .copied()
+                .with_context(|| format!("{key} in {} is not an int", path.display()))
+        };
+        let seed = get("RandomSeed")?
+            .as_long()
+            .with_context(|| format!("RandomSeed in {} is not a long", path.display()))?;
+        let mut level = LevelResource {
+            seed: *seed as u64,
+            generator: int("Generator")?.try_into()?,
+            spawn_position: (int("SpawnX")?, int("SpawnY")?, int("SpawnZ")?),
+            path: Some(path.to_owned()),
+            ..Default::default()
+        };
+        if let Some(layers) = level_dat.get("FlatWorldLayers").and_then(Value::as_str) {
+            level.flat_world_layers = layers.to_owned();
+        }
+        if let Some(v1_villagers) = level_dat.get("SpawnV1Villagers").and_then(Value::as_byte) {
+            level.only_spawn_v1_villagers = *v1_villagers != 0;
+        }
+        level.level_dat = level_dat;
+        level.apply_settings(config);
+        Ok(level)
+    }
+
+    /// Settings of `config` that belong to the server rather than the world.
+    fn apply_settings(&mut self, config: &LevelConfig) {
+        self.game_mode = match config.game_mode {
+            LevelGameMode::Survival => GameMode::Survival,
+            LevelGameMode::Creative => GameMode::Creative,
+            LevelGameMode::Adventure => GameMode::Adventure,
+            LevelGameMode::Spectator => GameMode::Spectator,
+        };
+        self.view_distance = config.view_distance;
+        self.movement_authority = match config.movement_authority {
+            LevelMovementAuthority::Client => MovementAuthority::Client,
+            LevelMovementAuthority::Server => MovementAuthority::Server,
+            LevelMovementAuthority::ServerWithRewind => MovementAuthority::ServerWithRewind,
+        };
+    }
+
+    /// Writes the world back to [`Self::path`], if it has one.
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        let (x, y, z) = self.spawn_position;
+        let mut level_dat = self.level_dat.clone();
+        level_dat
+            .entry("LevelName".to_owned())
+            .or_insert_with(|| Value::String("hob".to_owned()));
+        level_dat.extend([
+            ("RandomSeed".to_owned(), Value::Long(self.seed as i64)),
+            ("Generator".to_owned(), Value::Int(self.generator as i32)),
+            (
+                "FlatWorldLayers".to_owned(),
+                Value::String(self.flat_world_layers.clone()),
+            ),
+            ("GameType".to_owned(), Value::Int(self.game_mode as i32)),
+            ("SpawnX".to_owned(), Value::Int(x)),
+            ("SpawnY".to_owned(), Value::Int(y)),
+            ("SpawnZ".to_owned(), Value::Int(z)),
+            (
+                "SpawnV1Villagers".to_owned(),
+                Value::Byte(self.only_spawn_v1_villagers as i8),
+            ),
+        ]);
+        write_level_dat(path, &level_dat)
+    }
+}
+
+/// Loaded chunks. Missing chunks are generated on the rayon pool by [`Self::request`]
+/// and become visible once `handle_world` has collected them.
+pub struct ChunkStorageResource {
+    dimension: Dimension,
+    generator: Arc<dyn WorldGenerator>,
+    chunks: HashMap<ChunkPos, Arc<Chunk>>,
+    pending: HashSet<ChunkPos>,
+    /// Kept loaded whoever views them; see [`Self::pin_spawn_area`].
+    pinned: HashSet<ChunkPos>,
+    generated_tx: UnboundedSender<Chunk>,
+    generated_rx: UnboundedReceiver<Chunk>,
+}
+impl ChunkStorageResource {
+    pub fn new(level: &LevelResource) -> Self {
+        let (generated_tx, generated_rx) = mpsc::unbounded_channel();
+        ChunkStorageResource {
+            dimension: level.dimension,
+            generator: create_generator(level),
+            chunks: HashMap::new(),
+            pending: HashSet::new(),
+            pinned: HashSet::new(),
+            generated_tx,
+            generated_rx,
+        }
+    }
+    pub fn get(&self, pos: ChunkPos) -> Option<Arc<Chunk>> {
+        self.chunks.get(&pos).cloned()
+    }
+    /// Block at world coordinates, or `None` when its chunk is not loaded.
+    pub fn block_at(&self, x: i32, y: i32, z: i32) -> Option<&BlockState> {
+        self.chunks.get(&ChunkPos::from_block(x, z))?.get_block(
+            (x & 0xf) as usize,
+            y,
+            (z & 0xf) as usize,
+        )
+    }
+    /// Schedules generation of `pos` unless it is already loaded or in progress.
+    pub fn request(&mut self, pos: ChunkPos) {
+        if self.chunks.contains_key(&pos) || !self.pending.insert(pos) {
+            return;
+        }
+        let generator = Arc::clone(&self.generator);
+        let dimension = self.dimension;
+        let generated = self.generated_tx.clone();
+        specs::rayon::spawn(move || {
+            let _ = generated.send(generator.generate(pos, dimension));
+        });
+    }
+    /// Generates `pos` on the calling thread. Prefer [`Self::request`] during ticks.
+    pub fn get_or_generate(&mut self, pos: ChunkPos) -> Arc<Chunk> {
+        if let Some(chunk) = self.get(pos) {
+            return chunk;
+        }
+        self.insert(self.generator.generate(pos, self.dimension));
+        self.chunks[&pos].clone()
+    }
+    /// Y of the highest non-air block in the column, generating its chunk if needed.
+    pub fn highest_block(&mut self, x: i32, z: i32) -> Option<i32> {
+        let chunk = self.get_or_generate(ChunkPos::from_block(x, z));
+        chunk.highest_block((x & 0xf) as usize, (z & 0xf) as usize)
+    }
+    /// Generates the chunks within [`SPAWN_CHUNK_RADIUS`] of the spawn point on the calling
+    /// thread and keeps them loaded.
+    pub fn pin_spawn_area(&mut self, level: &LevelResource) {
+        let (x, _, z) = level.spawn_position;
+        let center = ChunkPos::from_block(x, z);
+        let r = SPAWN_CHUNK_RADIUS;
+        for dx in -r..=r {
+            for dz in -r..=r {
+                let pos = ChunkPos::new(center.x + dx, center.z + dz);
+                if in_radius(center, pos, r) {
+                    self.get_or_generate(pos);
+                    self.pinned.insert(pos);
+                }
+            }
+        }
+    }
+    pub fn is_pending(&self, pos: ChunkPos) -> bool {
+        self.pending.contains(&pos)
+    }
+    pub fn insert(&mut self, chunk: Chunk) {
+        self.pending.remove(&chunk.pos);
+        self.chunks.insert(chunk.pos, Arc::new(chunk));
+    }
+    pub fn unload(&mut self, pos: ChunkPos) -> Option<Arc<Chunk>> {
+        self.pending.remove(&pos);
+        self.chunks.remove(&pos)
+    }
+    pub fn loaded(&self) -> usize {
+        self.chunks.len()
+    }
+    /// Unloads the chunks `f` rejects, except pinned ones.
+    pub fn retain(&mut self, mut f: impl FnMut(ChunkPos) -> bool) {
+        let pinned = &self.pinned;
+        self.chunks.retain(|pos, _| pinned.contains(pos) || f(*pos));
+        self.pending.retain(|pos| f(*pos));
+    }
+    /// Moves finished chunks from the generator pool into storage.
+    pub fn collect_generated(&mut self) -> Vec<ChunkPos> {
+        let mut generated = Vec::new();
+        while let Ok(chunk) = self.generated_rx.try_recv() {
+            if !self.pending.contains(&chunk.pos) {
+                continue;
+            }
+            generated.push(chunk.pos);
+            self.insert(chunk);
+        }
+        generated
+    }
+}
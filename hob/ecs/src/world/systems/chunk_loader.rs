@@ -0,0 +1,155 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use hob_protocol::packet::{
+    network_chunk_publisher_update::NetworkChunkPublisherUpdatePacket,
+    play_status::PlayStatusPacket,
+};
+use specs::prelude::*;
+
+use crate::{
+    player::components::{
+        chunk::{in_radius, ChunkRadiusComponent, ChunkViewComponent},
+        connection::ConnectionStreamComponent,
+        PositionComponent,
+    },
+    world::{chunk::ChunkPos, resources::ChunkStorageResource},
+};
+
+/// Chunks sent to a single player per tick.
+const CHUNKS_PER_TICK: usize = 8;
+/// Chunks handed to the generator per player per tick.
+const REQUESTS_PER_TICK: usize = 32;
+/// Radius that must be sent before `PlayStatus::PlayerSpawn`.
+const SPAWN_RADIUS: i32 = 2;
+/// How long a chunk stays loaded once no player views it, so that players walking back and
+/// forth or rejoining don't have it generated again.
+pub const UNLOAD_AFTER: Duration = Duration::from_secs(30);
+
+/// Streams chunks around each player in spiral order and unloads the ones nobody has
+/// viewed for [`UNLOAD_AFTER`].
+pub struct ChunkLoaderSystem {
+    unload_after: Duration,
+    /// Loaded chunks outside every view, and since when.
+    unviewed: HashMap<ChunkPos, Instant>,
+    spirals: HashMap<i32, Arc<[(i32, i32)]>>,
+}
+impl Default for ChunkLoaderSystem {
+    fn default() -> Self {
+        ChunkLoaderSystem {
+            unload_after: UNLOAD_AFTER,
+            unviewed: HashMap::new(),
+            spirals: HashMap::new(),
+        }
+    }
+}
+impl ChunkLoaderSystem {
+    pub fn with_unload_after(mut self, unload_after: Duration) -> Self {
+        self.unload_after = unload_after;
+        self
+    }
+    /// Offsets of [`spiral_offsets`]; views share a handful of radii, so each is built once.
+    fn spiral(&mut self, radius: i32) -> Arc<[(i32, i32)]> {
+        let spiral = self
+            .spirals
+            .entry(radius)
+            .or_insert_with(|| spiral_offsets(radius).into());
+        Arc::clone(spiral)
+    }
+}
+
+impl<'a> System<'a> for ChunkLoaderSystem {
+    type SystemData = (
+        WriteExpect<'a, ChunkStorageResource>,
+        ReadStorage<'a, PositionComponent>,
+        ReadStorage<'a, ChunkRadiusComponent>,
+        WriteStorage<'a, ChunkViewComponent>,
+        WriteStorage<'a, ConnectionStreamComponent>,
+    );
+
+    fn run(&mut self, (mut storage, positions, radii, mut views, mut conns): Self::SystemData) {
+        let mut viewed = HashSet::new();
+        for (pos, radius, view, conn) in (&positions, &radii, &mut views, &mut conns).join() {
+            let (x, y, z) = pos.block_pos();
+            let center = ChunkPos::from_block(x, z);
+            if view.center != Some(center) || view.radius != radius.0 {
+                view.center = Some(center);
+                view.radius = radius.0;
+                view.sent.retain(|c| in_radius(center, *c, radius.0));
+                conn.send_packet(NetworkChunkPublisherUpdatePacket {
+                    position: (x, y, z),
+                    radius: (radius.0 as u32) << 4,
+                    saved_chunks: Vec::new(),
+                });
+            }
+
+            let (mut sends, mut requests) = (CHUNKS_PER_TICK, REQUESTS_PER_TICK);
+            for &(dx, dz) in self.spiral(radius.0).iter() {
+                let chunk_pos = ChunkPos::new(center.x + dx, center.z + dz);
+                viewed.insert(chunk_pos);
+                if view.sent.contains(&chunk_pos) {
+                    continue;
+                }
+                match storage.get(chunk_pos) {
+                    Some(chunk) if sends > 0 => {
+                        conn.send_packet(chunk.to_packet());
+                        view.sent.insert(chunk_pos);
+                        sends -= 1;
+                    }
+                    None if requests > 0 && !storage.is_pending(chunk_pos) => {
+                        storage.request(chunk_pos);
+                        requests -= 1;
+                    }
+                    _ => {}
+                }
+            }
+
+            if !view.spawned
+                && self
+                    .spiral(SPAWN_RADIUS.min(radius.0))
+                    .iter()
+                    .all(|(dx, dz)| {
+                        view.sent
+                            .contains(&ChunkPos::new(center.x + dx, center.z + dz))
+                    })
+            {
+                conn.send_packet(PlayStatusPacket::PlayerSpawn);
+                view.spawned = true;
+            }
+        }
+
+        let now = Instant::now();
+        let mut unviewed = HashMap::new();
+        storage.retain(|pos| {
+            if viewed.contains(&pos) {
+                return true;
+            }
+            let since = self.unviewed.get(&pos).copied().unwrap_or(now);
+            if now.duration_since(since) >= self.unload_after {
+                return false;
+            }
+            unviewed.insert(pos, since);
+            true
+        });
+        self.unviewed = unviewed;
+    }
+}
+
+/// Chunk offsets within `radius`, ring by ring outward from the center.
+pub fn spiral_offsets(radius: i32) -> Vec<(i32, i32)> {
+    let center = ChunkPos::new(0, 0);
+    let mut offsets = vec![(0, 0)];
+    for ring in 1..=radius.max(0) {
+        for i in -ring..=ring {
+            offsets.extend([(i, -ring), (i, ring)]);
+        }
+        for i in 1 - ring..ring {
+            offsets.extend([(-ring, i), (ring, i)]);
+        }
+    }
+    offsets.retain(|&(x, z)| in_radius(center, ChunkPos::new(x, z), radius));
+    offsets
+}
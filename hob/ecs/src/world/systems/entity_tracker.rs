@@ -0,0 +1,241 @@
+use std::collections::{HashMap, HashSet};
+
+use hob_protocol::packet::{
+    add_actor::AddActorPacket,
+    add_player::{AbilityData, AddPlayerPacket},
+    remove_actor::RemoveActorPacket,
+    PacketKind,
+};
+use specs::prelude::*;
+
+use crate::{
+    player::{
+        components::{
+            chunk::ChunkViewComponent, connection::ConnectionStreamComponent, PositionComponent,
+            RotationComponent, VelocityComponent, VisibleEntitiesComponent,
+        },
+        resources::OnlinePlayers,
+    },
+    world::{
+        chunk::ChunkPos,
+        components::{
+            ActorMetadataComponent, ActorTypeComponent, DimensionComponent, RuntimeIdComponent,
+            UniqueIdComponent,
+        },
+        resources::LevelResource,
+    },
+};
+
+/// Upper bound on the horizontal distance, in blocks, at which entities are spawned.
+pub const MAX_TRACKING_DISTANCE: f32 = 128.0;
+
+/// Spawns or removes entities on each client as they enter or leave the player's range.
+pub struct EntityTrackerSystem;
+
+struct TrackerData<'a> {
+    entities: Entities<'a>,
+    level: ReadExpect<'a, LevelResource>,
+    runtime_ids: ReadStorage<'a, RuntimeIdComponent>,
+    unique_ids: ReadStorage<'a, UniqueIdComponent>,
+    dimensions: ReadStorage<'a, DimensionComponent>,
+    actor_types: ReadStorage<'a, ActorTypeComponent>,
+    metadata: ReadStorage<'a, ActorMetadataComponent>,
+    online: ReadExpect<'a, OnlinePlayers>,
+    positions: ReadStorage<'a, PositionComponent>,
+    rotations: ReadStorage<'a, RotationComponent>,
+    velocities: ReadStorage<'a, VelocityComponent>,
+    views: ReadStorage<'a, ChunkViewComponent>,
+    visible: WriteStorage<'a, VisibleEntitiesComponent>,
+    conns: WriteStorage<'a, ConnectionStreamComponent>,
+}
+
+impl<'a> System<'a> for EntityTrackerSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, LevelResource>,
+        (
+            ReadStorage<'a, RuntimeIdComponent>,
+            ReadStorage<'a, UniqueIdComponent>,
+            ReadStorage<'a, DimensionComponent>,
+            ReadStorage<'a, ActorTypeComponent>,
+            ReadStorage<'a, ActorMetadataComponent>,
+        ),
+        ReadExpect<'a, OnlinePlayers>,
+        (
+            ReadStorage<'a, PositionComponent>,
+            ReadStorage<'a, RotationComponent>,
+            ReadStorage<'a, VelocityComponent>,
+        ),
+        ReadStorage<'a, ChunkViewComponent>,
+        WriteStorage<'a, VisibleEntitiesComponent>,
+        WriteStorage<'a, ConnectionStreamComponent>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            level,
+            (runtime_ids, unique_ids, dimensions, actor_types, metadata),
+            online,
+            (positions, rotations, velocities),
+            views,
+            visible,
+            conns,
+        ): Self::SystemData,
+    ) {
+        let mut data = TrackerData {
+            entities,
+            level,
+            runtime_ids,
+            unique_ids,
+            dimensions,
+            actor_types,
+            metadata,
+            online,
+            positions,
+            rotations,
+            velocities,
+            views,
+            visible,
+            conns,
+        };
+        update_visibility(&mut data);
+    }
+}
+
+fn update_visibility(data: &mut TrackerData) {
+    // Players are only spawned for others once they have spawned themselves.
+    let candidates: Vec<_> = (
+        &data.entities,
+        &data.runtime_ids,
+        &data.unique_ids,
+        &data.dimensions,
+        &data.positions,
+    )
+        .join()
+        .filter(|(ent, ..)| {
+            data.views
+                .get(*ent)
+                .map_or(data.actor_types.contains(*ent), |view| {
+                    view.spawned && data.online.get(*ent).is_some()
+                })
+        })
+        .map(|(ent, _, unique_id, dim, pos)| (ent, unique_id.0, dim.0, (pos.x, pos.y, pos.z)))
+        .collect();
+
+    let viewers: Vec<Entity> = (&data.entities, &data.views, &data.visible)
+        .join()
+        .filter(|(_, view, _)| view.spawned)
+        .map(|(ent, ..)| ent)
+        .collect();
+    for viewer in viewers {
+        let (Some(view), Some(dim), Some(pos)) = (
+            data.views.get(viewer),
+            data.dimensions.get(viewer),
+            data.positions.get(viewer),
+        ) else {
+            continue;
+        };
+        let range = (view.radius as f32 * 16.0).min(MAX_TRACKING_DISTANCE);
+        let in_range: HashMap<Entity, i64> = candidates
+            .iter()
+            .filter(|(ent, _, other_dim, (x, _, z))| {
+                let (dx, dz) = (x - pos.x, z - pos.z);
+                *ent != viewer
+                    && *other_dim == dim.0
+                    && dx * dx + dz * dz <= range * range
+                    && view
+                        .sent
+                        .contains(&ChunkPos::from_block(x.floor() as i32, z.floor() as i32))
+            })
+            .map(|(ent, unique_id, ..)| (*ent, *unique_id))
+            .collect();
+
+        let visible = &data.visible.get(viewer).unwrap().entities;
+        let removed: Vec<i64> = visible
+            .iter()
+            .filter(|(ent, _)| !in_range.contains_key(ent))
+            .map(|(_, unique_id)| *unique_id)
+            .collect();
+        let added: HashSet<Entity> = in_range
+            .keys()
+            .filter(|ent| !visible.contains_key(ent))
+            .copied()
+            .collect();
+        if removed.is_empty() && added.is_empty() {
+            continue;
+        }
+
+        let packets: Vec<PacketKind> = added
+            .iter()
+            .filter_map(|ent| spawn_packet(data, *ent))
+            .collect();
+        let Some(conn) = data.conns.get_mut(viewer) else {
+            continue;
+        };
+        for unique_id in removed {
+            conn.send_packet(RemoveActorPacket { unique_id });
+        }
+        for packet in packets {
+            conn.send_packet(packet);
+        }
+        data.visible.get_mut(viewer).unwrap().entities = in_range;
+    }
+}
+
+fn spawn_packet(data: &TrackerData, ent: Entity) -> Option<PacketKind> {
+    let runtime_id = data.runtime_ids.get(ent)?.0;
+    let unique_id = data.unique_ids.get(ent)?.0;
+    let pos = data.positions.get(ent)?;
+    let velocity = data
+        .velocities
+        .get(ent)
+        .map_or((0.0, 0.0, 0.0), |v| (v.x, v.y, v.z));
+    let (pitch, yaw, head_yaw) = data
+        .rotations
+        .get(ent)
+        .map_or((0.0, 0.0, 0.0), |r| (r.pitch, r.yaw, r.head_yaw));
+    let metadata = data
+        .metadata
+        .get(ent)
+        .map(|m| m.metadata().clone())
+        .unwrap_or_default();
+    if let Some(player) = data.online.get(ent) {
+        return Some(
+            AddPlayerPacket {
+                uuid: player.uuid,
+                username: player.name.clone(),
+                runtime_id,
+                platform_chat_id: "".to_string(),
+                position: (pos.x, pos.y, pos.z),
+                velocity,
+                pitch,
+                yaw,
+                head_yaw,
+                game_mode: data.level.game_mode,
+                metadata,
+                abilities: AbilityData::new(unique_id),
+                device_id: player.device_id.clone(),
+                build_platform: player.build_platform,
+            }
+            .into(),
+        );
+    }
+    Some(
+        AddActorPacket {
+            unique_id,
+            runtime_id,
+            actor_type: data.actor_types.get(ent)?.0.clone(),
+            position: (pos.x, pos.y, pos.z),
+            velocity,
+            pitch,
+            yaw,
+            head_yaw,
+            body_yaw: yaw,
+            attributes: Vec::new(),
+            metadata,
+        }
+        .into(),
+    )
+}
@@ -0,0 +1,56 @@
+use hob_protocol::packet::set_actor_data::SetActorDataPacket;
+use specs::prelude::*;
+
+use crate::{
+    player::components::{
+        connection::ConnectionStreamComponent, MovementStateComponent, VisibleEntitiesComponent,
+    },
+    world::components::{ActorMetadataComponent, RuntimeIdComponent},
+};
+
+/// Sends the actor data changed this tick to the entity itself and to everyone who has it spawned.
+/// Players' data carries the tick of their last input, as `MovePlayer` does, so that their
+/// client orders it against its movement prediction.
+pub struct MetadataSyncSystem;
+
+impl<'a> System<'a> for MetadataSyncSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, RuntimeIdComponent>,
+        WriteStorage<'a, ActorMetadataComponent>,
+        ReadStorage<'a, MovementStateComponent>,
+        ReadStorage<'a, VisibleEntitiesComponent>,
+        WriteStorage<'a, ConnectionStreamComponent>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, runtime_ids, mut metadata, states, visible, mut conns): Self::SystemData,
+    ) {
+        let mut changed = Vec::new();
+        for (ent, runtime_id, metadata) in (&entities, &runtime_ids, &mut metadata).join() {
+            if !metadata.is_dirty() {
+                continue;
+            }
+            let packet = SetActorDataPacket {
+                runtime_id: runtime_id.0,
+                metadata: metadata.take_dirty(),
+                tick: states.get(ent).map_or(0, |state| state.tick),
+            };
+            changed.push((ent, packet));
+        }
+        if changed.is_empty() {
+            return;
+        }
+        for (ent, conn) in (&entities, &mut conns).join() {
+            for (owner, packet) in changed.iter() {
+                let spawned = visible
+                    .get(ent)
+                    .is_some_and(|v| v.entities.contains_key(owner));
+                if *owner == ent || spawned {
+                    conn.send_packet(packet.clone());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod chunk_loader;
+pub mod entity_tracker;
+pub mod metadata;
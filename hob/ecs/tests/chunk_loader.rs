@@ -0,0 +1,128 @@
+use std::{collections::HashSet, thread::sleep, time::Duration};
+
+use hob_ecs::{
+    player::components::{
+        chunk::{ChunkRadiusComponent, ChunkViewComponent},
+        connection::ConnectionStreamComponent,
+        PositionComponent,
+    },
+    world::{
+        chunk::ChunkPos,
+        resources::{ChunkStorageResource, LevelResource},
+        systems::chunk_loader::{spiral_offsets, ChunkLoaderSystem},
+    },
+    Builder, RunNow, World, WorldExt,
+};
+use hob_server::outbound::{self, OutboundReceiver};
+use tokio::sync::{mpsc, oneshot};
+
+const GRACE: Duration = Duration::from_millis(200);
+
+#[test]
+fn spiral_goes_outward_once() {
+    let offsets = spiral_offsets(3);
+    let unique: HashSet<_> = offsets.iter().collect();
+    assert_eq!(unique.len(), offsets.len());
+    assert_eq!(offsets.len(), 29);
+    assert_eq!(offsets[0], (0, 0));
+    let distances: Vec<_> = offsets.iter().map(|(x, z)| x.abs().max(z.abs())).collect();
+    assert!(distances.windows(2).all(|w| w[0] <= w[1]));
+    assert!(offsets.iter().all(|(x, z)| x * x + z * z <= 9));
+    assert_eq!(spiral_offsets(0), [(0, 0)]);
+}
+
+fn world() -> World {
+    let mut world = World::new();
+    world.register::<PositionComponent>();
+    world.register::<ChunkRadiusComponent>();
+    world.register::<ChunkViewComponent>();
+    world.register::<ConnectionStreamComponent>();
+    world.insert(ChunkStorageResource::new(&LevelResource::default()));
+    world
+}
+
+/// A player at block `x`, 0, who views chunks within a radius of 1.
+fn player(world: &mut World, x: f32) -> (hob_ecs::Entity, OutboundReceiver) {
+    let (_, from_client) = mpsc::channel(8);
+    let (to_client, packets) = outbound::channel(1 << 20, Duration::from_secs(15));
+    let (_, disconnect) = oneshot::channel();
+    let entity = world
+        .create_entity()
+        .with(PositionComponent::new(x, 0.0, 0.0))
+        .with(ChunkRadiusComponent(1))
+        .with(ChunkViewComponent::default())
+        .with(ConnectionStreamComponent::new(
+            from_client,
+            to_client,
+            disconnect,
+            "Steve",
+        ))
+        .build();
+    (entity, packets)
+}
+
+fn loaded(world: &World, pos: ChunkPos) -> bool {
+    world
+        .read_resource::<ChunkStorageResource>()
+        .get(pos)
+        .is_some()
+}
+
+#[test]
+fn unviewed_chunks_unload_after_a_grace_period() {
+    let mut world = world();
+    let (near, far) = (ChunkPos::new(0, 0), ChunkPos::new(20, 0));
+    {
+        let mut storage = world.write_resource::<ChunkStorageResource>();
+        storage.get_or_generate(near);
+        storage.get_or_generate(far);
+    }
+    let mut loader = ChunkLoaderSystem::default().with_unload_after(GRACE);
+
+    // Nobody online: everything stays for the grace period, then goes.
+    loader.run_now(&world);
+    assert!(loaded(&world, near) && loaded(&world, far));
+    sleep(GRACE);
+    loader.run_now(&world);
+    assert!(!loaded(&world, near) && !loaded(&world, far));
+
+    // A viewed chunk stays however long it is viewed; leaving restarts the wait.
+    world
+        .write_resource::<ChunkStorageResource>()
+        .get_or_generate(near);
+    let (steve, _packets) = player(&mut world, 8.0);
+    loader.run_now(&world);
+    sleep(GRACE);
+    loader.run_now(&world);
+    assert!(loaded(&world, near));
+
+    *world
+        .write_storage::<PositionComponent>()
+        .get_mut(steve)
+        .unwrap() = PositionComponent::new(20.0 * 16.0, 0.0, 0.0);
+    loader.run_now(&world);
+    assert!(loaded(&world, near));
+    sleep(GRACE);
+    loader.run_now(&world);
+    assert!(!loaded(&world, near));
+}
+
+#[test]
+fn spawn_area_stays_loaded() {
+    let world = world();
+    let level = LevelResource {
+        spawn_position: (100, 0, -40),
+        ..Default::default()
+    };
+    world
+        .write_resource::<ChunkStorageResource>()
+        .pin_spawn_area(&level);
+    let spawn = ChunkPos::from_block(100, -40);
+    assert_eq!(world.read_resource::<ChunkStorageResource>().loaded(), 13);
+    assert!(loaded(&world, spawn));
+
+    let mut loader = ChunkLoaderSystem::default().with_unload_after(Duration::ZERO);
+    loader.run_now(&world);
+    assert_eq!(world.read_resource::<ChunkStorageResource>().loaded(), 13);
+    assert!(loaded(&world, ChunkPos::new(spawn.x + 2, spawn.z)));
+}
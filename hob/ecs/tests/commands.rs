@@ -0,0 +1,237 @@
+use std::sync::{Arc, Mutex};
+
+use hob_ecs::{
+    command::{
+        args::{ArgValue, CommandArgs, Param, ParamKind},
+        execute, init_command, register_command, Command, CommandContext, CommandRegistry,
+        CommandResult, CommandRunNow, CommandSys,
+    },
+    player::{
+        components::{DisplayNameComponent, PositionComponent},
+        resources::{OnlinePlayer, OnlinePlayers},
+    },
+    world::components::{ActorTypeComponent, DimensionComponent},
+    Builder, Entity, World, WorldExt,
+};
+use hob_protocol::packet::{player_list::Skin, start_game::Dimension, Packet};
+use proto_bytes::BytesMut;
+use uuid::Uuid;
+
+#[derive(Clone, Default)]
+struct Record(Arc<Mutex<Option<CommandArgs>>>);
+impl Record {
+    fn take(&self) -> CommandArgs {
+        self.0.lock().unwrap().take().unwrap()
+    }
+}
+impl<'a> CommandSys<'a> for Record {
+    type SystemData = ();
+    fn run(&mut self, ctx: &CommandContext, _: Self::SystemData) -> CommandResult {
+        *self.0.lock().unwrap() = Some(ctx.args.clone());
+        Ok(format!("ran {}", ctx.name))
+    }
+}
+
+struct Fixture {
+    world: World,
+    steve: Entity,
+    alex: Entity,
+    zombie: Entity,
+    record: Record,
+}
+
+fn spawn_player(world: &mut World, name: &str, pos: (f32, f32, f32)) -> Entity {
+    let entity = world
+        .create_entity()
+        .with(PositionComponent::new(pos.0, pos.1, pos.2))
+        .with(DimensionComponent(Dimension::OverWorld))
+        .with(DisplayNameComponent(name.into()))
+        .build();
+    let runtime_id = entity.id() as u64 + 1;
+    world
+        .write_resource::<OnlinePlayers>()
+        .insert(OnlinePlayer {
+            entity,
+            xuid: runtime_id.to_string(),
+            uuid: Uuid::from_u64_pair(0, runtime_id),
+            name: name.into(),
+            runtime_id,
+            unique_id: runtime_id as i64,
+            skin: Skin::default(),
+            device_id: "".into(),
+            build_platform: 7,
+        });
+    entity
+}
+
+fn setup() -> Fixture {
+    let mut world = World::new();
+    world.register::<PositionComponent>();
+    world.register::<DimensionComponent>();
+    world.register::<DisplayNameComponent>();
+    world.register::<ActorTypeComponent>();
+    world.insert(OnlinePlayers::default());
+    init_command(&mut world);
+
+    let steve = spawn_player(&mut world, "Steve", (0.0, 0.0, 0.0));
+    let alex = spawn_player(&mut world, "Alex", (10.0, 0.0, 0.0));
+    let zombie = world
+        .create_entity()
+        .with(PositionComponent::new(3.0, 0.0, 0.0))
+        .with(DimensionComponent(Dimension::OverWorld))
+        .with(ActorTypeComponent("minecraft:zombie".into()))
+        .build();
+
+    let record = Record::default();
+    register_command(
+        &world,
+        Command::new("tp", "Teleports entities")
+            .alias("teleport")
+            .overload(vec![
+                Param::new("victim", ParamKind::Target),
+                Param::new("destination", ParamKind::Position),
+            ])
+            .overload(vec![Param::new("destination", ParamKind::Position)]),
+        record.clone(),
+    );
+    register_command(
+        &world,
+        Command::new("weather", "Sets the weather").overload(vec![
+            Param::new(
+                "type",
+                ParamKind::enumeration("WeatherType", &["clear", "rain"]),
+            ),
+            Param::new("duration", ParamKind::Int).optional(),
+        ]),
+        record.clone(),
+    );
+    register_command(
+        &world,
+        Command::new("say", "Broadcasts a message")
+            .overload(vec![Param::new("message", ParamKind::RawText)]),
+        record.clone(),
+    );
+    Fixture {
+        world,
+        steve,
+        alex,
+        zombie,
+        record,
+    }
+}
+
+fn targets(f: &Fixture, sender: Entity, selector: &str) -> Vec<Entity> {
+    let line = format!("tp {selector} 0 0 0");
+    execute(&f.world, sender, &line).unwrap();
+    f.record.take().targets("victim").unwrap().to_vec()
+}
+
+#[test]
+fn overloads_and_typed_arguments() {
+    let f = setup();
+    assert_eq!(
+        execute(&f.world, f.steve, "/TELEPORT ~ ~1.5 5"),
+        Ok("ran tp".into())
+    );
+    let args = f.record.take();
+    assert_eq!(args.overload, 1);
+    assert_eq!(args.position("destination"), Some((0.0, 1.5 - 1.62, 5.0)));
+
+    execute(&f.world, f.steve, "/tp Alex 1 2 3").unwrap();
+    let args = f.record.take();
+    assert_eq!(args.overload, 0);
+    assert_eq!(args.targets("victim"), Some(&[f.alex][..]));
+
+    execute(&f.world, f.steve, "/weather RAIN").unwrap();
+    let args = f.record.take();
+    assert_eq!(args.string("type"), Some("rain"));
+    assert_eq!(args.int("duration"), None);
+    execute(&f.world, f.steve, "/weather clear 600").unwrap();
+    assert_eq!(f.record.take().int("duration"), Some(600));
+
+    execute(&f.world, f.steve, "/say  hello   \"world\" ").unwrap();
+    assert_eq!(
+        f.record.take().get("message"),
+        Some(&ArgValue::String("hello   \"world\"".into()))
+    );
+}
+
+#[test]
+fn invalid_input_is_reported() {
+    let f = setup();
+    assert!(execute(&f.world, f.steve, "/nope").is_err());
+    assert!(execute(&f.world, f.steve, "/weather snow").is_err());
+    assert!(execute(&f.world, f.steve, "/weather clear soon").is_err());
+    assert!(execute(&f.world, f.steve, "/weather clear 1 2").is_err());
+    assert!(execute(&f.world, f.steve, "/tp Herobrine 0 0 0").is_err());
+    assert!(execute(&f.world, f.steve, "/tp @x 0 0 0").is_err());
+    assert!(execute(&f.world, f.steve, "/tp 0 0").is_err());
+    assert_eq!(
+        execute(&f.world, f.steve, "say ]"),
+        Err("Unexpected ] at 1".into())
+    );
+    assert!(execute(&f.world, f.steve, "/tp @e[type=zombie]] 0 0 0").is_err());
+    assert!(execute(&f.world, f.steve, "/say \"hello").is_err());
+}
+
+/// Runs the rest of the line as a command.
+struct Run;
+impl<'a> CommandRunNow<'a> for Run {
+    fn run_now(&mut self, ctx: &CommandContext, world: &'a World) -> CommandResult {
+        execute(world, ctx.sender, ctx.args.string("command").unwrap())
+    }
+}
+
+#[test]
+fn commands_can_run_commands() {
+    let f = setup();
+    register_command(
+        &f.world,
+        Command::new("run", "Runs a command")
+            .overload(vec![Param::new("command", ParamKind::RawText)]),
+        Run,
+    );
+    assert_eq!(
+        execute(&f.world, f.steve, "/run weather rain"),
+        Ok("ran weather".into())
+    );
+    assert_eq!(f.record.take().string("type"), Some("rain"));
+    assert_eq!(
+        execute(&f.world, f.steve, "/run run say hi"),
+        Err("/run can't run itself".into())
+    );
+    // Still usable afterwards.
+    assert!(execute(&f.world, f.steve, "/run say hi").is_ok());
+}
+
+#[test]
+fn selectors_resolve_against_entities() {
+    let f = setup();
+    assert_eq!(targets(&f, f.steve, "@s"), [f.steve]);
+    assert_eq!(targets(&f, f.steve, "@a"), [f.steve, f.alex]);
+    assert_eq!(targets(&f, f.steve, "@e"), [f.steve, f.alex, f.zombie]);
+    assert_eq!(targets(&f, f.alex, "@p"), [f.alex]);
+    assert_eq!(targets(&f, f.steve, "@p[name=!steve]"), [f.alex]);
+    assert_eq!(targets(&f, f.steve, "@e[type=zombie]"), [f.zombie]);
+    assert_eq!(targets(&f, f.steve, "@e[type=!player]"), [f.zombie]);
+    assert_eq!(targets(&f, f.steve, "@e[r=5]"), [f.steve, f.zombie]);
+    assert_eq!(targets(&f, f.steve, "@e[rm=1, c=1]"), [f.zombie]);
+    assert_eq!(targets(&f, f.steve, "@e[c=-1]"), [f.alex]);
+    assert_eq!(targets(&f, f.steve, "@a[x=9,y=0,z=0,r=2]"), [f.alex]);
+}
+
+#[test]
+fn available_commands_lists_registered_schemas() {
+    let f = setup();
+    let packet = f
+        .world
+        .read_resource::<CommandRegistry>()
+        .available_commands(|_| true);
+    let names: Vec<_> = packet.commands.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["say", "tp", "weather"]);
+    assert_eq!(packet.commands[1].aliases, ["teleport"]);
+    assert_eq!(packet.commands[1].overloads.len(), 2);
+    let mut bytes = BytesMut::new();
+    packet.encode(&mut bytes).unwrap();
+    assert!(!bytes.is_empty());
+}
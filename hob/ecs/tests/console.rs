@@ -0,0 +1,85 @@
+use hob_ecs::{
+    command::init_command,
+    console::{handle_console, init_console, ConsoleSender},
+    permission::{has_permission, init_permission, Permissions},
+    player::resources::OnlinePlayers,
+    rcon::{handle_rcon, init_rcon, RconSender},
+    shutdown::{init_shutdown, ShutdownHandle},
+    World, WorldExt,
+};
+use hob_protocol::packet::start_game::PermissionLevel;
+use hob_server::{
+    console::ConsoleInput,
+    rcon::{RconCommand, RconCommands},
+};
+use tokio::sync::{mpsc, oneshot};
+
+#[test]
+fn console_lines_run_as_commands() {
+    let mut world = World::new();
+    world.insert(OnlinePlayers::default());
+    init_command(&mut world);
+    let path = std::env::temp_dir().join(format!("hob-{}-console.json", std::process::id()));
+    init_permission(&mut world, &path);
+    init_shutdown(&mut world);
+
+    let (lines, input) = mpsc::channel(8);
+    let input = ConsoleInput::new(input);
+    let completions = input.completions.clone();
+    init_console(&mut world, input);
+    assert!(world.is_alive(world.read_resource::<ConsoleSender>().0));
+
+    lines.try_send("nonsense".into()).unwrap();
+    lines.try_send("/stop Restarting".into()).unwrap();
+    handle_console(&world);
+    let requested = world.read_resource::<ShutdownHandle>().requested().unwrap();
+    assert_eq!(requested.message.as_deref(), Some("Restarting"));
+
+    let commands = completions.get().commands;
+    assert!(commands.contains(&"stop".to_owned()));
+    assert!(commands.contains(&"op".to_owned()));
+}
+
+#[test]
+fn rcon_commands_are_answered() {
+    let mut world = World::new();
+    world.insert(OnlinePlayers::default());
+    init_command(&mut world);
+    let path = std::env::temp_dir().join(format!("hob-{}-rcon.json", std::process::id()));
+    init_permission(&mut world, &path);
+    init_shutdown(&mut world);
+
+    let (commands, queue) = mpsc::channel(8);
+    init_rcon(&mut world, RconCommands(queue));
+    let (reply, mut unknown) = oneshot::channel();
+    commands
+        .try_send(RconCommand {
+            line: "nonsense".into(),
+            reply,
+        })
+        .unwrap();
+    let (reply, mut stop) = oneshot::channel();
+    commands
+        .try_send(RconCommand {
+            line: "stop".into(),
+            reply,
+        })
+        .unwrap();
+    handle_rcon(&world);
+
+    assert_eq!(unknown.try_recv().unwrap(), "Unknown command: nonsense");
+    assert_eq!(stop.try_recv().unwrap(), "Stopping the server");
+    assert!(world
+        .read_resource::<ShutdownHandle>()
+        .requested()
+        .is_some());
+
+    // RCON has the rights of an operator, whatever they are.
+    let sender = world.read_resource::<RconSender>().0;
+    assert!(has_permission(&world, sender, "hob.command.op"));
+    world
+        .write_resource::<Permissions>()
+        .set_level_nodes(PermissionLevel::Operator, vec!["hob.command.stop".into()]);
+    assert!(has_permission(&world, sender, "hob.command.stop"));
+    assert!(!has_permission(&world, sender, "hob.command.op"));
+}
@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use hob_ecs::{
+    events::duplicate_login::{check_duplicate_login, DuplicateLoginEvent, DuplicateLoginPolicy},
+    player::{
+        components::connection::ConnectionStreamComponent,
+        resources::{OnlinePlayer, OnlinePlayers},
+    },
+    plugin::{Plugin, PluginSys},
+    Builder, Entity, World, WorldExt,
+};
+use hob_protocol::packet::{
+    disconnect::DisconnectFailReason, login::ExtraUserdata, player_list::Skin, PacketKind,
+};
+use hob_server::outbound::{self, OutboundReceiver};
+use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
+
+fn user(name: &str, xuid: &str) -> ExtraUserdata {
+    ExtraUserdata {
+        xuid: xuid.into(),
+        identity: Uuid::nil().to_string(),
+        display_name: name.into(),
+        title_id: "".into(),
+        sandbox_id: "".into(),
+    }
+}
+
+/// A world with Steve online; returns his entity and the receiving end of his connection.
+fn setup() -> (World, Entity, OutboundReceiver) {
+    let mut world = World::new();
+    world.register::<ConnectionStreamComponent>();
+    world.insert(Plugin::<DuplicateLoginEvent>::new());
+    world.insert(DuplicateLoginPolicy::default());
+
+    let (_, from_client) = mpsc::channel(8);
+    let (to_client, packets) = outbound::channel(1 << 20, Duration::from_secs(15));
+    let (_, disconnect) = oneshot::channel();
+    let steve = world
+        .create_entity()
+        .with(ConnectionStreamComponent::new(
+            from_client,
+            to_client,
+            disconnect,
+            "Steve",
+        ))
+        .build();
+    let mut online = OnlinePlayers::default();
+    online.insert(OnlinePlayer {
+        entity: steve,
+        xuid: "100".into(),
+        uuid: Uuid::nil(),
+        name: "Steve".into(),
+        runtime_id: 1,
+        unique_id: 1,
+        skin: Skin::default(),
+        device_id: "".into(),
+        build_platform: 7,
+    });
+    world.insert(online);
+    (world, steve, packets)
+}
+
+fn logged_in_elsewhere(packet: Option<PacketKind>) -> bool {
+    matches!(
+        packet,
+        Some(PacketKind::Disconnect(p))
+            if matches!(p.reason, DisconnectFailReason::LoggedInOtherLocation)
+    )
+}
+
+#[test]
+fn kick_old_session() {
+    let (world, steve, mut old) = setup();
+    let (to_new, mut new) = outbound::channel(1 << 20, Duration::from_secs(15));
+    assert!(check_duplicate_login(
+        &world,
+        &user("Steve", "100"),
+        &to_new
+    ));
+    assert!(logged_in_elsewhere(old.try_recv_packet()));
+    assert!(world
+        .read_storage::<ConnectionStreamComponent>()
+        .get(steve)
+        .unwrap()
+        .is_closing());
+    assert!(new.try_recv().is_none());
+
+    // Other accounts and offline-mode logins are never duplicates.
+    assert!(check_duplicate_login(&world, &user("Alex", "200"), &to_new));
+    assert!(check_duplicate_login(&world, &user("Steve", ""), &to_new));
+}
+
+#[test]
+fn reject_new_session() {
+    let (mut world, steve, mut old) = setup();
+    world.insert(DuplicateLoginPolicy::RejectNew);
+    let (to_new, mut new) = outbound::channel(1 << 20, Duration::from_secs(15));
+    assert!(!check_duplicate_login(
+        &world,
+        &user("Steve", "100"),
+        &to_new
+    ));
+    assert!(logged_in_elsewhere(new.try_recv_packet()));
+    assert!(old.try_recv().is_none());
+    assert!(!world
+        .read_storage::<ConnectionStreamComponent>()
+        .get(steve)
+        .unwrap()
+        .is_closing());
+}
+
+struct KeepFirst;
+impl<'a> PluginSys<'a, DuplicateLoginEvent> for KeepFirst {
+    type SystemData = ();
+    fn run(&mut self, event: &'a DuplicateLoginEvent, _: Self::SystemData) -> bool {
+        event.policy == DuplicateLoginPolicy::KickOld
+    }
+}
+
+#[test]
+fn plugin_overrides_policy() {
+    let (world, _, mut old) = setup();
+    world
+        .write_resource::<Plugin<DuplicateLoginEvent>>()
+        .add_plugin(KeepFirst);
+    let (to_new, mut new) = outbound::channel(1 << 20, Duration::from_secs(15));
+    assert!(!check_duplicate_login(
+        &world,
+        &user("steve", "100"),
+        &to_new
+    ));
+    assert!(logged_in_elsewhere(new.try_recv_packet()));
+    assert!(old.try_recv().is_none());
+}
@@ -0,0 +1,244 @@
+use std::time::Duration;
+
+use hob_ecs::{
+    player::{
+        components::{
+            chunk::ChunkViewComponent, connection::ConnectionStreamComponent, PositionComponent,
+            RotationComponent, VelocityComponent, VisibleEntitiesComponent,
+        },
+        resources::{OnlinePlayer, OnlinePlayers},
+        systems::player_list::PlayerListSystem,
+    },
+    world::{
+        chunk::ChunkPos,
+        components::{
+            ActorMetadataComponent, ActorTypeComponent, DimensionComponent, RuntimeIdComponent,
+            UniqueIdComponent,
+        },
+        resources::LevelResource,
+        systems::entity_tracker::EntityTrackerSystem,
+    },
+    Builder, Entity, RunNow, World, WorldExt,
+};
+use hob_protocol::packet::{player_list::Skin, start_game::Dimension};
+use hob_server::outbound::{self, OutboundReceiver};
+use proto_bytes::{Buf, Bytes, ConditionalBuf};
+use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
+
+const STEVE: i64 = 1;
+const ALEX: i64 = 2;
+const ZOMBIE: i64 = 3;
+
+/// What a client was told, read back from the encoded packets; none of them have decoders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Sent {
+    ListAdd(u64),
+    ListRemove(u64),
+    AddPlayer(u64),
+    AddActor(i64),
+    RemoveActor(i64),
+}
+
+fn sent(packet: Bytes) -> Sent {
+    let mut body = packet.clone();
+    match body.get_varint() {
+        0x0c => {
+            body.advance(16); // uuid
+            body.get_string_varint();
+            Sent::AddPlayer(body.get_varint())
+        }
+        0x0d => Sent::AddActor(body.get_zigzag64()),
+        0x0e => Sent::RemoveActor(body.get_zigzag64()),
+        0x3f => match body.get_u8() {
+            0 => Sent::ListAdd(body.get_varint()),
+            _ => Sent::ListRemove(body.get_varint()),
+        },
+        id => panic!("Unexpected packet {id:#x}: {packet:?}"),
+    }
+}
+
+/// Everything sent since the last call; the spawns of one tick come in no particular order.
+fn received(packets: &mut OutboundReceiver) -> Vec<Sent> {
+    let mut received: Vec<_> = std::iter::from_fn(|| packets.try_recv())
+        .map(sent)
+        .collect();
+    received.sort();
+    received
+}
+
+struct Tracker {
+    world: World,
+    player_list: PlayerListSystem,
+}
+impl Tracker {
+    fn new() -> Self {
+        let mut world = World::new();
+        world.register::<RuntimeIdComponent>();
+        world.register::<UniqueIdComponent>();
+        world.register::<DimensionComponent>();
+        world.register::<ActorTypeComponent>();
+        world.register::<ActorMetadataComponent>();
+        world.register::<PositionComponent>();
+        world.register::<RotationComponent>();
+        world.register::<VelocityComponent>();
+        world.register::<ChunkViewComponent>();
+        world.register::<VisibleEntitiesComponent>();
+        world.register::<ConnectionStreamComponent>();
+        world.insert(LevelResource::default());
+        world.insert(OnlinePlayers::default());
+        Tracker {
+            world,
+            player_list: PlayerListSystem::default(),
+        }
+    }
+
+    /// A spawned player at `x`, 0 in the overworld, who has been sent the chunks 16 blocks
+    /// around, and sees 4 chunks far.
+    fn join(&mut self, name: &str, id: i64, x: f32) -> (Entity, OutboundReceiver) {
+        let (_, from_client) = mpsc::channel(8);
+        let (to_client, packets) = outbound::channel(1 << 20, Duration::from_secs(15));
+        let (_, disconnect) = oneshot::channel();
+        let view = ChunkViewComponent {
+            center: Some(ChunkPos::new(0, 0)),
+            radius: 4,
+            sent: (-16..=16)
+                .flat_map(|x| (-16..=16).map(move |z| ChunkPos::new(x, z)))
+                .collect(),
+            spawned: true,
+        };
+        let entity = self
+            .world
+            .create_entity()
+            .with(RuntimeIdComponent(id as u64))
+            .with(UniqueIdComponent(id))
+            .with(DimensionComponent(Dimension::OverWorld))
+            .with(PositionComponent::new(x, 0.0, 0.0))
+            .with(view)
+            .with(VisibleEntitiesComponent::default())
+            .with(ConnectionStreamComponent::new(
+                from_client,
+                to_client,
+                disconnect,
+                name,
+            ))
+            .build();
+        self.world
+            .write_resource::<OnlinePlayers>()
+            .insert(OnlinePlayer {
+                entity,
+                xuid: id.to_string(),
+                uuid: Uuid::from_u64_pair(0, id as u64),
+                name: name.into(),
+                runtime_id: id as u64,
+                unique_id: id,
+                skin: Skin::default(),
+                device_id: "".into(),
+                build_platform: 7,
+            });
+        (entity, packets)
+    }
+
+    fn leave(&mut self, entity: Entity) {
+        self.world.write_resource::<OnlinePlayers>().remove(entity);
+        self.world.delete_entity(entity).unwrap();
+    }
+
+    fn place(&mut self, entity: Entity, x: f32, dimension: Dimension) {
+        *self
+            .world
+            .write_storage::<PositionComponent>()
+            .get_mut(entity)
+            .unwrap() = PositionComponent::new(x, 0.0, 0.0);
+        self.world
+            .write_storage::<DimensionComponent>()
+            .get_mut(entity)
+            .unwrap()
+            .0 = dimension;
+    }
+
+    /// Runs the systems in the dispatcher's order.
+    fn tick(&mut self) {
+        self.player_list.run_now(&self.world);
+        EntityTrackerSystem.run_now(&self.world);
+        self.world.maintain();
+    }
+}
+
+#[test]
+fn players_and_actors_follow_range_and_dimension() {
+    let mut tracker = Tracker::new();
+    tracker
+        .world
+        .create_entity()
+        .with(RuntimeIdComponent(ZOMBIE as u64))
+        .with(UniqueIdComponent(ZOMBIE))
+        .with(DimensionComponent(Dimension::OverWorld))
+        .with(ActorTypeComponent("minecraft:zombie".into()))
+        .with(PositionComponent::new(10.0, 0.0, 0.0))
+        .build();
+    let (_, mut steve) = tracker.join("Steve", STEVE, 0.0);
+    tracker.tick();
+    assert_eq!(
+        received(&mut steve),
+        [Sent::ListAdd(1), Sent::AddActor(ZOMBIE)]
+    );
+
+    // Alex joins within 64 blocks: both are listed and spawned for each other.
+    let (alex, mut alex_packets) = tracker.join("Alex", ALEX, 40.0);
+    tracker.tick();
+    assert_eq!(
+        received(&mut steve),
+        [Sent::ListAdd(1), Sent::AddPlayer(ALEX as u64)]
+    );
+    assert_eq!(
+        received(&mut alex_packets),
+        [
+            Sent::ListAdd(2),
+            Sent::AddPlayer(STEVE as u64),
+            Sent::AddActor(ZOMBIE)
+        ]
+    );
+    tracker.tick();
+    assert_eq!(received(&mut steve), []);
+    assert_eq!(received(&mut alex_packets), []);
+
+    // Out of range of Steve and the zombie; still on the player list.
+    tracker.place(alex, 120.0, Dimension::OverWorld);
+    tracker.tick();
+    assert_eq!(received(&mut steve), [Sent::RemoveActor(ALEX)]);
+    assert_eq!(
+        received(&mut alex_packets),
+        [Sent::RemoveActor(STEVE), Sent::RemoveActor(ZOMBIE)]
+    );
+
+    tracker.place(alex, 40.0, Dimension::OverWorld);
+    tracker.tick();
+    assert_eq!(received(&mut steve), [Sent::AddPlayer(ALEX as u64)]);
+    assert_eq!(
+        received(&mut alex_packets),
+        [Sent::AddPlayer(STEVE as u64), Sent::AddActor(ZOMBIE)]
+    );
+
+    // The same coordinates in another dimension are out of sight.
+    tracker.place(alex, 40.0, Dimension::Nether);
+    tracker.tick();
+    assert_eq!(received(&mut steve), [Sent::RemoveActor(ALEX)]);
+    assert_eq!(
+        received(&mut alex_packets),
+        [Sent::RemoveActor(STEVE), Sent::RemoveActor(ZOMBIE)]
+    );
+
+    tracker.place(alex, 40.0, Dimension::OverWorld);
+    tracker.tick();
+    assert_eq!(received(&mut steve), [Sent::AddPlayer(ALEX as u64)]);
+    received(&mut alex_packets);
+
+    // Leaving removes Alex from both the list and the world.
+    tracker.leave(alex);
+    tracker.tick();
+    assert_eq!(
+        received(&mut steve),
+        [Sent::ListRemove(1), Sent::RemoveActor(ALEX)]
+    );
+}
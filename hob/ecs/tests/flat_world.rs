@@ -0,0 +1,40 @@
+use hob_ecs::world::{
+    chunk::ChunkPos,
+    generator::{
+        flat::{BlockLayer, FlatGenerator, FlatWorldLayers},
+        WorldGenerator,
+    },
+};
+use hob_protocol::packet::start_game::Dimension;
+
+#[test]
+fn flat_world_layers() {
+    let json = "{\"biome_id\":1,\"block_layers\":[{\"block_name\":\"minecraft:bedrock\",\"count\":1},{\"block_name\":\"minecraft:dirt\",\"count\":2},{\"block_name\":\"minecraft:grass\",\"count\":1}],\"encoding_version\":6,\"structure_options\":null,\"world_version\":\"version.post_1_18\"}\n";
+    let layers = FlatWorldLayers::from_json(json).unwrap();
+    assert_eq!(layers.biome_id, 1);
+    assert_eq!(layers.encoding_version, 6);
+    assert_eq!(
+        layers.block_layers[1],
+        BlockLayer {
+            block_name: "minecraft:dirt".into(),
+            count: 2
+        }
+    );
+
+    let chunk = FlatGenerator::new(&layers).generate(ChunkPos::new(3, -2), Dimension::OverWorld);
+    let name = |y| chunk.get_block(5, y, 9).unwrap().name.as_str();
+    assert_eq!(name(-64), "minecraft:bedrock");
+    assert_eq!(name(-63), "minecraft:dirt");
+    assert_eq!(name(-62), "minecraft:dirt");
+    assert_eq!(name(-61), "minecraft:grass");
+    assert_eq!(name(-60), "minecraft:air");
+    assert_eq!(chunk.get_biome(0, 15), 1);
+    assert!(chunk.sub_chunks[1].is_empty());
+    assert_eq!(chunk.highest_block(0, 0), Some(-61));
+
+    let packet = chunk.to_packet();
+    assert_eq!((packet.chunk_x, packet.chunk_z), (3, -2));
+    assert_eq!(packet.sub_chunk_count, 1);
+    // version, layer count and y index of the lowest sub chunk
+    assert_eq!(packet.payload[..3], [9, 1, (-4i8) as u8]);
+}
@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+
+use hob_ecs::world::{
+    generator::GeneratorType,
+    level_dat::{read_level_dat, STORAGE_VERSION},
+    resources::{LevelResource, SPAWN_Y_SURFACE},
+};
+use hob_nbt::value::Value;
+use hob_protocol::packet::start_game::{GameMode, MovementAuthority};
+use hob_server::config::{LevelConfig, LevelGameMode, LevelGenerator, LevelMovementAuthority};
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("hob-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn new_world_keeps_its_seed() {
+    let path = temp_path("new-level.dat");
+    let config = LevelConfig {
+        generator: LevelGenerator::Void,
+        seed: Some(-42),
+        view_distance: 6,
+        ..Default::default()
+    };
+    let level = LevelResource::load(&config, &path).unwrap();
+    assert_eq!(level.seed, -42i64 as u64);
+    assert_eq!(level.generator, GeneratorType::Void);
+    assert_eq!(level.spawn_position, (0, SPAWN_Y_SURFACE, 0));
+    assert_eq!(level.view_distance, 6);
+    let bytes = std::fs::read(&path).unwrap();
+    assert_eq!(bytes[..4], STORAGE_VERSION.to_le_bytes());
+
+    // The world stays as created; the server settings follow the config.
+    let changed = LevelConfig {
+        generator: LevelGenerator::Overworld,
+        seed: Some(7),
+        game_mode: LevelGameMode::Creative,
+        movement_authority: LevelMovementAuthority::Server,
+        ..Default::default()
+    };
+    let mut level = LevelResource::load(&changed, &path).unwrap();
+    assert_eq!(level.seed, -42i64 as u64);
+    assert_eq!(level.generator, GeneratorType::Void);
+    assert!(matches!(level.game_mode, GameMode::Creative));
+    assert!(matches!(
+        level.movement_authority,
+        MovementAuthority::Server
+    ));
+    assert_eq!(level.view_distance, 10);
+
+    level.spawn_position = (100, 70, -20);
+    level.only_spawn_v1_villagers = true;
+    level.save().unwrap();
+    let level = LevelResource::load(&changed, &path).unwrap();
+    assert_eq!(level.spawn_position, (100, 70, -20));
+    assert!(level.only_spawn_v1_villagers);
+    let _ = std::fs::remove_file(&path);
+
+    // Without a seed every world gets its own.
+    let random = LevelConfig::default();
+    assert_ne!(
+        LevelResource::from_config(&random).seed,
+        LevelResource::from_config(&random).seed
+    );
+}
+
+#[test]
+fn bedrock_level_dat_is_read_and_kept() {
+    let path = temp_path("bedrock-level.dat");
+    std::fs::write(&path, include_bytes!("../../nbt/tests/level.dat")).unwrap();
+    let level = LevelResource::load(&LevelConfig::default(), &path).unwrap();
+    assert_eq!(level.seed, -8710180785603480548i64 as u64);
+    assert_eq!(level.generator, GeneratorType::Overworld);
+    assert_eq!(level.spawn_position, (12, SPAWN_Y_SURFACE, 41));
+    assert!(level.flat_world_layers.contains("minecraft:bedrock"));
+
+    // Saving only touches the keys hob uses.
+    level.save().unwrap();
+    let saved = read_level_dat(&path).unwrap().unwrap();
+    assert_eq!(saved.get("worldStartCount"), Some(&Value::Long(4294967294)));
+    assert_eq!(saved.get("LevelName"), level.level_dat.get("LevelName"));
+    assert_eq!(saved.get("GameType"), Some(&Value::Int(0)));
+    assert_eq!(saved.len(), level.level_dat.len());
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn broken_level_dat_is_refused() {
+    let path = temp_path("broken-level.dat");
+    std::fs::write(&path, [10, 0, 0, 0, 200, 0, 0, 0, 10]).unwrap();
+    let e = LevelResource::load(&LevelConfig::default(), &path).unwrap_err();
+    assert!(e.to_string().contains("Invalid level.dat"), "{e:#}");
+    // Left as it was, for whoever wants to recover it.
+    assert_eq!(std::fs::read(&path).unwrap().len(), 9);
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,70 @@
+use std::time::{Duration, Instant};
+
+use hob_ecs::{
+    player::{
+        components::{
+            chunk::ChunkViewComponent,
+            connection::{ConnectionStreamComponent, LoginStateComponent},
+        },
+        systems::login::LoginTimeoutSystem,
+    },
+    Builder, Entity, RunNow, World, WorldExt,
+};
+use hob_protocol::packet::{disconnect::DisconnectFailReason, PacketKind};
+use hob_server::{
+    initial_handler::LoginState,
+    outbound::{self, OutboundReceiver},
+};
+use tokio::sync::{mpsc, oneshot};
+
+fn player(
+    world: &mut World,
+    state: LoginState,
+    age: Duration,
+    spawned: bool,
+) -> (Entity, OutboundReceiver) {
+    let (to_client, packets) = outbound::channel(1 << 20, Duration::from_secs(15));
+    let entity = world
+        .create_entity()
+        .with(ConnectionStreamComponent::new(
+            mpsc::channel(1).1,
+            to_client,
+            oneshot::channel().1,
+            "Steve",
+        ))
+        .with(LoginStateComponent {
+            state,
+            since: Instant::now() - age,
+        })
+        .with(ChunkViewComponent {
+            spawned,
+            ..Default::default()
+        })
+        .build();
+    (entity, packets)
+}
+
+#[test]
+fn stuck_logins_are_kicked() {
+    let mut world = World::new();
+    world.register::<ConnectionStreamComponent>();
+    world.register::<LoginStateComponent>();
+    world.register::<ChunkViewComponent>();
+    let late = LoginState::ResourcePacks.timeout() + Duration::from_secs(1);
+    let (stuck, mut stuck_packets) = player(&mut world, LoginState::ResourcePacks, late, false);
+    let (loading, mut loading_packets) =
+        player(&mut world, LoginState::Spawning, Duration::ZERO, false);
+    let (spawned, _) = player(&mut world, LoginState::Spawning, Duration::ZERO, true);
+
+    LoginTimeoutSystem.run_now(&world);
+
+    assert!(matches!(
+        stuck_packets.try_recv_packet(),
+        Some(PacketKind::Disconnect(p)) if matches!(p.reason, DisconnectFailReason::LoadingStateTimeout)
+    ));
+    assert!(loading_packets.try_recv().is_none());
+    let logins = world.read_storage::<LoginStateComponent>();
+    assert!(!logins.contains(stuck));
+    assert!(logins.contains(loading));
+    assert!(!logins.contains(spawned));
+}
@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use hob_ecs::{
+    player::components::{
+        connection::ConnectionStreamComponent, MovementStateComponent, VisibleEntitiesComponent,
+    },
+    world::{
+        components::{ActorMetadataComponent, RuntimeIdComponent},
+        systems::metadata::MetadataSyncSystem,
+    },
+    Builder, RunNow, World, WorldExt,
+};
+use hob_protocol::packet::set_actor_data::{
+    actor_flag, metadata_key, ActorMetadata, MetadataValue,
+};
+use hob_server::outbound;
+use proto_bytes::BytesMut;
+use tokio::sync::{mpsc, oneshot};
+
+#[test]
+fn dirty_keys_only() {
+    let mut metadata = ActorMetadata::new();
+    metadata.set(metadata_key::SCALE, MetadataValue::Float(1.0));
+    metadata.set_flag(actor_flag::BREATHING, true);
+    let mut component = ActorMetadataComponent::new(metadata);
+    assert!(!component.is_dirty());
+
+    component.set(metadata_key::SCALE, MetadataValue::Float(1.0));
+    assert!(!component.is_dirty());
+    component.set_flag(actor_flag::SNEAKING, true);
+    component.set(
+        metadata_key::NAME_TAG,
+        MetadataValue::String("Steve".into()),
+    );
+    let dirty = component.take_dirty();
+    assert_eq!(dirty.len(), 2);
+    assert_eq!(
+        dirty.get(metadata_key::FLAGS),
+        Some(&MetadataValue::Long(
+            (1 << actor_flag::BREATHING) | (1 << actor_flag::SNEAKING)
+        ))
+    );
+    assert!(!component.is_dirty());
+    assert!(component.get_flag(actor_flag::SNEAKING));
+
+    component.set_flag(actor_flag::SWIMMING + 64, true);
+    assert!(component.take_dirty().get(metadata_key::FLAGS_2).is_some());
+}
+
+#[test]
+fn metadata_encoding() {
+    let mut metadata = ActorMetadata::new();
+    metadata.set(metadata_key::AIR, MetadataValue::Short(300));
+    metadata.set(metadata_key::SCALE, MetadataValue::Float(1.0));
+    metadata.set(
+        metadata_key::BED_POSITION,
+        MetadataValue::BlockPos(1, -1, 0),
+    );
+    let mut bytes = BytesMut::new();
+    metadata.encode(&mut bytes).unwrap();
+    assert_eq!(
+        bytes[..],
+        [
+            3, // entries
+            7, 1, 0x2c, 0x01, // air: short 300
+            28, 6, 2, 1, 0, // bed position: zigzag block pos
+            38, 3, 0x00, 0x00, 0x80, 0x3f, // scale: float 1.0
+        ]
+    );
+}
+
+#[test]
+fn player_data_carries_the_input_tick() {
+    let mut world = World::new();
+    world.register::<RuntimeIdComponent>();
+    world.register::<ActorMetadataComponent>();
+    world.register::<MovementStateComponent>();
+    world.register::<VisibleEntitiesComponent>();
+    world.register::<ConnectionStreamComponent>();
+    let (_, from_client) = mpsc::channel(8);
+    let (to_client, mut packets) = outbound::channel(1 << 20, Duration::from_secs(15));
+    let (_, disconnect) = oneshot::channel();
+    let mut metadata = ActorMetadataComponent::new(ActorMetadata::new());
+    metadata.set_flag(actor_flag::SNEAKING, true);
+    world
+        .create_entity()
+        .with(RuntimeIdComponent(5))
+        .with(metadata)
+        .with(MovementStateComponent {
+            tick: 300,
+            ..Default::default()
+        })
+        .with(VisibleEntitiesComponent::default())
+        .with(ConnectionStreamComponent::new(
+            from_client,
+            to_client,
+            disconnect,
+            "Steve",
+        ))
+        .build();
+
+    MetadataSyncSystem.run_now(&world);
+    let packet = packets.try_recv().unwrap();
+    assert_eq!(packet[..2], [0x27, 5]);
+    assert!(packet.ends_with(&[0, 0, 0xac, 0x02]), "{packet:?}");
+    assert!(packets.try_recv().is_none());
+}
@@ -0,0 +1,189 @@
+use std::time::Duration;
+
+use hob_ecs::{
+    player::{
+        components::{
+            connection::ConnectionStreamComponent, MovementStateComponent, PositionComponent,
+            RotationComponent,
+        },
+        movement::correct_movement,
+        validation::{validate_move, MoveContext, MovementViolation},
+    },
+    world::{
+        chunk::{BlockState, ChunkPos},
+        components::RuntimeIdComponent,
+        resources::{ChunkStorageResource, LevelResource},
+    },
+};
+use hob_protocol::packet::{move_player::MoveMode, start_game::MovementAuthority, PacketKind};
+use hob_server::outbound;
+use tokio::sync::{mpsc, oneshot};
+
+// The default flat world's grass layer tops out at y = -61.
+const EYE_ON_GROUND: f32 = -60.0 + 1.62;
+
+fn storage() -> ChunkStorageResource {
+    let mut storage = ChunkStorageResource::new(&LevelResource::default());
+    storage.get_or_generate(ChunkPos::new(0, 0));
+    storage
+}
+
+fn standing() -> MoveContext {
+    MoveContext {
+        from: (8.5, EYE_ON_GROUND, 8.5),
+        velocity_y: 0.0,
+        on_ground: true,
+        ticks: 1,
+        can_fly: false,
+    }
+}
+
+#[test]
+fn walking_and_jumping() {
+    let storage = storage();
+    let ctx = standing();
+    assert_eq!(
+        validate_move(&storage, &ctx, (8.7, EYE_ON_GROUND, 8.5)),
+        Ok(true)
+    );
+    assert_eq!(
+        validate_move(&storage, &ctx, (8.5, EYE_ON_GROUND + 0.42, 8.5)),
+        Ok(false)
+    );
+}
+
+#[test]
+fn speed_and_flight() {
+    let storage = storage();
+    let ctx = standing();
+    assert!(matches!(
+        validate_move(&storage, &ctx, (12.5, EYE_ON_GROUND, 8.5)),
+        Err(MovementViolation::TooFast { .. })
+    ));
+    let falling = MoveContext {
+        from: (8.5, EYE_ON_GROUND + 3.0, 8.5),
+        velocity_y: -0.2,
+        on_ground: false,
+        ..ctx
+    };
+    assert!(matches!(
+        validate_move(&storage, &falling, (8.5, EYE_ON_GROUND + 3.5, 8.5)),
+        Err(MovementViolation::Flying { .. })
+    ));
+    let creative = MoveContext {
+        can_fly: true,
+        ..falling
+    };
+    assert!(validate_move(&storage, &creative, (8.5, EYE_ON_GROUND + 3.5, 8.5)).is_ok());
+}
+
+#[test]
+fn collision() {
+    let mut storage = storage();
+    let mut chunk = (*storage.get(ChunkPos::new(0, 0)).unwrap()).clone();
+    chunk.set_block(9, -59, 8, &BlockState::new("minecraft:stone"));
+    storage.insert(chunk);
+    assert_eq!(
+        validate_move(&storage, &standing(), (9.5, EYE_ON_GROUND, 8.5)),
+        Err(MovementViolation::Collision { block: (9, -59, 8) })
+    );
+}
+
+#[test]
+fn idle_then_long_jump() {
+    let storage = storage();
+    let mut state = MovementStateComponent::default();
+    assert_eq!(state.input_ticks(100), 1);
+    // A second of standing still, one input per tick.
+    for tick in 101..=120 {
+        assert_eq!(state.input_ticks(tick), 1);
+    }
+    let ctx = MoveContext {
+        ticks: state.input_ticks(121),
+        ..standing()
+    };
+    assert!(matches!(
+        validate_move(&storage, &ctx, (28.5, EYE_ON_GROUND, 8.5)),
+        Err(MovementViolation::TooFast { .. })
+    ));
+}
+
+#[test]
+fn move_through_wall() {
+    let mut storage = storage();
+    let mut chunk = (*storage.get(ChunkPos::new(0, 0)).unwrap()).clone();
+    chunk.set_block(9, -60, 8, &BlockState::new("minecraft:stone"));
+    chunk.set_block(9, -59, 8, &BlockState::new("minecraft:stone"));
+    storage.insert(chunk);
+    let ctx = MoveContext {
+        ticks: 2,
+        ..standing()
+    };
+    // The far side is clear, but the way there is not.
+    assert_eq!(
+        validate_move(&storage, &ctx, (10.5, EYE_ON_GROUND, 8.5)),
+        Err(MovementViolation::Collision { block: (9, -60, 8) })
+    );
+
+    // Stepping up onto a single block goes over it rather than through it.
+    let mut storage = self::storage();
+    let mut chunk = (*storage.get(ChunkPos::new(0, 0)).unwrap()).clone();
+    chunk.set_block(9, -60, 8, &BlockState::new("minecraft:stone"));
+    storage.insert(chunk);
+    let ctx = MoveContext {
+        ticks: 10,
+        ..standing()
+    };
+    assert_eq!(
+        validate_move(&storage, &ctx, (9.5, EYE_ON_GROUND + 1.0, 8.5)),
+        Ok(true)
+    );
+    // And walking off it goes across before down.
+    let ctx = MoveContext {
+        from: (9.5, EYE_ON_GROUND + 1.0, 8.5),
+        ..ctx
+    };
+    assert_eq!(
+        validate_move(&storage, &ctx, (10.5, EYE_ON_GROUND, 8.5)),
+        Ok(true)
+    );
+}
+
+#[test]
+fn correction_matches_authority() {
+    let (_, from_client) = mpsc::channel(8);
+    let (to_client, mut packets) = outbound::channel(1 << 20, Duration::from_secs(15));
+    let (_, disconnect) = oneshot::channel();
+    let mut conn = ConnectionStreamComponent::new(from_client, to_client, disconnect, "Steve");
+    let pos = PositionComponent::new(8.5, EYE_ON_GROUND, 8.5);
+    let rot = RotationComponent {
+        pitch: 0.0,
+        yaw: 90.0,
+        head_yaw: 90.0,
+    };
+    let state = MovementStateComponent {
+        on_ground: true,
+        tick: 300,
+        ..Default::default()
+    };
+    let correct = |conn: &mut ConnectionStreamComponent, authority| {
+        correct_movement(conn, authority, &RuntimeIdComponent(1), &pos, &rot, &state)
+    };
+
+    // Without a rewind history the client only understands being reset.
+    correct(&mut conn, MovementAuthority::Server);
+    assert!(matches!(
+        packets.try_recv_packet(),
+        Some(PacketKind::MovePlayer(p))
+            if matches!(p.mode, MoveMode::Reset)
+                && p.runtime_id == 1
+                && p.position == (8.5, EYE_ON_GROUND, 8.5)
+                && p.yaw == 90.0
+                && p.tick == 300
+    ));
+
+    // Clientbound only, so it is checked by its header: id 0xa1 as a varint.
+    correct(&mut conn, MovementAuthority::ServerWithRewind);
+    assert_eq!(packets.try_recv().unwrap()[..2], [0xa1, 0x01]);
+    assert!(packets.try_recv().is_none());
+}
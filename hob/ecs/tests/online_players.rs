@@ -0,0 +1,55 @@
+use hob_ecs::{
+    player::resources::{OnlinePlayer, OnlinePlayers},
+    Builder, World, WorldExt,
+};
+use hob_protocol::packet::player_list::Skin;
+use uuid::Uuid;
+
+fn player(entity: hob_ecs::Entity, name: &str, xuid: &str, runtime_id: u64) -> OnlinePlayer {
+    OnlinePlayer {
+        entity,
+        xuid: xuid.into(),
+        uuid: Uuid::from_u64_pair(0, runtime_id),
+        name: name.into(),
+        runtime_id,
+        unique_id: runtime_id as i64,
+        skin: Skin::default(),
+        device_id: "".into(),
+        build_platform: 7,
+    }
+}
+
+#[test]
+fn lookups_follow_join_and_leave() {
+    let mut world = World::new();
+    let steve = world.create_entity().build();
+    let alex = world.create_entity().build();
+
+    let mut online = OnlinePlayers::default();
+    online.insert(player(steve, "Steve", "100", 1));
+    online.insert(player(alex, "Alex", "200", 2));
+    assert_eq!(online.len(), 2);
+    assert_eq!(online.by_name("steve").unwrap().entity, steve);
+    assert_eq!(online.by_xuid("200").unwrap().entity, alex);
+    assert_eq!(online.by_runtime_id(1).unwrap().entity, steve);
+    assert_eq!(
+        online.by_uuid(&Uuid::from_u64_pair(0, 2)).unwrap().entity,
+        alex
+    );
+    assert_eq!(
+        online.search("AL").map(|p| p.entity).collect::<Vec<_>>(),
+        [alex]
+    );
+
+    // The same account rejoining on a new entity before the old one is cleaned up.
+    let steve_again = world.create_entity().build();
+    online.insert(player(steve_again, "Steve", "100", 3));
+    online.remove(steve);
+    assert_eq!(online.by_xuid("100").unwrap().entity, steve_again);
+    assert_eq!(online.by_name("Steve").unwrap().runtime_id, 3);
+    assert!(online.by_runtime_id(1).is_none());
+
+    online.remove(alex);
+    assert!(online.by_name("alex").is_none());
+    assert_eq!(online.len(), 1);
+}
@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use hob_ecs::{
+    command::{execute, init_command},
+    console::ConsoleSender,
+    player::{
+        components::connection::ConnectionStreamComponent, netstat::init_netstat,
+        resources::OnlinePlayers, systems::outbound::SlowClientSystem,
+    },
+    Builder, RunNow, World, WorldExt,
+};
+use hob_protocol::packet::{
+    disconnect::DisconnectFailReason, level_chunk::LevelChunkPacket, start_game::Dimension,
+    PacketKind,
+};
+use hob_server::outbound;
+use tokio::sync::{mpsc, oneshot};
+
+fn chunk(size: usize) -> LevelChunkPacket {
+    LevelChunkPacket {
+        chunk_x: 0,
+        chunk_z: 0,
+        dimension: Dimension::OverWorld,
+        sub_chunk_count: 0,
+        cache_enabled: false,
+        payload: vec![0; size],
+    }
+}
+
+#[test]
+fn slow_clients_are_disconnected() {
+    let mut world = World::new();
+    world.register::<ConnectionStreamComponent>();
+    world.insert(OnlinePlayers::default());
+    init_command(&mut world);
+    init_netstat(&mut world);
+    let console = world.create_entity().build();
+    world.insert(ConsoleSender(console));
+    assert_eq!(
+        execute(&world, console, "netstat"),
+        Ok("No players are connected".into())
+    );
+
+    let (to_client, mut packets) = outbound::channel(1000, Duration::from_millis(50));
+    let steve = world
+        .create_entity()
+        .with(ConnectionStreamComponent::new(
+            mpsc::channel(1).1,
+            to_client,
+            oneshot::channel().1,
+            "Steve",
+        ))
+        .build();
+    world
+        .write_storage::<ConnectionStreamComponent>()
+        .get_mut(steve)
+        .unwrap()
+        .send_packet(chunk(1500));
+    let report = execute(&world, console, "netstat").unwrap();
+    assert!(report.contains("Steve: 1 packets"), "{report}");
+
+    SlowClientSystem.run_now(&world);
+    assert!(!world
+        .read_storage::<ConnectionStreamComponent>()
+        .get(steve)
+        .unwrap()
+        .is_closing());
+    std::thread::sleep(Duration::from_millis(80));
+    SlowClientSystem.run_now(&world);
+
+    // The backlog is dropped so the disconnect goes out next.
+    assert!(matches!(
+        packets.try_recv_packet(),
+        Some(PacketKind::Disconnect(p)) if matches!(p.reason, DisconnectFailReason::Timeout)
+    ));
+    assert!(world
+        .read_storage::<ConnectionStreamComponent>()
+        .get(steve)
+        .unwrap()
+        .is_closing());
+}
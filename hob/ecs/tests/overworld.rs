@@ -0,0 +1,31 @@
+use hob_ecs::world::{
+    chunk::{Chunk, ChunkPos},
+    generator::{overworld::OverworldGenerator, WorldGenerator},
+};
+use hob_protocol::packet::start_game::Dimension;
+
+fn blocks(chunk: &Chunk) -> Vec<String> {
+    let mut names = Vec::new();
+    for x in 0..16 {
+        for z in 0..16 {
+            for y in chunk.min_y()..chunk.max_y() {
+                names.push(chunk.get_block(x, y, z).unwrap().name.clone());
+            }
+        }
+    }
+    names
+}
+
+#[test]
+fn same_seed_same_world() {
+    let pos = ChunkPos::new(7, -3);
+    let a = OverworldGenerator::new(12345).generate(pos, Dimension::OverWorld);
+    let b = OverworldGenerator::new(12345).generate(pos, Dimension::OverWorld);
+    let c = OverworldGenerator::new(54321).generate(pos, Dimension::OverWorld);
+    assert_eq!(blocks(&a), blocks(&b));
+    assert_eq!(a.biomes, b.biomes);
+    assert_ne!(blocks(&a), blocks(&c));
+
+    assert_eq!(a.get_block(0, -64, 0).unwrap().name, "minecraft:bedrock");
+    assert!(a.get_block(0, 319, 0).unwrap().is_air());
+}
@@ -0,0 +1,234 @@
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use hob_ecs::{
+    command::{execute, init_command},
+    console::ConsoleSender,
+    permission::{has_permission, init_permission, player_abilities, Permissions},
+    player::{
+        components::{
+            connection::{ConnectionAddressComponent, ConnectionStreamComponent, QuitReason},
+            PositionComponent,
+        },
+        resources::{OnlinePlayer, OnlinePlayers},
+    },
+    world::components::{ActorTypeComponent, DimensionComponent},
+    Builder, Entity, World, WorldExt,
+};
+use hob_protocol::packet::{
+    add_player::{ability, command_permission},
+    player_list::Skin,
+    start_game::{GameMode, PermissionLevel},
+};
+use hob_server::{access::AccessControl, outbound, Server};
+use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
+
+fn temp_file(name: &str, contents: Option<&str>) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("hob-{}-{name}", std::process::id()));
+    match contents {
+        Some(text) => std::fs::write(&path, text).unwrap(),
+        None => {
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+    path
+}
+
+#[test]
+fn levels_and_nodes_from_file() {
+    let path = temp_file(
+        "levels.json",
+        Some(
+            r#"[
+                {"permission": "operator", "xuid": "100"},
+                {"permission": "visitor", "name": "Griefer"},
+                {"permission": "member", "xuid": "300", "nodes": ["hob.command.tp", "hob.kit.*"]}
+            ]"#,
+        ),
+    );
+    let permissions = Permissions::load(&path).unwrap();
+    assert_eq!(permissions.level("100", "Steve"), PermissionLevel::Operator);
+    assert_eq!(permissions.level("", "griefer"), PermissionLevel::Visitor);
+    assert_eq!(permissions.level("400", "Alex"), PermissionLevel::Member);
+
+    assert!(permissions.has("100", "Steve", "anything.at.all"));
+    assert!(permissions.has("300", "Alex", "hob.command.tp"));
+    assert!(permissions.has("300", "Alex", "hob.kit.starter"));
+    assert!(!permissions.has("300", "Alex", "hob.kitchen"));
+    assert!(!permissions.has("400", "Alex", "hob.command.tp"));
+
+    let invalid = temp_file("invalid.json", Some(r#"[{"permission": "admin"}]"#));
+    assert!(Permissions::load(&invalid).is_err());
+}
+
+#[test]
+fn changes_are_saved() {
+    let path = temp_file("saved.json", None);
+    let mut permissions = Permissions::load(&path).unwrap();
+    permissions
+        .set_level("100", "Steve", PermissionLevel::Operator)
+        .unwrap();
+    permissions.grant("200", "Alex", "hob.command.tp").unwrap();
+    assert!(permissions.take_changed());
+    assert!(!permissions.take_changed());
+    assert!(permissions
+        .set_level("200", "Alex", PermissionLevel::Custom)
+        .is_err());
+
+    let reloaded = Permissions::load(&path).unwrap();
+    assert_eq!(reloaded.entries(), permissions.entries());
+    assert_eq!(reloaded.level("100", "Steve"), PermissionLevel::Operator);
+
+    // Entries that match the defaults are dropped.
+    permissions
+        .set_level("100", "Steve", PermissionLevel::Member)
+        .unwrap();
+    permissions.revoke("200", "Alex", "hob.command.tp").unwrap();
+    assert!(Permissions::load(&path).unwrap().entries().is_empty());
+}
+
+#[test]
+fn abilities_follow_level_and_game_mode() {
+    let values = |level, mode| player_abilities(1, level, mode).layers[0].values;
+    let op = player_abilities(1, PermissionLevel::Operator, GameMode::Survival);
+    assert_eq!(op.command_permission, command_permission::GAME_DIRECTORS);
+    assert_ne!(op.layers[0].values & ability::OPERATOR_COMMANDS, 0);
+    assert_eq!(
+        values(PermissionLevel::Visitor, GameMode::Survival) & ability::BUILD,
+        0
+    );
+    assert_ne!(
+        values(PermissionLevel::Member, GameMode::Creative) & ability::MAY_FLY,
+        0
+    );
+    assert_eq!(
+        values(PermissionLevel::Member, GameMode::Adventure) & ability::MINE,
+        0
+    );
+}
+
+fn spawn_player(world: &mut World, name: &str, xuid: &str) -> Entity {
+    let entity = world
+        .create_entity()
+        .with(PositionComponent::new(0.0, 0.0, 0.0))
+        .build();
+    world
+        .write_resource::<OnlinePlayers>()
+        .insert(OnlinePlayer {
+            entity,
+            xuid: xuid.into(),
+            uuid: Uuid::nil(),
+            name: name.into(),
+            runtime_id: entity.id() as u64,
+            unique_id: entity.id() as i64,
+            skin: Skin::default(),
+            device_id: "".into(),
+            build_platform: 7,
+        });
+    entity
+}
+
+#[test]
+fn op_commands_need_permission() {
+    let mut world = World::new();
+    world.register::<PositionComponent>();
+    world.register::<DimensionComponent>();
+    world.register::<ActorTypeComponent>();
+    world.insert(OnlinePlayers::default());
+    init_command(&mut world);
+    init_permission(&mut world, temp_file("ops.json", None));
+
+    let steve = spawn_player(&mut world, "Steve", "100");
+    let alex = spawn_player(&mut world, "Alex", "200");
+    let console = world.create_entity().build();
+    world.insert(ConsoleSender(console));
+
+    assert!(execute(&world, alex, "/op Alex").is_err());
+    // Senders that are neither online players nor the console get nothing.
+    let stranger = world.create_entity().build();
+    assert!(!has_permission(&world, stranger, "hob.command.op"));
+    assert!(execute(&world, stranger, "op Alex").is_err());
+    assert!(has_permission(&world, console, "hob.command.op"));
+    assert_eq!(
+        execute(&world, console, "op Steve"),
+        Ok("Opped Steve".into())
+    );
+    assert_eq!(execute(&world, steve, "/op Alex"), Ok("Opped Alex".into()));
+    assert_eq!(
+        execute(&world, alex, "/deop Steve"),
+        Ok("De-opped Steve".into())
+    );
+    let permissions = world.read_resource::<Permissions>();
+    assert_eq!(permissions.level("100", "Steve"), PermissionLevel::Member);
+    assert_eq!(permissions.level("200", "Alex"), PermissionLevel::Operator);
+    drop(permissions);
+
+    // An operator, but no longer online.
+    world.write_resource::<OnlinePlayers>().remove(alex);
+    assert!(!has_permission(&world, alex, "hob.command.op"));
+}
+
+#[test]
+fn bans_kick_online_players() {
+    let mut world = World::new();
+    world.register::<PositionComponent>();
+    world.register::<DimensionComponent>();
+    world.register::<ActorTypeComponent>();
+    world.register::<ConnectionStreamComponent>();
+    world.register::<ConnectionAddressComponent>();
+    world.insert(OnlinePlayers::default());
+    world.insert(Server {
+        player_registry: mpsc::channel(1).1,
+        access: Arc::new(AccessControl::default()),
+        slots: Arc::default(),
+        motd: Arc::default(),
+        stopping: Arc::default(),
+        query: Arc::default(),
+    });
+    init_command(&mut world);
+    init_permission(&mut world, temp_file("ban-ops.json", None));
+
+    let steve = spawn_player(&mut world, "Steve", "100");
+    let (to_client, _packets) = outbound::channel(1 << 20, Duration::from_secs(15));
+    let conn = ConnectionStreamComponent::new(
+        mpsc::channel(1).1,
+        to_client,
+        oneshot::channel().1,
+        "Steve",
+    );
+    world.write_storage().insert(steve, conn).unwrap();
+    world
+        .write_storage()
+        .insert(
+            steve,
+            ConnectionAddressComponent("203.0.113.7:19132".parse().unwrap()),
+        )
+        .unwrap();
+    let console = world.create_entity().build();
+    world.insert(ConsoleSender(console));
+
+    assert_eq!(
+        execute(&world, console, "ban-ip 198.51.100.0/24"),
+        Ok("Banned 198.51.100.0/24".into())
+    );
+    // Durations that overflow an expiry are refused, not wrapped.
+    assert!(execute(&world, console, "tempban Steve 99999999999999w").is_err());
+    assert!(execute(&world, console, "tempban Steve 18446744073709551000s").is_err());
+    assert_eq!(
+        execute(&world, console, "tempban Steve 1h being rude"),
+        Ok("Banned Steve; kicked Steve".into())
+    );
+    let mut conns = world.write_storage::<ConnectionStreamComponent>();
+    let quit = conns.get_mut(steve).unwrap().poll_quit();
+    assert!(matches!(quit, Some(QuitReason::Kicked(m)) if m.contains("being rude")));
+    drop(conns);
+
+    let access = world.read_resource::<Server>().access.clone();
+    assert!(access
+        .check("100", "Steve", "192.0.2.1".parse().unwrap())
+        .is_some());
+    assert!(execute(&world, console, "pardon Steve").is_ok());
+    assert!(execute(&world, console, "unban-ip 198.51.100.0/24").is_ok());
+    assert!(access.bans().is_empty());
+    assert!(execute(&world, console, "unban Steve").is_err());
+}
@@ -0,0 +1,138 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use hob_ecs::{
+    command::{execute, init_command},
+    console::ConsoleSender,
+    events::{player_quit::PlayerQuitEvent, server_shutdown::ServerShutdownEvent},
+    permission::init_permission,
+    player::{
+        components::connection::ConnectionStreamComponent,
+        resources::{OnlinePlayer, OnlinePlayers},
+    },
+    plugin::{Plugin, PluginSys},
+    shutdown::{init_shutdown, shutdown, ShutdownHandle, ShutdownRequest},
+    world::resources::LevelResource,
+    Builder, World, WorldExt,
+};
+use hob_protocol::packet::{disconnect::DisconnectFailReason, player_list::Skin, PacketKind};
+use hob_server::{access::AccessControl, config::LevelConfig, outbound, Server};
+use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
+
+struct RecordShutdown(Arc<Mutex<Option<Option<String>>>>);
+impl<'a> PluginSys<'a, ServerShutdownEvent> for RecordShutdown {
+    type SystemData = ();
+    fn run(&mut self, event: &'a ServerShutdownEvent, _data: Self::SystemData) -> bool {
+        *self.0.lock().unwrap() = Some(event.message.clone());
+        false
+    }
+}
+
+#[test]
+fn stop_command_requests_shutdown() {
+    let mut world = World::new();
+    world.insert(OnlinePlayers::default());
+    init_command(&mut world);
+    init_shutdown(&mut world);
+    let console = world.create_entity().build();
+    world.insert(ConsoleSender(console));
+
+    let handle = ShutdownHandle::clone(&world.read_resource());
+    assert_eq!(handle.requested(), None);
+    assert_eq!(
+        execute(&world, console, "stop Back in five minutes"),
+        Ok("Stopping the server".into())
+    );
+    // Later requests don't replace the first one.
+    handle.request(None);
+    assert_eq!(
+        handle.requested(),
+        Some(ShutdownRequest {
+            message: Some("Back in five minutes".into())
+        })
+    );
+}
+
+#[test]
+fn shutdown_disconnects_everyone() {
+    let mut world = World::new();
+    world.register::<ConnectionStreamComponent>();
+    world.insert(OnlinePlayers::default());
+    world.insert(Plugin::<PlayerQuitEvent>::new());
+    world.insert(Plugin::<ServerShutdownEvent>::new());
+    let stopping = Arc::new(AtomicBool::new(false));
+    world.insert(Server {
+        player_registry: mpsc::channel(1).1,
+        access: Arc::new(AccessControl::default()),
+        slots: Arc::default(),
+        motd: Arc::default(),
+        stopping: Arc::clone(&stopping),
+        query: Arc::default(),
+    });
+    init_command(&mut world);
+    let path = std::env::temp_dir().join(format!("hob-{}-shutdown.json", std::process::id()));
+    init_permission(&mut world, &path);
+    let level_path =
+        std::env::temp_dir().join(format!("hob-{}-shutdown-level.dat", std::process::id()));
+    let _ = std::fs::remove_file(&level_path);
+    world.insert(LevelResource {
+        seed: 1234,
+        path: Some(level_path.clone()),
+        ..Default::default()
+    });
+
+    let recorded = Arc::new(Mutex::new(None));
+    world
+        .write_resource::<Plugin<ServerShutdownEvent>>()
+        .add_plugin(RecordShutdown(Arc::clone(&recorded)));
+
+    let (to_client, mut packets) = outbound::channel(1 << 20, Duration::from_secs(15));
+    let steve = world
+        .create_entity()
+        .with(ConnectionStreamComponent::new(
+            mpsc::channel(1).1,
+            to_client,
+            oneshot::channel().1,
+            "Steve",
+        ))
+        .build();
+    world
+        .write_resource::<OnlinePlayers>()
+        .insert(OnlinePlayer {
+            entity: steve,
+            xuid: "100".into(),
+            uuid: Uuid::nil(),
+            name: "Steve".into(),
+            runtime_id: 1,
+            unique_id: 1,
+            skin: Skin::default(),
+            device_id: "".into(),
+            build_platform: 7,
+        });
+
+    shutdown(&world, Some("Maintenance"));
+    world.maintain();
+
+    assert!(stopping.load(Ordering::Relaxed));
+    assert!(matches!(
+        packets.try_recv_packet(),
+        Some(PacketKind::Disconnect(p))
+            if matches!(p.reason, DisconnectFailReason::Shutdown)
+                && p.message.as_deref() == Some("Maintenance")
+    ));
+    assert!(!world.is_alive(steve));
+    assert!(world.read_resource::<OnlinePlayers>().get(steve).is_none());
+    assert_eq!(
+        *recorded.lock().unwrap(),
+        Some(Some("Maintenance".to_owned()))
+    );
+    let saved = LevelResource::load(&LevelConfig::default(), &level_path).unwrap();
+    assert_eq!(saved.seed, 1234);
+    let _ = std::fs::remove_file(&level_path);
+}
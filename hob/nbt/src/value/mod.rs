@@ -3,7 +3,7 @@ pub mod ser;
 
 use std::collections::HashMap;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     Byte(i8),
     Short(i16),
@@ -46,10 +46,15 @@ impl Decoder {
         }
         let mut packets = Vec::new();
         while !bytes.is_empty() {
-            let size = bytes.get_varint();
-            let expected = bytes.len() - size as usize;
-            packets.push(PacketKind::decode(bytes)?);
-            ensure!(bytes.len() == expected, "Invalid packet size");
+            let size = bytes.get_varint() as usize;
+            ensure!(size <= bytes.len(), "Invalid packet size");
+            let mut packet_buf = bytes.split_to(size);
+            let packet = PacketKind::decode(&mut packet_buf)?;
+            // Unknown packets are skipped as a whole instead of failing the batch.
+            if !matches!(packet, PacketKind::Unknown(_)) {
+                ensure!(packet_buf.is_empty(), "Invalid packet size");
+            }
+            packets.push(packet);
         }
         Ok(packets)
     }
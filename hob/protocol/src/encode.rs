@@ -28,13 +28,15 @@ impl Encoder {
         self.ss_key.copy_from_slice(shared_secret);
     }
     pub fn encode(&mut self, packet: PacketKind) -> Vec<u8> {
-        let mut content = BytesMut::new();
-        {
-            let mut packet_buf = BytesMut::new();
-            packet.encode(&mut packet_buf).unwrap();
-            content.put_varint(packet_buf.len() as u64);
-            content.put(packet_buf);
-        }
+        let mut packet_buf = BytesMut::new();
+        packet.encode(&mut packet_buf).unwrap();
+        self.encode_raw(&packet_buf)
+    }
+    /// Batches a packet already encoded by [`PacketKind::encode`].
+    pub fn encode_raw(&mut self, packet: &[u8]) -> Vec<u8> {
+        let mut content = BytesMut::with_capacity(packet.len() + 5);
+        content.put_varint(packet.len() as u64);
+        content.put_slice(packet);
 
         if self.compression_ready {
             let mut compressed = Vec::new();
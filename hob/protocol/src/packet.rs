@@ -1,26 +1,58 @@
+pub mod add_actor;
+pub mod add_player;
+pub mod available_commands;
+pub mod chunk_radius_update;
 pub mod client_cache_status;
+pub mod command_output;
+pub mod command_request;
+pub mod correct_player_move_prediction;
 pub mod disconnect;
 pub mod handshake;
+pub mod level_chunk;
 pub mod login;
+pub mod move_player;
+pub mod network_chunk_publisher_update;
 pub mod network_settings;
 pub mod play_status;
+pub mod player_auth_input;
+pub mod player_list;
+pub mod remove_actor;
+pub mod request_chunk_radius;
 pub mod request_network_setting;
 pub mod resource_pack_info;
 pub mod resource_pack_response;
 pub mod resource_pack_stack;
+pub mod set_actor_data;
 pub mod start_game;
+pub mod update_abilities;
 
+use add_actor::*;
+use add_player::*;
+use available_commands::*;
+use chunk_radius_update::*;
 use client_cache_status::*;
+use command_output::*;
+use command_request::*;
+use correct_player_move_prediction::*;
 use disconnect::*;
 use handshake::*;
+use level_chunk::*;
 use login::*;
+use move_player::*;
+use network_chunk_publisher_update::*;
 use network_settings::*;
 use play_status::*;
+use player_auth_input::*;
+use player_list::*;
+use remove_actor::*;
+use request_chunk_radius::*;
 use request_network_setting::*;
 use resource_pack_info::*;
 use resource_pack_response::*;
 use resource_pack_stack::*;
+use set_actor_data::*;
 use start_game::*;
+use update_abilities::*;
 
 use crate::packet_kind;
 
@@ -41,8 +73,24 @@ packet_kind! {
     ResourcePacksStack = 7
     ResourcePackClientResponse = 8
     StartGame = 0xB
+    AddPlayer = 0xC
+    AddActor = 0xD
+    RemoveActor = 0xE
+    MovePlayer = 0x13
+    SetActorData = 0x27
+    LevelChunk = 0x3A
+    PlayerList = 0x3F
+    RequestChunkRadius = 0x45
+    ChunkRadiusUpdate = 0x46
+    AvailableCommands = 0x4C
+    CommandRequest = 0x4D
+    CommandOutput = 0x4F
+    NetworkChunkPublisherUpdate = 0x79
     ClientCacheStatus = 0x81
     NetworkSettings = 0x8F
+    PlayerAuthInput = 0x90
+    UpdateAbilities = 0xBB
+    CorrectPlayerMovePrediction = 0xA1
     RequestNetworkSetting = 0xC1
 }
 impl std::fmt::Display for PacketKind {
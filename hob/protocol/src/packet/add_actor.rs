@@ -0,0 +1,74 @@
+use proto_bytes::{BufMut, ConditionalBufMut};
+
+use super::{set_actor_data::ActorMetadata, Packet};
+
+#[derive(Debug, Clone)]
+pub struct AddActorPacket {
+    pub unique_id: i64,
+    pub runtime_id: u64,
+    /// Actor identifier such as `minecraft:pig`.
+    pub actor_type: String,
+    pub position: (f32, f32, f32),
+    pub velocity: (f32, f32, f32),
+    pub pitch: f32,
+    pub yaw: f32,
+    pub head_yaw: f32,
+    pub body_yaw: f32,
+    pub attributes: Vec<ActorAttribute>,
+    pub metadata: ActorMetadata,
+}
+
+impl Packet for AddActorPacket {
+    fn decode(_bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        anyhow::bail!("AddActorPacket is clientbound only")
+    }
+
+    // https://github.com/Sandertv/gophertunnel/blob/master/minecraft/protocol/packet/add_actor.go
+
+    fn encode(&self, bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
+        bytes.put_zigzag64(self.unique_id);
+        bytes.put_varint(self.runtime_id);
+        bytes.put_string_varint(&self.actor_type);
+        {
+            let (x, y, z) = self.position;
+            bytes.put_f32_le(x);
+            bytes.put_f32_le(y);
+            bytes.put_f32_le(z);
+        }
+        {
+            let (x, y, z) = self.velocity;
+            bytes.put_f32_le(x);
+            bytes.put_f32_le(y);
+            bytes.put_f32_le(z);
+        }
+        bytes.put_f32_le(self.pitch);
+        bytes.put_f32_le(self.yaw);
+        bytes.put_f32_le(self.head_yaw);
+        bytes.put_f32_le(self.body_yaw);
+        bytes.put_varint(self.attributes.len() as u64);
+        for attribute in self.attributes.iter() {
+            bytes.put_string_varint(&attribute.name);
+            bytes.put_f32_le(attribute.min);
+            bytes.put_f32_le(attribute.value);
+            bytes.put_f32_le(attribute.max);
+        }
+        self.metadata.encode(bytes)?;
+        // int and float actor properties
+        bytes.put_varint(0);
+        bytes.put_varint(0);
+        // actor links
+        bytes.put_varint(0);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ActorAttribute {
+    pub name: String,
+    pub min: f32,
+    pub value: f32,
+    pub max: f32,
+}
@@ -0,0 +1,161 @@
+use proto_bytes::{BufMut, ConditionalBufMut};
+use uuid::Uuid;
+
+use super::{
+    player_list::put_uuid,
+    set_actor_data::ActorMetadata,
+    start_game::{GameMode, PermissionLevel},
+    Packet,
+};
+
+#[derive(Debug, Clone)]
+pub struct AddPlayerPacket {
+    pub uuid: Uuid,
+    pub username: String,
+    pub runtime_id: u64,
+    pub platform_chat_id: String,
+    pub position: (f32, f32, f32),
+    pub velocity: (f32, f32, f32),
+    pub pitch: f32,
+    pub yaw: f32,
+    pub head_yaw: f32,
+    pub game_mode: GameMode,
+    pub metadata: ActorMetadata,
+    pub abilities: AbilityData,
+    pub device_id: String,
+    pub build_platform: i32,
+}
+
+impl Packet for AddPlayerPacket {
+    fn decode(_bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        anyhow::bail!("AddPlayerPacket is clientbound only")
+    }
+
+    // https://github.com/Sandertv/gophertunnel/blob/master/minecraft/protocol/packet/add_player.go
+
+    fn encode(&self, bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
+        put_uuid(bytes, &self.uuid);
+        bytes.put_string_varint(&self.username);
+        bytes.put_varint(self.runtime_id);
+        bytes.put_string_varint(&self.platform_chat_id);
+        {
+            let (x, y, z) = self.position;
+            bytes.put_f32_le(x);
+            bytes.put_f32_le(y);
+            bytes.put_f32_le(z);
+        }
+        {
+            let (x, y, z) = self.velocity;
+            bytes.put_f32_le(x);
+            bytes.put_f32_le(y);
+            bytes.put_f32_le(z);
+        }
+        bytes.put_f32_le(self.pitch);
+        bytes.put_f32_le(self.yaw);
+        bytes.put_f32_le(self.head_yaw);
+        // held item: air
+        bytes.put_zigzag32(0);
+        bytes.put_zigzag32(self.game_mode as i32);
+        self.metadata.encode(bytes)?;
+        // int and float actor properties
+        bytes.put_varint(0);
+        bytes.put_varint(0);
+        self.abilities.encode(bytes);
+        // actor links
+        bytes.put_varint(0);
+        bytes.put_string_varint(&self.device_id);
+        bytes.put_i32_le(self.build_platform);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AbilityData {
+    pub unique_id: i64,
+    pub player_permission: PermissionLevel,
+    pub command_permission: u8,
+    pub layers: Vec<AbilityLayer>,
+}
+impl AbilityData {
+    /// Base layer with every ability present and unset, as sent for other players.
+    pub fn new(unique_id: i64) -> Self {
+        AbilityData {
+            unique_id,
+            player_permission: PermissionLevel::Member,
+            command_permission: 0,
+            layers: vec![AbilityLayer {
+                layer_type: AbilityLayer::BASE,
+                abilities: AbilityLayer::ALL,
+                values: 0,
+                fly_speed: 0.0,
+                walk_speed: 0.0,
+            }],
+        }
+    }
+    pub(crate) fn encode(&self, bytes: &mut proto_bytes::BytesMut) {
+        bytes.put_i64_le(self.unique_id);
+        bytes.put_u8(self.player_permission as u8);
+        bytes.put_u8(self.command_permission);
+        bytes.put_u8(self.layers.len() as u8);
+        for layer in self.layers.iter() {
+            bytes.put_u16_le(layer.layer_type);
+            bytes.put_u32_le(layer.abilities);
+            bytes.put_u32_le(layer.values);
+            bytes.put_f32_le(layer.fly_speed);
+            bytes.put_f32_le(layer.walk_speed);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AbilityLayer {
+    pub layer_type: u16,
+    /// Abilities this layer defines.
+    pub abilities: u32,
+    /// Which of `abilities` are enabled.
+    pub values: u32,
+    pub fly_speed: f32,
+    pub walk_speed: f32,
+}
+impl AbilityLayer {
+    pub const BASE: u16 = 1;
+    pub const ALL: u32 = (1 << 19) - 1;
+    pub const DEFAULT_FLY_SPEED: f32 = 0.05;
+    pub const DEFAULT_WALK_SPEED: f32 = 0.1;
+}
+
+/// Bits of [`AbilityLayer::abilities`] and [`AbilityLayer::values`].
+pub mod ability {
+    pub const BUILD: u32 = 1 << 0;
+    pub const MINE: u32 = 1 << 1;
+    pub const DOORS_AND_SWITCHES: u32 = 1 << 2;
+    pub const OPEN_CONTAINERS: u32 = 1 << 3;
+    pub const ATTACK_PLAYERS: u32 = 1 << 4;
+    pub const ATTACK_MOBS: u32 = 1 << 5;
+    pub const OPERATOR_COMMANDS: u32 = 1 << 6;
+    pub const TELEPORT: u32 = 1 << 7;
+    pub const INVULNERABLE: u32 = 1 << 8;
+    pub const FLYING: u32 = 1 << 9;
+    pub const MAY_FLY: u32 = 1 << 10;
+    pub const INSTANT_BUILD: u32 = 1 << 11;
+    pub const LIGHTNING: u32 = 1 << 12;
+    pub const FLY_SPEED: u32 = 1 << 13;
+    pub const WALK_SPEED: u32 = 1 << 14;
+    pub const MUTED: u32 = 1 << 15;
+    pub const WORLD_BUILDER: u32 = 1 << 16;
+    pub const NO_CLIP: u32 = 1 << 17;
+    pub const PRIVILEGED_BUILDER: u32 = 1 << 18;
+}
+
+/// Values of [`AbilityData::command_permission`].
+pub mod command_permission {
+    pub const NORMAL: u8 = 0;
+    pub const GAME_DIRECTORS: u8 = 1;
+    pub const ADMIN: u8 = 2;
+    pub const HOST: u8 = 3;
+    pub const OWNER: u8 = 4;
+    pub const INTERNAL: u8 = 5;
+}
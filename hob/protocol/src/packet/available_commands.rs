@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+
+use proto_bytes::{BufMut, ConditionalBufMut};
+
+use super::Packet;
+
+/// Commands the client may autocomplete. The enum and alias tables are built on encode.
+#[derive(Debug, Clone, Default)]
+pub struct AvailableCommandsPacket {
+    pub commands: Vec<CommandData>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CommandData {
+    pub name: String,
+    pub description: String,
+    pub flags: u16,
+    pub permission_level: u8,
+    pub aliases: Vec<String>,
+    pub overloads: Vec<CommandOverload>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CommandOverload {
+    pub chaining: bool,
+    pub parameters: Vec<CommandParameter>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CommandParameter {
+    pub name: String,
+    pub param_type: CommandParamType,
+    pub optional: bool,
+    pub options: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandParamType {
+    Int,
+    Float,
+    Value,
+    WildcardInt,
+    Operator,
+    CompareOperator,
+    Target,
+    WildcardTarget,
+    FilePath,
+    IntegerRange,
+    EquipmentSlots,
+    String,
+    BlockPosition,
+    Position,
+    Message,
+    RawText,
+    Json,
+    BlockStates,
+    Command,
+    Enum(CommandEnum),
+}
+impl CommandParamType {
+    const VALID: u32 = 0x100000;
+    const ENUM: u32 = 0x200000;
+
+    fn symbol(&self) -> u32 {
+        match self {
+            CommandParamType::Int => 1,
+            CommandParamType::Float => 3,
+            CommandParamType::Value => 4,
+            CommandParamType::WildcardInt => 5,
+            CommandParamType::Operator => 6,
+            CommandParamType::CompareOperator => 7,
+            CommandParamType::Target => 8,
+            CommandParamType::WildcardTarget => 10,
+            CommandParamType::FilePath => 17,
+            CommandParamType::IntegerRange => 23,
+            CommandParamType::EquipmentSlots => 38,
+            CommandParamType::String => 39,
+            CommandParamType::BlockPosition => 47,
+            CommandParamType::Position => 48,
+            CommandParamType::Message => 51,
+            CommandParamType::RawText => 53,
+            CommandParamType::Json => 57,
+            CommandParamType::BlockStates => 67,
+            CommandParamType::Command => 70,
+            CommandParamType::Enum(_) => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandEnum {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+impl Packet for AvailableCommandsPacket {
+    fn decode(_bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        anyhow::bail!("AvailableCommandsPacket is clientbound only")
+    }
+
+    // https://github.com/Sandertv/gophertunnel/blob/master/minecraft/protocol/packet/available_commands.go
+
+    fn encode(&self, bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
+        let mut tables = EnumTables::default();
+        let commands: Vec<(Option<u32>, Vec<Vec<u32>>)> = self
+            .commands
+            .iter()
+            .map(|command| {
+                let aliases = (!command.aliases.is_empty()).then(|| {
+                    let mut values = vec![command.name.clone()];
+                    values.extend(command.aliases.iter().cloned());
+                    tables.add_enum(&CommandEnum {
+                        name: format!("{}Aliases", command.name),
+                        values,
+                    })
+                });
+                let overloads = command
+                    .overloads
+                    .iter()
+                    .map(|overload| {
+                        overload
+                            .parameters
+                            .iter()
+                            .map(|param| match &param.param_type {
+                                CommandParamType::Enum(e) => {
+                                    CommandParamType::VALID
+                                        | CommandParamType::ENUM
+                                        | tables.add_enum(e)
+                                }
+                                other => CommandParamType::VALID | other.symbol(),
+                            })
+                            .collect()
+                    })
+                    .collect();
+                (aliases, overloads)
+            })
+            .collect();
+
+        put_strings(bytes, &tables.values);
+        // chained subcommand values
+        bytes.put_varint(0);
+        // suffixes
+        bytes.put_varint(0);
+        bytes.put_varint(tables.enums.len() as u64);
+        for (name, indices) in tables.enums.iter() {
+            bytes.put_string_varint(name);
+            bytes.put_varint(indices.len() as u64);
+            for index in indices {
+                match tables.values.len() {
+                    0..=0xff => bytes.put_u8(*index as u8),
+                    0x100..=0xffff => bytes.put_u16_le(*index as u16),
+                    _ => bytes.put_u32_le(*index),
+                }
+            }
+        }
+        // chained subcommands
+        bytes.put_varint(0);
+        bytes.put_varint(self.commands.len() as u64);
+        for (command, (aliases, overloads)) in self.commands.iter().zip(commands) {
+            bytes.put_string_varint(&command.name);
+            bytes.put_string_varint(&command.description);
+            bytes.put_u16_le(command.flags);
+            bytes.put_u8(command.permission_level);
+            bytes.put_i32_le(aliases.map_or(-1, |v| v as i32));
+            // chained subcommand offsets
+            bytes.put_varint(0);
+            bytes.put_varint(command.overloads.len() as u64);
+            for (overload, types) in command.overloads.iter().zip(overloads) {
+                bytes.put_bool(overload.chaining);
+                bytes.put_varint(overload.parameters.len() as u64);
+                for (param, param_type) in overload.parameters.iter().zip(types) {
+                    bytes.put_string_varint(&param.name);
+                    bytes.put_u32_le(param_type);
+                    bytes.put_bool(param.optional);
+                    bytes.put_u8(param.options);
+                }
+            }
+        }
+        // dynamic enums
+        bytes.put_varint(0);
+        // enum constraints
+        bytes.put_varint(0);
+        Ok(())
+    }
+}
+
+/// Deduplicated enum values and enums, in first-seen order.
+#[derive(Default)]
+struct EnumTables {
+    values: Vec<String>,
+    value_index: HashMap<String, u32>,
+    enums: Vec<(String, Vec<u32>)>,
+    enum_index: HashMap<String, u32>,
+}
+impl EnumTables {
+    fn add_enum(&mut self, e: &CommandEnum) -> u32 {
+        if let Some(index) = self.enum_index.get(&e.name) {
+            return *index;
+        }
+        let indices = e
+            .values
+            .iter()
+            .map(|value| {
+                *self.value_index.entry(value.clone()).or_insert_with(|| {
+                    self.values.push(value.clone());
+                    self.values.len() as u32 - 1
+                })
+            })
+            .collect();
+        let index = self.enums.len() as u32;
+        self.enums.push((e.name.clone(), indices));
+        self.enum_index.insert(e.name.clone(), index);
+        index
+    }
+}
+
+fn put_strings(bytes: &mut proto_bytes::BytesMut, strings: &[String]) {
+    bytes.put_varint(strings.len() as u64);
+    for s in strings {
+        bytes.put_string_varint(s);
+    }
+}
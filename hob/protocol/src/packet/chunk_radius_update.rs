@@ -0,0 +1,23 @@
+use proto_bytes::ConditionalBufMut;
+
+use super::Packet;
+
+#[derive(Debug)]
+pub struct ChunkRadiusUpdatePacket {
+    pub chunk_radius: i32,
+}
+
+impl Packet for ChunkRadiusUpdatePacket {
+    fn decode(_bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        anyhow::bail!("ChunkRadiusUpdatePacket is clientbound only")
+    }
+
+    #[inline]
+    fn encode(&self, bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
+        bytes.put_zigzag32(self.chunk_radius);
+        Ok(())
+    }
+}
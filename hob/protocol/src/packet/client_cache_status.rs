@@ -17,6 +17,6 @@ impl Packet for ClientCacheStatusPacket {
     }
 
     fn encode(&self, _bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
-        todo!()
+        anyhow::bail!("ClientCacheStatusPacket is serverbound only")
     }
 }
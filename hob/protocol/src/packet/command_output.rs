@@ -0,0 +1,60 @@
+use proto_bytes::{BufMut, ConditionalBufMut};
+
+use super::{command_request::CommandOrigin, Packet};
+
+#[derive(Debug, Clone)]
+pub struct CommandOutputPacket {
+    pub origin: CommandOrigin,
+    pub output_type: CommandOutputType,
+    pub success_count: u32,
+    pub messages: Vec<CommandOutputMessage>,
+    /// Only sent with [`CommandOutputType::DataSet`].
+    pub data_set: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandOutputType {
+    None = 0,
+    LastOutput = 1,
+    Silent = 2,
+    AllOutput = 3,
+    DataSet = 4,
+}
+
+#[derive(Debug, Clone)]
+pub struct CommandOutputMessage {
+    pub success: bool,
+    /// Plain text or a translation key filled from `parameters`.
+    pub message: String,
+    pub parameters: Vec<String>,
+}
+
+impl Packet for CommandOutputPacket {
+    fn decode(_bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        anyhow::bail!("CommandOutputPacket is clientbound only")
+    }
+
+    // https://github.com/Sandertv/gophertunnel/blob/master/minecraft/protocol/packet/command_output.go
+
+    fn encode(&self, bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
+        self.origin.encode(bytes);
+        bytes.put_u8(self.output_type as u8);
+        bytes.put_varint(self.success_count as u64);
+        bytes.put_varint(self.messages.len() as u64);
+        for message in self.messages.iter() {
+            bytes.put_bool(message.success);
+            bytes.put_string_varint(&message.message);
+            bytes.put_varint(message.parameters.len() as u64);
+            for parameter in message.parameters.iter() {
+                bytes.put_string_varint(parameter);
+            }
+        }
+        if self.output_type == CommandOutputType::DataSet {
+            bytes.put_string_varint(&self.data_set);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use proto_bytes::{Buf, ConditionalBuf, ConditionalBufMut};
+use uuid::Uuid;
+
+use super::{player_list::put_uuid, Packet};
+
+#[derive(Debug)]
+pub struct CommandRequestPacket {
+    /// Raw input including the leading `/`.
+    pub command_line: String,
+    pub origin: CommandOrigin,
+    pub internal: bool,
+    pub version: i32,
+}
+
+impl Packet for CommandRequestPacket {
+    fn decode(bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        let command_line = bytes.get_string_varint();
+        let origin = CommandOrigin::decode(bytes);
+        let internal = bytes.get_bool();
+        let version = bytes.get_zigzag32();
+        Ok(CommandRequestPacket {
+            command_line,
+            origin,
+            internal,
+            version,
+        })
+    }
+
+    fn encode(&self, _bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
+        anyhow::bail!("CommandRequestPacket is serverbound only")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandOriginType {
+    Player = 0,
+    Block = 1,
+    MinecartBlock = 2,
+    DevConsole = 3,
+    Test = 4,
+    AutomationPlayer = 5,
+    ClientAutomation = 6,
+    DedicatedServer = 7,
+    Entity = 8,
+    Virtual = 9,
+    GameArgument = 10,
+    EntityServer = 11,
+    Precompiled = 12,
+    GameDirectorEntityServer = 13,
+    Script = 14,
+    Executor = 15,
+}
+impl From<u64> for CommandOriginType {
+    fn from(value: u64) -> Self {
+        use CommandOriginType::*;
+        match value {
+            1 => Block,
+            2 => MinecartBlock,
+            3 => DevConsole,
+            4 => Test,
+            5 => AutomationPlayer,
+            6 => ClientAutomation,
+            7 => DedicatedServer,
+            8 => Entity,
+            9 => Virtual,
+            10 => GameArgument,
+            11 => EntityServer,
+            12 => Precompiled,
+            13 => GameDirectorEntityServer,
+            14 => Script,
+            15 => Executor,
+            _ => Player,
+        }
+    }
+}
+
+/// Who issued a command; echoed back in `CommandOutput`.
+#[derive(Debug, Clone)]
+pub struct CommandOrigin {
+    pub origin_type: CommandOriginType,
+    pub uuid: Uuid,
+    pub request_id: String,
+    pub player_unique_id: i64,
+}
+impl CommandOrigin {
+    fn has_unique_id(&self) -> bool {
+        matches!(
+            self.origin_type,
+            CommandOriginType::DevConsole | CommandOriginType::Test
+        )
+    }
+    pub(crate) fn decode(bytes: &mut proto_bytes::BytesMut) -> Self {
+        let origin_type = CommandOriginType::from(bytes.get_varint());
+        let most_sig = bytes.get_u64_le();
+        let least_sig = bytes.get_u64_le();
+        let mut origin = CommandOrigin {
+            origin_type,
+            uuid: Uuid::from_u64_pair(most_sig, least_sig),
+            request_id: bytes.get_string_varint(),
+            player_unique_id: 0,
+        };
+        if origin.has_unique_id() {
+            origin.player_unique_id = bytes.get_zigzag64();
+        }
+        origin
+    }
+    pub(crate) fn encode(&self, bytes: &mut proto_bytes::BytesMut) {
+        bytes.put_varint(self.origin_type as u64);
+        put_uuid(bytes, &self.uuid);
+        bytes.put_string_varint(&self.request_id);
+        if self.has_unique_id() {
+            bytes.put_zigzag64(self.player_unique_id);
+        }
+    }
+}
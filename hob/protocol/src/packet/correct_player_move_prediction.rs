@@ -0,0 +1,38 @@
+use proto_bytes::{BufMut, ConditionalBufMut};
+
+use super::Packet;
+
+#[derive(Debug)]
+pub struct CorrectPlayerMovePredictionPacket {
+    pub position: (f32, f32, f32),
+    pub delta: (f32, f32, f32),
+    pub on_ground: bool,
+    pub tick: u64,
+}
+
+impl Packet for CorrectPlayerMovePredictionPacket {
+    fn decode(_bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        anyhow::bail!("CorrectPlayerMovePredictionPacket is clientbound only")
+    }
+
+    fn encode(&self, bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
+        {
+            let (x, y, z) = self.position;
+            bytes.put_f32_le(x);
+            bytes.put_f32_le(y);
+            bytes.put_f32_le(z);
+        }
+        {
+            let (x, y, z) = self.delta;
+            bytes.put_f32_le(x);
+            bytes.put_f32_le(y);
+            bytes.put_f32_le(z);
+        }
+        bytes.put_bool(self.on_ground);
+        bytes.put_varint(self.tick);
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::Context;
-use proto_bytes::ConditionalBufMut;
+use from_num::from_num;
+use proto_bytes::{ConditionalBuf, ConditionalBufMut};
 
 use super::Packet;
 
@@ -9,6 +10,16 @@ pub struct DisconnectPacket {
     pub hide_message: bool,
     pub message: Option<String>,
 }
+impl DisconnectPacket {
+    /// Shows `message` on the disconnect screen, tagged with `reason` for telemetry.
+    pub fn new(reason: DisconnectFailReason, message: impl Into<String>) -> Self {
+        Self {
+            reason,
+            hide_message: false,
+            message: Some(message.into()),
+        }
+    }
+}
 impl From<DisconnectFailReason> for DisconnectPacket {
     fn from(reason: DisconnectFailReason) -> Self {
         Self {
@@ -38,11 +49,18 @@ impl<'a> From<&'a str> for DisconnectPacket {
 }
 
 impl Packet for DisconnectPacket {
-    fn decode(_bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
+    fn decode(bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
     where
         Self: Sized,
     {
-        todo!()
+        let reason = DisconnectFailReason::from_i32(bytes.get_zigzag32())?;
+        let hide_message = bytes.get_bool();
+        let message = (!hide_message).then(|| bytes.get_string_varint());
+        Ok(Self {
+            reason,
+            hide_message,
+            message,
+        })
     }
 
     #[inline]
@@ -61,6 +79,7 @@ impl Packet for DisconnectPacket {
 }
 
 #[derive(Debug, Clone)]
+#[from_num(i32)]
 pub enum DisconnectFailReason {
     Unknown,
     CantConnectInternet,
@@ -46,7 +46,7 @@ pub struct ServerToClientHandshakePacket {
 
 impl Packet for ServerToClientHandshakePacket {
     fn decode(_bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self> {
-        todo!()
+        anyhow::bail!("ServerToClientHandshakePacket is clientbound only")
     }
 
     #[inline]
@@ -66,6 +66,6 @@ impl Packet for ClientToServerHandshakePacket {
     }
 
     fn encode(&self, _bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
-        todo!()
+        anyhow::bail!("ClientToServerHandshakePacket is serverbound only")
     }
 }
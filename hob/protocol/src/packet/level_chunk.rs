@@ -0,0 +1,34 @@
+use proto_bytes::{BufMut, ConditionalBufMut};
+
+use super::{start_game::Dimension, Packet};
+
+#[derive(Debug)]
+pub struct LevelChunkPacket {
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+    pub dimension: Dimension,
+    pub sub_chunk_count: u32,
+    pub cache_enabled: bool,
+    /// Serialized sub chunks, biomes and border blocks.
+    pub payload: Vec<u8>,
+}
+
+impl Packet for LevelChunkPacket {
+    fn decode(_bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        anyhow::bail!("LevelChunkPacket is clientbound only")
+    }
+
+    fn encode(&self, bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
+        bytes.put_zigzag32(self.chunk_x);
+        bytes.put_zigzag32(self.chunk_z);
+        bytes.put_zigzag32(self.dimension as i32);
+        bytes.put_varint(self.sub_chunk_count as u64);
+        bytes.put_bool(self.cache_enabled);
+        bytes.put_varint(self.payload.len() as u64);
+        bytes.put_slice(&self.payload);
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use anyhow::{anyhow, ensure, Result};
 use base64::prelude::*;
-use proto_bytes::{Buf, BytesMut, ConditionalBuf};
+use proto_bytes::{Buf, BufMut, BytesMut, ConditionalBuf, ConditionalBufMut};
 use serde::Deserialize;
 
 use crate::jwt::ES384PublicKey;
@@ -87,12 +87,18 @@ impl Packet for LoginPacket {
         })
     }
 
-    fn encode(&self, _bytes: &mut BytesMut) -> anyhow::Result<()> {
-        todo!()
+    fn encode(&self, bytes: &mut BytesMut) -> anyhow::Result<()> {
+        bytes.put_i32(self.protocol_version);
+        let mut request = BytesMut::new();
+        request.put_string_lu32(&self.identity);
+        request.put_string_lu32(&self.client);
+        bytes.put_varint(request.len() as u64);
+        bytes.put(request);
+        Ok(())
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct SkinData {
     pub animated_image_data: Vec<AnimatedImageDataType>,
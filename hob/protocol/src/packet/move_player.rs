@@ -0,0 +1,92 @@
+use anyhow::bail;
+use proto_bytes::{Buf, BufMut, ConditionalBuf, ConditionalBufMut};
+
+use super::Packet;
+
+#[derive(Debug, Clone)]
+pub struct MovePlayerPacket {
+    pub runtime_id: u64,
+    pub position: (f32, f32, f32),
+    pub pitch: f32,
+    pub yaw: f32,
+    pub head_yaw: f32,
+    pub mode: MoveMode,
+    pub on_ground: bool,
+    pub ridden_runtime_id: u64,
+    pub tick: u64,
+}
+
+impl Packet for MovePlayerPacket {
+    fn decode(bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        let runtime_id = bytes.get_varint();
+        let position = (bytes.get_f32_le(), bytes.get_f32_le(), bytes.get_f32_le());
+        let pitch = bytes.get_f32_le();
+        let yaw = bytes.get_f32_le();
+        let head_yaw = bytes.get_f32_le();
+        let mode = match bytes.get_u8() {
+            0 => MoveMode::Normal,
+            1 => MoveMode::Reset,
+            2 => MoveMode::Teleport {
+                cause: bytes.get_i32_le(),
+                source_entity_type: bytes.get_i32_le(),
+            },
+            3 => MoveMode::Rotation,
+            n => bail!("Unknown MoveMode:{}", n),
+        };
+        let on_ground = bytes.get_bool();
+        let ridden_runtime_id = bytes.get_varint();
+        let tick = bytes.get_varint();
+        Ok(MovePlayerPacket {
+            runtime_id,
+            position,
+            pitch,
+            yaw,
+            head_yaw,
+            mode,
+            on_ground,
+            ridden_runtime_id,
+            tick,
+        })
+    }
+
+    fn encode(&self, bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
+        bytes.put_varint(self.runtime_id);
+        {
+            let (x, y, z) = self.position;
+            bytes.put_f32_le(x);
+            bytes.put_f32_le(y);
+            bytes.put_f32_le(z);
+        }
+        bytes.put_f32_le(self.pitch);
+        bytes.put_f32_le(self.yaw);
+        bytes.put_f32_le(self.head_yaw);
+        match self.mode {
+            MoveMode::Normal => bytes.put_u8(0),
+            MoveMode::Reset => bytes.put_u8(1),
+            MoveMode::Teleport {
+                cause,
+                source_entity_type,
+            } => {
+                bytes.put_u8(2);
+                bytes.put_i32_le(cause);
+                bytes.put_i32_le(source_entity_type);
+            }
+            MoveMode::Rotation => bytes.put_u8(3),
+        }
+        bytes.put_bool(self.on_ground);
+        bytes.put_varint(self.ridden_runtime_id);
+        bytes.put_varint(self.tick);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum MoveMode {
+    Normal,
+    Reset,
+    Teleport { cause: i32, source_entity_type: i32 },
+    Rotation,
+}
@@ -0,0 +1,36 @@
+use proto_bytes::{BufMut, ConditionalBufMut};
+
+use super::Packet;
+
+#[derive(Debug)]
+pub struct NetworkChunkPublisherUpdatePacket {
+    pub position: (i32, i32, i32),
+    /// Radius in blocks.
+    pub radius: u32,
+    pub saved_chunks: Vec<(i32, i32)>,
+}
+
+impl Packet for NetworkChunkPublisherUpdatePacket {
+    fn decode(_bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        anyhow::bail!("NetworkChunkPublisherUpdatePacket is clientbound only")
+    }
+
+    fn encode(&self, bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
+        {
+            let (x, y, z) = self.position;
+            bytes.put_zigzag32(x);
+            bytes.put_zigzag32(y);
+            bytes.put_zigzag32(z);
+        }
+        bytes.put_varint(self.radius as u64);
+        bytes.put_u32_le(self.saved_chunks.len() as u32);
+        for (x, z) in self.saved_chunks.iter() {
+            bytes.put_zigzag32(*x);
+            bytes.put_zigzag32(*z);
+        }
+        Ok(())
+    }
+}
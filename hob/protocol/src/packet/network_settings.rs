@@ -1,4 +1,5 @@
-use proto_bytes::{BufMut, ConditionalBufMut};
+use anyhow::bail;
+use proto_bytes::{Buf, BufMut, ConditionalBuf, ConditionalBufMut};
 
 use super::Packet;
 
@@ -12,11 +13,23 @@ pub struct NetworkSettingsPacket {
 }
 
 impl Packet for NetworkSettingsPacket {
-    fn decode(_bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
+    fn decode(bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
     where
         Self: Sized,
     {
-        todo!()
+        let compression_threshold = bytes.get_u16();
+        let compression_algorithm = match bytes.get_u16() {
+            0 => CompressionAlgorithmType::Deflate,
+            1 => CompressionAlgorithmType::Snappy,
+            v => bail!("Unknown compression algorithm {v}"),
+        };
+        Ok(NetworkSettingsPacket {
+            compression_threshold,
+            compression_algorithm,
+            client_throttle: bytes.get_bool(),
+            client_throttle_threshold: bytes.get_u8(),
+            client_throttle_scalar: bytes.get_f32_le(),
+        })
     }
 
     fn encode(&self, bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
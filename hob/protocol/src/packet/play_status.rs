@@ -1,8 +1,10 @@
-use proto_bytes::BufMut;
+use from_num::from_num;
+use proto_bytes::{Buf, BufMut};
 
 use super::Packet;
 
 #[derive(Debug, Clone)]
+#[from_num(i32)]
 pub enum PlayStatusPacket {
     LoginSuccess,
     FailedClient,
@@ -17,8 +19,8 @@ pub enum PlayStatusPacket {
 }
 
 impl Packet for PlayStatusPacket {
-    fn decode(_bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self> {
-        todo!()
+    fn decode(bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self> {
+        PlayStatusPacket::from_i32(bytes.get_i32())
     }
 
     #[inline]
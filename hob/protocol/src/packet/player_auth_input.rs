@@ -0,0 +1,95 @@
+use proto_bytes::{Buf, ConditionalBuf};
+
+use super::Packet;
+
+#[derive(Debug)]
+pub struct PlayerAuthInputPacket {
+    pub pitch: f32,
+    pub yaw: f32,
+    pub position: (f32, f32, f32),
+    pub move_vector: (f32, f32),
+    pub head_yaw: f32,
+    pub input_data: u64,
+    pub input_mode: u32,
+    pub play_mode: u32,
+    pub interaction_model: i32,
+    pub gaze_direction: Option<(f32, f32, f32)>,
+    pub tick: u64,
+    pub delta: (f32, f32, f32),
+}
+impl PlayerAuthInputPacket {
+    #[inline]
+    pub fn has_flag(&self, flag: u64) -> bool {
+        self.input_data & (1 << flag) != 0
+    }
+}
+
+impl Packet for PlayerAuthInputPacket {
+    fn decode(bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        let pitch = bytes.get_f32_le();
+        let yaw = bytes.get_f32_le();
+        let position = (bytes.get_f32_le(), bytes.get_f32_le(), bytes.get_f32_le());
+        let move_vector = (bytes.get_f32_le(), bytes.get_f32_le());
+        let head_yaw = bytes.get_f32_le();
+        let input_data = bytes.get_varint();
+        let input_mode = bytes.get_varint() as u32;
+        let play_mode = bytes.get_varint() as u32;
+        let interaction_model = bytes.get_zigzag32();
+        let gaze_direction = if play_mode == PLAY_MODE_REALITY {
+            Some((bytes.get_f32_le(), bytes.get_f32_le(), bytes.get_f32_le()))
+        } else {
+            None
+        };
+        let tick = bytes.get_varint();
+        let delta = (bytes.get_f32_le(), bytes.get_f32_le(), bytes.get_f32_le());
+        // Item interactions, item stack requests, block actions and the analogue move
+        // vector follow; none of them are handled yet.
+        bytes.advance(bytes.remaining());
+        Ok(PlayerAuthInputPacket {
+            pitch,
+            yaw,
+            position,
+            move_vector,
+            head_yaw,
+            input_data,
+            input_mode,
+            play_mode,
+            interaction_model,
+            gaze_direction,
+            tick,
+            delta,
+        })
+    }
+
+    fn encode(&self, _bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
+        anyhow::bail!("PlayerAuthInputPacket is serverbound only")
+    }
+}
+
+pub const PLAY_MODE_REALITY: u32 = 4;
+
+pub mod input_flag {
+    pub const ASCEND: u64 = 0;
+    pub const DESCEND: u64 = 1;
+    pub const JUMP_DOWN: u64 = 3;
+    pub const SPRINT_DOWN: u64 = 4;
+    pub const JUMPING: u64 = 6;
+    pub const SNEAKING: u64 = 8;
+    pub const SNEAK_DOWN: u64 = 9;
+    pub const SPRINTING: u64 = 20;
+    pub const START_SPRINTING: u64 = 25;
+    pub const STOP_SPRINTING: u64 = 26;
+    pub const START_SNEAKING: u64 = 27;
+    pub const STOP_SNEAKING: u64 = 28;
+    pub const START_SWIMMING: u64 = 29;
+    pub const STOP_SWIMMING: u64 = 30;
+    pub const START_JUMPING: u64 = 31;
+    pub const START_GLIDING: u64 = 32;
+    pub const STOP_GLIDING: u64 = 33;
+    pub const PERFORM_ITEM_INTERACTION: u64 = 34;
+    pub const PERFORM_BLOCK_ACTIONS: u64 = 35;
+    pub const PERFORM_ITEM_STACK_REQUEST: u64 = 36;
+}
@@ -0,0 +1,287 @@
+use base64::prelude::*;
+use proto_bytes::{BufMut, ConditionalBufMut};
+use uuid::Uuid;
+
+use super::{login::SkinData, Packet};
+
+#[derive(Debug, Clone)]
+pub enum PlayerListPacket {
+    Add(Vec<PlayerListEntry>),
+    Remove(Vec<Uuid>),
+}
+
+#[derive(Debug, Clone)]
+pub struct PlayerListEntry {
+    pub uuid: Uuid,
+    pub unique_id: i64,
+    pub username: String,
+    pub xuid: String,
+    pub platform_chat_id: String,
+    pub build_platform: i32,
+    pub skin: Skin,
+    pub is_teacher: bool,
+    pub is_host: bool,
+    pub is_sub_client: bool,
+}
+
+impl Packet for PlayerListPacket {
+    fn decode(_bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        anyhow::bail!("PlayerListPacket is clientbound only")
+    }
+
+    // https://github.com/Sandertv/gophertunnel/blob/master/minecraft/protocol/packet/player_list.go
+
+    fn encode(&self, bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
+        match self {
+            PlayerListPacket::Add(entries) => {
+                bytes.put_u8(0);
+                bytes.put_varint(entries.len() as u64);
+                for entry in entries {
+                    put_uuid(bytes, &entry.uuid);
+                    bytes.put_zigzag64(entry.unique_id);
+                    bytes.put_string_varint(&entry.username);
+                    bytes.put_string_varint(&entry.xuid);
+                    bytes.put_string_varint(&entry.platform_chat_id);
+                    bytes.put_i32_le(entry.build_platform);
+                    entry.skin.encode(bytes);
+                    bytes.put_bool(entry.is_teacher);
+                    bytes.put_bool(entry.is_host);
+                    bytes.put_bool(entry.is_sub_client);
+                }
+                for entry in entries {
+                    bytes.put_bool(entry.skin.trusted);
+                }
+            }
+            PlayerListPacket::Remove(uuids) => {
+                bytes.put_u8(1);
+                bytes.put_varint(uuids.len() as u64);
+                for uuid in uuids {
+                    put_uuid(bytes, uuid);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn put_uuid(bytes: &mut proto_bytes::BytesMut, uuid: &Uuid) {
+    let (most_sig, least_sig) = uuid.as_u64_pair();
+    bytes.put_u64_le(most_sig);
+    bytes.put_u64_le(least_sig);
+}
+
+fn put_byte_slice(bytes: &mut proto_bytes::BytesMut, data: &[u8]) {
+    bytes.put_varint(data.len() as u64);
+    bytes.put_slice(data);
+}
+
+/// Skin as sent in `PlayerList`; image data is raw RGBA.
+#[derive(Debug, Clone)]
+pub struct Skin {
+    pub skin_id: String,
+    pub play_fab_id: String,
+    pub resource_patch: String,
+    pub image_width: u32,
+    pub image_height: u32,
+    pub image_data: Vec<u8>,
+    pub animations: Vec<SkinAnimation>,
+    pub cape_image_width: u32,
+    pub cape_image_height: u32,
+    pub cape_data: Vec<u8>,
+    pub geometry_data: String,
+    pub geometry_data_engine_version: String,
+    pub animation_data: String,
+    pub cape_id: String,
+    pub full_id: String,
+    pub arm_size: String,
+    pub skin_color: String,
+    pub persona_pieces: Vec<PersonaPiece>,
+    pub piece_tint_colors: Vec<PieceTintColor>,
+    pub premium: bool,
+    pub persona: bool,
+    pub cape_on_classic_skin: bool,
+    pub primary_user: bool,
+    pub override_appearance: bool,
+    pub trusted: bool,
+}
+impl Default for Skin {
+    /// Blank 64x64 classic skin.
+    fn default() -> Self {
+        Skin {
+            skin_id: "Standard_Custom".to_string(),
+            play_fab_id: "".to_string(),
+            resource_patch: r#"{"geometry":{"default":"geometry.humanoid.custom"}}"#.to_string(),
+            image_width: 64,
+            image_height: 64,
+            image_data: vec![0; 64 * 64 * 4],
+            animations: Vec::new(),
+            cape_image_width: 0,
+            cape_image_height: 0,
+            cape_data: Vec::new(),
+            geometry_data: "".to_string(),
+            geometry_data_engine_version: "".to_string(),
+            animation_data: "".to_string(),
+            cape_id: "".to_string(),
+            full_id: "Standard_Custom".to_string(),
+            arm_size: "wide".to_string(),
+            skin_color: "#0".to_string(),
+            persona_pieces: Vec::new(),
+            piece_tint_colors: Vec::new(),
+            premium: false,
+            persona: false,
+            cape_on_classic_skin: false,
+            primary_user: true,
+            override_appearance: true,
+            trusted: false,
+        }
+    }
+}
+impl Skin {
+    fn encode(&self, bytes: &mut proto_bytes::BytesMut) {
+        bytes.put_string_varint(&self.skin_id);
+        bytes.put_string_varint(&self.play_fab_id);
+        bytes.put_string_varint(&self.resource_patch);
+        bytes.put_u32_le(self.image_width);
+        bytes.put_u32_le(self.image_height);
+        put_byte_slice(bytes, &self.image_data);
+        bytes.put_u32_le(self.animations.len() as u32);
+        for animation in self.animations.iter() {
+            bytes.put_u32_le(animation.image_width);
+            bytes.put_u32_le(animation.image_height);
+            put_byte_slice(bytes, &animation.image_data);
+            bytes.put_u32_le(animation.animation_type);
+            bytes.put_f32_le(animation.frame_count);
+            bytes.put_u32_le(animation.expression_type);
+        }
+        bytes.put_u32_le(self.cape_image_width);
+        bytes.put_u32_le(self.cape_image_height);
+        put_byte_slice(bytes, &self.cape_data);
+        bytes.put_string_varint(&self.geometry_data);
+        bytes.put_string_varint(&self.geometry_data_engine_version);
+        bytes.put_string_varint(&self.animation_data);
+        bytes.put_string_varint(&self.cape_id);
+        bytes.put_string_varint(&self.full_id);
+        bytes.put_string_varint(&self.arm_size);
+        bytes.put_string_varint(&self.skin_color);
+        bytes.put_u32_le(self.persona_pieces.len() as u32);
+        for piece in self.persona_pieces.iter() {
+            bytes.put_string_varint(&piece.piece_id);
+            bytes.put_string_varint(&piece.piece_type);
+            bytes.put_string_varint(&piece.pack_id);
+            bytes.put_bool(piece.is_default);
+            bytes.put_string_varint(&piece.product_id);
+        }
+        bytes.put_u32_le(self.piece_tint_colors.len() as u32);
+        for tint in self.piece_tint_colors.iter() {
+            bytes.put_string_varint(&tint.piece_type);
+            bytes.put_u32_le(tint.colors.len() as u32);
+            for color in tint.colors.iter() {
+                bytes.put_string_varint(color);
+            }
+        }
+        bytes.put_bool(self.premium);
+        bytes.put_bool(self.persona);
+        bytes.put_bool(self.cape_on_classic_skin);
+        bytes.put_bool(self.primary_user);
+        bytes.put_bool(self.override_appearance);
+    }
+}
+
+impl TryFrom<&SkinData> for Skin {
+    type Error = anyhow::Error;
+
+    /// Decodes the base64 fields the client sends in its login chain.
+    fn try_from(data: &SkinData) -> anyhow::Result<Self> {
+        let text = |v: &str| -> anyhow::Result<String> {
+            Ok(String::from_utf8(BASE64_STANDARD.decode(v)?)?)
+        };
+        let animations = data
+            .animated_image_data
+            .iter()
+            .map(|v| {
+                Ok(SkinAnimation {
+                    image_width: v.image_width as u32,
+                    image_height: v.image_height as u32,
+                    image_data: BASE64_STANDARD.decode(&v.image)?,
+                    animation_type: v.t_ype as u32,
+                    frame_count: v.frames as f32,
+                    expression_type: v.animation_expression as u32,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Skin {
+            skin_id: data.skin_id.clone(),
+            play_fab_id: data.play_fab_id.clone(),
+            resource_patch: text(&data.skin_resource_patch)?,
+            image_width: data.skin_image_width as u32,
+            image_height: data.skin_image_height as u32,
+            image_data: BASE64_STANDARD.decode(&data.skin_data)?,
+            animations,
+            cape_image_width: data.cape_image_width as u32,
+            cape_image_height: data.cape_image_height as u32,
+            cape_data: BASE64_STANDARD.decode(&data.cape_data)?,
+            geometry_data: text(&data.skin_geometry_data)?,
+            // Older clients send the engine version unencoded.
+            geometry_data_engine_version: text(&data.skin_geometry_data_engine_version)
+                .unwrap_or_else(|_| data.skin_geometry_data_engine_version.clone()),
+            animation_data: text(&data.skin_animation_data)?,
+            cape_id: data.cape_id.clone(),
+            full_id: format!("{}{}", data.skin_id, data.cape_id),
+            arm_size: data.arm_size.clone(),
+            skin_color: data.skin_color.clone(),
+            persona_pieces: data
+                .persona_pieces
+                .iter()
+                .map(|v| PersonaPiece {
+                    piece_id: v.piece_id.clone(),
+                    piece_type: v.piece_type.clone(),
+                    pack_id: v.pack_id.clone(),
+                    is_default: v.is_default,
+                    product_id: v.product_id.clone(),
+                })
+                .collect(),
+            piece_tint_colors: data
+                .piece_tint_colors
+                .iter()
+                .map(|v| PieceTintColor {
+                    piece_type: v.piece_type.clone(),
+                    colors: v.colors.clone(),
+                })
+                .collect(),
+            premium: data.premium_skin,
+            persona: data.persona_skin,
+            cape_on_classic_skin: data.cape_on_classic_skin,
+            primary_user: true,
+            override_appearance: data.override_skin,
+            trusted: data.trusted_skin,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SkinAnimation {
+    pub image_width: u32,
+    pub image_height: u32,
+    pub image_data: Vec<u8>,
+    pub animation_type: u32,
+    pub frame_count: f32,
+    pub expression_type: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct PersonaPiece {
+    pub piece_id: String,
+    pub piece_type: String,
+    pub pack_id: String,
+    pub is_default: bool,
+    pub product_id: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct PieceTintColor {
+    pub piece_type: String,
+    pub colors: Vec<String>,
+}
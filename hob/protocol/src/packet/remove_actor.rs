@@ -0,0 +1,22 @@
+use proto_bytes::ConditionalBufMut;
+
+use super::Packet;
+
+#[derive(Debug, Clone)]
+pub struct RemoveActorPacket {
+    pub unique_id: i64,
+}
+
+impl Packet for RemoveActorPacket {
+    fn decode(_bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        anyhow::bail!("RemoveActorPacket is clientbound only")
+    }
+
+    fn encode(&self, bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
+        bytes.put_zigzag64(self.unique_id);
+        Ok(())
+    }
+}
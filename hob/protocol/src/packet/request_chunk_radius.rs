@@ -0,0 +1,29 @@
+use proto_bytes::{Buf, BufMut, ConditionalBuf, ConditionalBufMut};
+
+use super::Packet;
+
+#[derive(Debug)]
+pub struct RequestChunkRadiusPacket {
+    pub chunk_radius: i32,
+    pub max_chunk_radius: u8,
+}
+
+impl Packet for RequestChunkRadiusPacket {
+    fn decode(bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        let chunk_radius = bytes.get_zigzag32();
+        let max_chunk_radius = bytes.get_u8();
+        Ok(RequestChunkRadiusPacket {
+            chunk_radius,
+            max_chunk_radius,
+        })
+    }
+
+    fn encode(&self, bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
+        bytes.put_zigzag32(self.chunk_radius);
+        bytes.put_u8(self.max_chunk_radius);
+        Ok(())
+    }
+}
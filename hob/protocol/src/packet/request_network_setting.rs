@@ -1,4 +1,4 @@
-use proto_bytes::Buf;
+use proto_bytes::{Buf, BufMut};
 
 use super::Packet;
 
@@ -16,7 +16,8 @@ impl Packet for RequestNetworkSettingPacket {
         Ok(RequestNetworkSettingPacket { client_protocol })
     }
 
-    fn encode(&self, _bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
-        todo!()
+    fn encode(&self, bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
+        bytes.put_i32(self.client_protocol);
+        Ok(())
     }
 }
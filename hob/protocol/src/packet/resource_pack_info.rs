@@ -18,7 +18,7 @@ impl Packet for ResourcePacksInfoPacket {
     where
         Self: Sized,
     {
-        todo!()
+        anyhow::bail!("ResourcePacksInfoPacket is clientbound only")
     }
 
     #[inline]
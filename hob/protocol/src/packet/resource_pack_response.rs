@@ -28,7 +28,7 @@ impl Packet for ResourcePackClientResponsePacket {
     }
 
     fn encode(&self, _bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
-        todo!()
+        anyhow::bail!("ResourcePackClientResponsePacket is serverbound only")
     }
 }
 
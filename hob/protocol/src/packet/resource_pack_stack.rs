@@ -51,7 +51,7 @@ impl Packet for ResourcePacksStackPacket {
     where
         Self: Sized,
     {
-        todo!()
+        anyhow::bail!("ResourcePacksStackPacket is clientbound only")
     }
 
     fn encode(&self, bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+
+use anyhow::anyhow;
+use hob_nbt::VarInt;
+use proto_bytes::{BufMut, ConditionalBufMut};
+
+use super::Packet;
+
+#[derive(Debug, Clone)]
+pub struct SetActorDataPacket {
+    pub runtime_id: u64,
+    pub metadata: ActorMetadata,
+    pub tick: u64,
+}
+
+impl Packet for SetActorDataPacket {
+    fn decode(_bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        anyhow::bail!("SetActorDataPacket is clientbound only")
+    }
+
+    // https://github.com/Sandertv/gophertunnel/blob/master/minecraft/protocol/packet/set_actor_data.go
+
+    fn encode(&self, bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
+        bytes.put_varint(self.runtime_id);
+        self.metadata.encode(bytes)?;
+        // int and float actor properties
+        bytes.put_varint(0);
+        bytes.put_varint(0);
+        bytes.put_varint(self.tick);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataValue {
+    Byte(u8),
+    Short(i16),
+    Int(i32),
+    Float(f32),
+    String(String),
+    Compound(hob_nbt::value::Value),
+    BlockPos(i32, i32, i32),
+    Long(i64),
+    Vec3(f32, f32, f32),
+}
+impl MetadataValue {
+    pub fn type_id(&self) -> u32 {
+        match self {
+            MetadataValue::Byte(_) => 0,
+            MetadataValue::Short(_) => 1,
+            MetadataValue::Int(_) => 2,
+            MetadataValue::Float(_) => 3,
+            MetadataValue::String(_) => 4,
+            MetadataValue::Compound(_) => 5,
+            MetadataValue::BlockPos(..) => 6,
+            MetadataValue::Long(_) => 7,
+            MetadataValue::Vec3(..) => 8,
+        }
+    }
+    fn encode(&self, bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
+        match self {
+            MetadataValue::Byte(v) => bytes.put_u8(*v),
+            MetadataValue::Short(v) => bytes.put_i16_le(*v),
+            MetadataValue::Int(v) => {
+                bytes.put_zigzag32(*v);
+            }
+            MetadataValue::Float(v) => bytes.put_f32_le(*v),
+            MetadataValue::String(v) => {
+                bytes.put_string_varint(v);
+            }
+            MetadataValue::Compound(v) => {
+                bytes.put_slice(&VarInt::to_vec(v).map_err(|e| anyhow!("{e}"))?);
+            }
+            MetadataValue::BlockPos(x, y, z) => {
+                bytes.put_zigzag32(*x);
+                bytes.put_zigzag32(*y);
+                bytes.put_zigzag32(*z);
+            }
+            MetadataValue::Long(v) => {
+                bytes.put_zigzag64(*v);
+            }
+            MetadataValue::Vec3(x, y, z) => {
+                bytes.put_f32_le(*x);
+                bytes.put_f32_le(*y);
+                bytes.put_f32_le(*z);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Actor data dictionary keyed by [`metadata_key`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ActorMetadata(pub BTreeMap<u32, MetadataValue>);
+impl ActorMetadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn get(&self, key: u32) -> Option<&MetadataValue> {
+        self.0.get(&key)
+    }
+    /// Returns whether the stored value changed.
+    pub fn set(&mut self, key: u32, value: MetadataValue) -> bool {
+        match self.0.get(&key) {
+            Some(old) if *old == value => false,
+            _ => {
+                self.0.insert(key, value);
+                true
+            }
+        }
+    }
+    /// Key holding `flag`: flags 0..64 live in `FLAGS`, the rest in `FLAGS_2`.
+    pub fn flag_key(flag: u32) -> u32 {
+        if flag < 64 {
+            metadata_key::FLAGS
+        } else {
+            metadata_key::FLAGS_2
+        }
+    }
+    pub fn get_flag(&self, flag: u32) -> bool {
+        let (key, bit) = (Self::flag_key(flag), flag % 64);
+        match self.0.get(&key) {
+            Some(MetadataValue::Long(v)) => v & (1 << bit) != 0,
+            _ => false,
+        }
+    }
+    /// Returns whether the flag changed.
+    pub fn set_flag(&mut self, flag: u32, value: bool) -> bool {
+        let (key, bit) = (Self::flag_key(flag), flag % 64);
+        let flags = match self.0.get(&key) {
+            Some(MetadataValue::Long(v)) => *v,
+            _ => 0,
+        };
+        let flags = if value {
+            flags | (1 << bit)
+        } else {
+            flags & !(1 << bit)
+        };
+        self.set(key, MetadataValue::Long(flags))
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    pub fn encode(&self, bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
+        bytes.put_varint(self.0.len() as u64);
+        for (key, value) in self.0.iter() {
+            bytes.put_varint(*key as u64);
+            bytes.put_varint(value.type_id() as u64);
+            value.encode(bytes)?;
+        }
+        Ok(())
+    }
+}
+
+pub mod metadata_key {
+    pub const FLAGS: u32 = 0;
+    pub const HEALTH: u32 = 1;
+    pub const VARIANT: u32 = 2;
+    pub const COLOR: u32 = 3;
+    pub const NAME_TAG: u32 = 4;
+    pub const OWNER: u32 = 5;
+    pub const TARGET: u32 = 6;
+    pub const AIR: u32 = 7;
+    pub const PLAYER_FLAGS: u32 = 26;
+    pub const BED_POSITION: u32 = 28;
+    pub const SCALE: u32 = 38;
+    pub const MAX_AIR: u32 = 42;
+    pub const MARK_VARIANT: u32 = 43;
+    pub const BOUNDING_BOX_WIDTH: u32 = 53;
+    pub const BOUNDING_BOX_HEIGHT: u32 = 54;
+    pub const ALWAYS_SHOW_NAME_TAG: u32 = 81;
+    pub const FLAGS_2: u32 = 92;
+}
+
+pub mod actor_flag {
+    pub const ON_FIRE: u32 = 0;
+    pub const SNEAKING: u32 = 1;
+    pub const RIDING: u32 = 2;
+    pub const SPRINTING: u32 = 3;
+    pub const USING_ITEM: u32 = 4;
+    pub const INVISIBLE: u32 = 5;
+    pub const BABY: u32 = 11;
+    pub const CAN_SHOW_NAME_TAG: u32 = 14;
+    pub const ALWAYS_SHOW_NAME_TAG: u32 = 15;
+    pub const NO_AI: u32 = 16;
+    pub const SILENT: u32 = 17;
+    pub const CAN_CLIMB: u32 = 19;
+    pub const CAN_FLY: u32 = 21;
+    pub const GLIDING: u32 = 32;
+    pub const BREATHING: u32 = 35;
+    pub const HAS_COLLISION: u32 = 47;
+    pub const HAS_GRAVITY: u32 = 48;
+    pub const FIRE_IMMUNE: u32 = 49;
+    pub const SWIMMING: u32 = 56;
+}
@@ -9,81 +9,81 @@ use super::Packet;
 
 #[derive(Debug)]
 pub struct StartGamePacket {
-    entity_id: i64,
-    runtime_id: u64,
-    gamemode: GameMode,
-    player_position: (f32, f32, f32),
-    rotation: (f32, f32),
-    seed: u64,
-    biome_type: i16,
-    biome_name: String,
-    dimension: Dimension,
-    generator: i32,
-    world_gamemode: GameMode,
-    difficulty: i32,
-    spawn_position: (i32, i32, i32),
-    achievements_disabled: bool,
-    editor_world_type: EditorWorldType,
-    created_in_editor: bool,
-    exported_from_editor: bool,
-    day_cycle_stop_time: i32,
-    education_offer: i32,
-    education_features_enabled: bool,
-    education_product_uuid: String,
-    rain_level: f32,
-    lightning_level: f32,
-    has_confirmed_platform_locked_content: bool,
-    is_multiplayer: bool,
-    broadcast_to_lan: bool,
-    xbox_live_broadcast_mode: u64,
-    platform_broadcast_mode: u64,
-    enable_commands: bool,
-    is_texturepacks_required: bool,
-    gamerules: Vec<GameRule>,
-    experiments: Vec<Experiment>,
-    experiments_previously_used: bool,
-    bonus_chest: bool,
-    map_enabled: bool,
-    permission_level: PermissionLevel,
-    server_chunk_tick_range: i32,
-    has_locked_behavior_pack: bool,
-    has_locked_resource_pack: bool,
-    is_from_locked_world_template: bool,
-    msa_gamertags_only: bool,
-    is_from_world_template: bool,
-    is_world_template_settings_locked: bool,
-    only_spawn_v1_villagers: bool,
-    persona_disabled: bool,
-    custom_skins_disabled: bool,
-    emote_chat_muted: bool,
-    game_version: String,
-    limited_world_width: i32,
-    limited_world_length: i32,
-    is_new_nether: bool,
-    edu_resource_uri: EducationSharedResourceURI,
-    experimental_gameplay_override: bool,
-    chat_restriction_level: ChatRestrictionLevel,
-    disable_player_interactions: bool,
-    level_id: String,
-    world_name: String,
-    premium_world_template_id: String,
-    is_trial: bool,
-    movement_authority: MovementAuthority,
-    rewind_history_size: i32,
-    server_authoritative_block_breaking: bool,
-    current_tick: i64,
-    enchantment_seed: i32,
-    block_properties: Vec<BlockProperty>,
-    itemstates: Vec<ItemState>,
-    multiplayer_correlation_id: String,
-    server_authoritative_inventory: bool,
-    engine: String,
-    property_data: hob_nbt::value::Value,
-    block_pallette_checksum: u64,
-    world_template_id: Uuid,
-    client_side_generation: bool,
-    block_network_ids_are_hashes: bool,
-    server_controlled_sound: bool,
+    pub entity_id: i64,
+    pub runtime_id: u64,
+    pub gamemode: GameMode,
+    pub player_position: (f32, f32, f32),
+    pub rotation: (f32, f32),
+    pub seed: u64,
+    pub biome_type: i16,
+    pub biome_name: String,
+    pub dimension: Dimension,
+    pub generator: i32,
+    pub world_gamemode: GameMode,
+    pub difficulty: i32,
+    pub spawn_position: (i32, i32, i32),
+    pub achievements_disabled: bool,
+    pub editor_world_type: EditorWorldType,
+    pub created_in_editor: bool,
+    pub exported_from_editor: bool,
+    pub day_cycle_stop_time: i32,
+    pub education_offer: i32,
+    pub education_features_enabled: bool,
+    pub education_product_uuid: String,
+    pub rain_level: f32,
+    pub lightning_level: f32,
+    pub has_confirmed_platform_locked_content: bool,
+    pub is_multiplayer: bool,
+    pub broadcast_to_lan: bool,
+    pub xbox_live_broadcast_mode: u64,
+    pub platform_broadcast_mode: u64,
+    pub enable_commands: bool,
+    pub is_texturepacks_required: bool,
+    pub gamerules: Vec<GameRule>,
+    pub experiments: Vec<Experiment>,
+    pub experiments_previously_used: bool,
+    pub bonus_chest: bool,
+    pub map_enabled: bool,
+    pub permission_level: PermissionLevel,
+    pub server_chunk_tick_range: i32,
+    pub has_locked_behavior_pack: bool,
+    pub has_locked_resource_pack: bool,
+    pub is_from_locked_world_template: bool,
+    pub msa_gamertags_only: bool,
+    pub is_from_world_template: bool,
+    pub is_world_template_settings_locked: bool,
+    pub only_spawn_v1_villagers: bool,
+    pub persona_disabled: bool,
+    pub custom_skins_disabled: bool,
+    pub emote_chat_muted: bool,
+    pub game_version: String,
+    pub limited_world_width: i32,
+    pub limited_world_length: i32,
+    pub is_new_nether: bool,
+    pub edu_resource_uri: EducationSharedResourceURI,
+    pub experimental_gameplay_override: bool,
+    pub chat_restriction_level: ChatRestrictionLevel,
+    pub disable_player_interactions: bool,
+    pub level_id: String,
+    pub world_name: String,
+    pub premium_world_template_id: String,
+    pub is_trial: bool,
+    pub movement_authority: MovementAuthority,
+    pub rewind_history_size: i32,
+    pub server_authoritative_block_breaking: bool,
+    pub current_tick: i64,
+    pub enchantment_seed: i32,
+    pub block_properties: Vec<BlockProperty>,
+    pub itemstates: Vec<ItemState>,
+    pub multiplayer_correlation_id: String,
+    pub server_authoritative_inventory: bool,
+    pub engine: String,
+    pub property_data: hob_nbt::value::Value,
+    pub block_pallette_checksum: u64,
+    pub world_template_id: Uuid,
+    pub client_side_generation: bool,
+    pub block_network_ids_are_hashes: bool,
+    pub server_controlled_sound: bool,
 }
 impl StartGamePacket {
     pub fn new(runtime_id: u64, game_mode: GameMode) -> Self {
@@ -172,7 +172,7 @@ impl Packet for StartGamePacket {
     where
         Self: Sized,
     {
-        todo!()
+        anyhow::bail!("StartGamePacket is clientbound only")
     }
 
     // https://github.com/Sandertv/gophertunnel/blob/master/minecraft/protocol/packet/start_game.go
@@ -316,7 +316,7 @@ pub enum GameMode {
     Spectator,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Dimension {
     OverWorld,
     Nether,
@@ -373,7 +373,7 @@ pub struct Experiment {
     enable: bool,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PermissionLevel {
     Visitor,
     Member,
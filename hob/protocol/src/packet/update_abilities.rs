@@ -0,0 +1,23 @@
+use super::{add_player::AbilityData, Packet};
+
+/// Replaces the abilities and permission level the client shows for a player.
+#[derive(Debug, Clone)]
+pub struct UpdateAbilitiesPacket {
+    pub ability_data: AbilityData,
+}
+
+impl Packet for UpdateAbilitiesPacket {
+    fn decode(_bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        anyhow::bail!("UpdateAbilitiesPacket is clientbound only")
+    }
+
+    // https://github.com/Sandertv/gophertunnel/blob/master/minecraft/protocol/packet/update_abilities.go
+
+    fn encode(&self, bytes: &mut proto_bytes::BytesMut) -> anyhow::Result<()> {
+        self.ability_data.encode(bytes);
+        Ok(())
+    }
+}
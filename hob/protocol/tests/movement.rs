@@ -0,0 +1,126 @@
+use hob_protocol::packet::{
+    move_player::{MoveMode, MovePlayerPacket},
+    player_auth_input::{input_flag, PlayerAuthInputPacket},
+    Packet,
+};
+use proto_bytes::BytesMut;
+
+fn reset() -> MovePlayerPacket {
+    MovePlayerPacket {
+        runtime_id: 1,
+        position: (0.5, 64.0, -1.0),
+        pitch: 0.0,
+        yaw: 90.0,
+        head_yaw: 90.0,
+        mode: MoveMode::Reset,
+        on_ground: true,
+        ridden_runtime_id: 0,
+        tick: 300,
+    }
+}
+
+const RESET: [u8; 30] = [
+    1, // runtime id
+    0x00, 0x00, 0x00, 0x3f, 0x00, 0x00, 0x80, 0x42, 0x00, 0x00, 0x80, 0xbf, // position
+    0x00, 0x00, 0x00, 0x00, // pitch
+    0x00, 0x00, 0xb4, 0x42, 0x00, 0x00, 0xb4, 0x42, // yaw, head yaw
+    1,    // mode: reset
+    1,    // on ground
+    0,    // ridden runtime id
+    0xac, 0x02, // tick 300
+];
+
+#[test]
+fn move_player_encoding() {
+    let mut bytes = BytesMut::new();
+    reset().encode(&mut bytes).unwrap();
+    assert_eq!(bytes[..], RESET);
+}
+
+#[test]
+fn move_player_decoding() {
+    let packet = MovePlayerPacket::decode(&mut BytesMut::from(&RESET[..])).unwrap();
+    assert!(matches!(
+        packet,
+        MovePlayerPacket {
+            runtime_id: 1,
+            position: (0.5, 64.0, -1.0),
+            mode: MoveMode::Reset,
+            on_ground: true,
+            ridden_runtime_id: 0,
+            tick: 300,
+            ..
+        } if (packet.pitch, packet.yaw, packet.head_yaw) == (0.0, 90.0, 90.0)
+    ));
+
+    // Teleports carry their cause and source.
+    let mut teleport = RESET.to_vec();
+    teleport.splice(25..26, [2, 1, 0, 0, 0, 0x0c, 0, 0, 0]);
+    let packet = MovePlayerPacket::decode(&mut BytesMut::from(&teleport[..])).unwrap();
+    assert!(matches!(
+        packet.mode,
+        MoveMode::Teleport {
+            cause: 1,
+            source_entity_type: 12
+        }
+    ));
+    assert_eq!(packet.tick, 300);
+    let mut bytes = BytesMut::new();
+    packet.encode(&mut bytes).unwrap();
+    assert_eq!(bytes[..], teleport[..]);
+
+    let mut unknown = RESET.to_vec();
+    unknown[25] = 4;
+    assert!(MovePlayerPacket::decode(&mut BytesMut::from(&unknown[..])).is_err());
+}
+
+fn auth_input(play_mode: u8, gaze: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![
+        0x00, 0x00, 0x80, 0x3f, // pitch 1.0
+        0x00, 0x00, 0xb4, 0x42, // yaw 90.0
+        0x00, 0x00, 0x00, 0x3f, 0x00, 0x00, 0x80, 0x42, 0x00, 0x00, 0x80, 0xbf, // position
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x3f, // move vector (0.0, 1.0)
+        0x00, 0x00, 0xb4, 0x42, // head yaw 90.0
+        0x80, 0x82, 0x40, // input data: sneaking and sprinting
+        1,    // input mode: mouse
+        play_mode, 0, // interaction model
+    ];
+    bytes.extend_from_slice(gaze);
+    bytes.extend_from_slice(&[
+        0xac, 0x02, // tick 300
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0xbf, 0x00, 0x00, 0x00, 0x00, // delta
+        0x00, 0x00, // item interactions and the like, skipped
+    ]);
+    bytes
+}
+
+#[test]
+fn player_auth_input_decoding() {
+    let mut bytes = BytesMut::from(&auth_input(0, &[])[..]);
+    let packet = PlayerAuthInputPacket::decode(&mut bytes).unwrap();
+    assert!(bytes.is_empty());
+    assert_eq!(
+        (packet.pitch, packet.yaw, packet.head_yaw),
+        (1.0, 90.0, 90.0)
+    );
+    assert_eq!(packet.position, (0.5, 64.0, -1.0));
+    assert_eq!(packet.move_vector, (0.0, 1.0));
+    assert!(packet.has_flag(input_flag::SNEAKING));
+    assert!(packet.has_flag(input_flag::SPRINTING));
+    assert!(!packet.has_flag(input_flag::JUMPING));
+    assert_eq!((packet.input_mode, packet.play_mode), (1, 0));
+    assert_eq!(packet.interaction_model, 0);
+    assert_eq!(packet.gaze_direction, None);
+    assert_eq!(packet.tick, 300);
+    assert_eq!(packet.delta, (0.0, -1.0, 0.0));
+
+    // Only reality mode sends a gaze direction.
+    let gaze = [
+        0x00, 0x00, 0x80, 0x3f, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+    let mut bytes = BytesMut::from(&auth_input(4, &gaze)[..]);
+    let packet = PlayerAuthInputPacket::decode(&mut bytes).unwrap();
+    assert_eq!(packet.gaze_direction, Some((1.0, 0.0, 0.0)));
+    assert_eq!(packet.tick, 300);
+    assert_eq!(packet.delta, (0.0, -1.0, 0.0));
+}
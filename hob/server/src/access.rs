@@ -0,0 +1,371 @@
+use std::{
+    fmt,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::RwLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+use hob_protocol::packet::disconnect::{DisconnectFailReason, DisconnectPacket};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+
+pub const DEFAULT_WHITELIST_PATH: &str = "whitelist.json";
+pub const DEFAULT_BANS_PATH: &str = "bans.json";
+
+/// An address or CIDR block such as `192.168.0.0/16`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpNet {
+    addr: IpAddr,
+    prefix: u8,
+}
+impl IpNet {
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match (self.addr, addr.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                prefix_eq(&net.octets(), &addr.octets(), self.prefix)
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                prefix_eq(&net.octets(), &addr.octets(), self.prefix)
+            }
+            _ => false,
+        }
+    }
+}
+fn prefix_eq(a: &[u8], b: &[u8], prefix: u8) -> bool {
+    let (bytes, bits) = (prefix as usize / 8, prefix % 8);
+    a[..bytes] == b[..bytes] && (bits == 0 || (a[bytes] ^ b[bytes]) >> (8 - bits) == 0)
+}
+impl FromStr for IpNet {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let addr = IpAddr::from_str(addr)
+            .with_context(|| format!("Invalid address {addr:?}"))?
+            .to_canonical();
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(v) => v.parse().with_context(|| format!("Invalid prefix {v:?}"))?,
+            None => max,
+        };
+        if prefix > max {
+            bail!("Prefix /{prefix} is too long for {addr}");
+        }
+        Ok(IpNet { addr, prefix })
+    }
+}
+impl fmt::Display for IpNet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.addr.is_ipv4(), self.prefix) {
+            (true, 32) | (false, 128) => write!(f, "{}", self.addr),
+            _ => write!(f, "{}/{}", self.addr, self.prefix),
+        }
+    }
+}
+impl Serialize for IpNet {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+impl<'de> Deserialize<'de> for IpNet {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        String::deserialize(d)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "lowercase")]
+pub enum BanTarget {
+    Xuid(String),
+    /// Display name, compared case-insensitively.
+    Name(String),
+    Ip(IpNet),
+}
+impl BanTarget {
+    fn matches(&self, xuid: &str, name: &str, addr: IpAddr) -> bool {
+        match self {
+            BanTarget::Xuid(banned) => !banned.is_empty() && banned == xuid,
+            BanTarget::Name(banned) => banned.eq_ignore_ascii_case(name),
+            BanTarget::Ip(net) => net.contains(addr),
+        }
+    }
+    fn same(&self, other: &BanTarget) -> bool {
+        match (self, other) {
+            (BanTarget::Name(a), BanTarget::Name(b)) => a.eq_ignore_ascii_case(b),
+            (a, b) => a == b,
+        }
+    }
+}
+impl fmt::Display for BanTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BanTarget::Xuid(xuid) => write!(f, "xuid {xuid}"),
+            BanTarget::Name(name) => write!(f, "{name}"),
+            BanTarget::Ip(net) => write!(f, "{net}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BanEntry {
+    #[serde(flatten)]
+    pub target: BanTarget,
+    /// Name of the player when they were banned, so XUID bans can be lifted by name.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default)]
+    pub reason: String,
+    /// Unix time in seconds; `None` bans forever.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<u64>,
+}
+impl BanEntry {
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires.is_some_and(|t| t <= now)
+    }
+    fn message(&self, now: u64) -> String {
+        let mut message = "You are banned from this server".to_owned();
+        if !self.reason.is_empty() {
+            message += &format!(": {}", self.reason);
+        }
+        if let Some(expires) = self.expires {
+            message += &format!(
+                " (expires in {})",
+                format_duration(expires.saturating_sub(now))
+            );
+        }
+        message
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WhitelistEntry {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub xuid: String,
+}
+impl WhitelistEntry {
+    fn matches(&self, xuid: &str, name: &str) -> bool {
+        if self.xuid.is_empty() || xuid.is_empty() {
+            self.name.eq_ignore_ascii_case(name)
+        } else {
+            self.xuid == xuid
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct WhitelistFile {
+    enabled: bool,
+    players: Vec<WhitelistEntry>,
+}
+
+#[derive(Debug, Default)]
+struct AccessLists {
+    whitelist: WhitelistFile,
+    bans: Vec<BanEntry>,
+}
+
+/// Whitelist and ban list shared by the login handler and the game.
+/// Every change is written back to the files it was loaded from.
+#[derive(Debug, Default)]
+pub struct AccessControl {
+    whitelist_path: Option<PathBuf>,
+    bans_path: Option<PathBuf>,
+    lists: RwLock<AccessLists>,
+}
+impl AccessControl {
+    /// Reads both files; missing ones start empty with the whitelist off.
+    pub fn load(whitelist_path: impl AsRef<Path>, bans_path: impl AsRef<Path>) -> Result<Self> {
+        let access = AccessControl {
+            whitelist_path: Some(whitelist_path.as_ref().to_owned()),
+            bans_path: Some(bans_path.as_ref().to_owned()),
+            lists: RwLock::default(),
+        };
+        access.reload()?;
+        Ok(access)
+    }
+    /// Re-reads the files, picking up edits made while the server runs.
+    pub fn reload(&self) -> Result<()> {
+        let whitelist = read_json(self.whitelist_path.as_deref())?;
+        let bans = read_json(self.bans_path.as_deref())?;
+        *self.lists.write().unwrap() = AccessLists { whitelist, bans };
+        Ok(())
+    }
+
+    /// Returns the packet to turn a player away with, if they may not join.
+    pub fn check(&self, xuid: &str, name: &str, addr: IpAddr) -> Option<DisconnectPacket> {
+        let lists = self.lists.read().unwrap();
+        let now = unix_now();
+        if let Some(ban) = lists
+            .bans
+            .iter()
+            .find(|b| !b.is_expired(now) && b.target.matches(xuid, name, addr))
+        {
+            return Some(DisconnectPacket::new(
+                DisconnectFailReason::Kicked,
+                ban.message(now),
+            ));
+        }
+        let whitelist = &lists.whitelist;
+        if whitelist.enabled && !whitelist.players.iter().any(|e| e.matches(xuid, name)) {
+            return Some(DisconnectPacket::new(
+                DisconnectFailReason::NotAllowed,
+                "You are not whitelisted on this server",
+            ));
+        }
+        None
+    }
+
+    pub fn whitelist_enabled(&self) -> bool {
+        self.lists.read().unwrap().whitelist.enabled
+    }
+    pub fn set_whitelist_enabled(&self, enabled: bool) -> Result<()> {
+        let mut lists = self.lists.write().unwrap();
+        lists.whitelist.enabled = enabled;
+        write_json(self.whitelist_path.as_deref(), &lists.whitelist)
+    }
+    pub fn whitelist(&self) -> Vec<WhitelistEntry> {
+        self.lists.read().unwrap().whitelist.players.clone()
+    }
+    pub fn is_whitelisted(&self, xuid: &str, name: &str) -> bool {
+        let lists = self.lists.read().unwrap();
+        lists
+            .whitelist
+            .players
+            .iter()
+            .any(|e| e.matches(xuid, name))
+    }
+    /// Returns false if the player was already whitelisted.
+    pub fn whitelist_add(&self, name: &str, xuid: &str) -> Result<bool> {
+        let mut lists = self.lists.write().unwrap();
+        let players = &mut lists.whitelist.players;
+        if players.iter().any(|e| e.matches(xuid, name)) {
+            return Ok(false);
+        }
+        players.push(WhitelistEntry {
+            name: name.to_owned(),
+            xuid: xuid.to_owned(),
+        });
+        write_json(self.whitelist_path.as_deref(), &lists.whitelist)?;
+        Ok(true)
+    }
+    /// Removes entries with this name or XUID. Returns false if there were none.
+    pub fn whitelist_remove(&self, name_or_xuid: &str) -> Result<bool> {
+        let mut lists = self.lists.write().unwrap();
+        let players = &mut lists.whitelist.players;
+        let len = players.len();
+        players.retain(|e| !e.name.eq_ignore_ascii_case(name_or_xuid) && e.xuid != name_or_xuid);
+        if players.len() == len {
+            return Ok(false);
+        }
+        write_json(self.whitelist_path.as_deref(), &lists.whitelist)?;
+        Ok(true)
+    }
+
+    /// Bans still in effect.
+    pub fn bans(&self) -> Vec<BanEntry> {
+        let now = unix_now();
+        let lists = self.lists.read().unwrap();
+        lists
+            .bans
+            .iter()
+            .filter(|b| !b.is_expired(now))
+            .cloned()
+            .collect()
+    }
+    /// Adds or replaces the ban on `entry.target`.
+    pub fn ban(&self, entry: BanEntry) -> Result<()> {
+        let mut lists = self.lists.write().unwrap();
+        let now = unix_now();
+        lists
+            .bans
+            .retain(|b| !b.is_expired(now) && !b.target.same(&entry.target));
+        lists.bans.push(entry);
+        write_json(self.bans_path.as_deref(), &lists.bans)
+    }
+    /// Returns false if `target` wasn't banned.
+    pub fn unban(&self, target: &BanTarget) -> Result<bool> {
+        self.unban_where(|b| b.target.same(target))
+    }
+    /// Lifts name bans and bans recorded under this name. Returns false if there were none.
+    pub fn unban_player(&self, name: &str) -> Result<bool> {
+        let target = BanTarget::Name(name.to_owned());
+        self.unban_where(|b| b.target.same(&target) || b.name.eq_ignore_ascii_case(name))
+    }
+    fn unban_where(&self, lifted: impl Fn(&BanEntry) -> bool) -> Result<bool> {
+        let mut lists = self.lists.write().unwrap();
+        let now = unix_now();
+        let len = lists.bans.len();
+        lists.bans.retain(|b| !lifted(b));
+        let removed = lists.bans.len() != len;
+        lists.bans.retain(|b| !b.is_expired(now));
+        write_json(self.bans_path.as_deref(), &lists.bans)?;
+        Ok(removed)
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Parses durations like `30s`, `15m`, `12h`, `7d` or `2w` into seconds.
+pub fn parse_duration(s: &str) -> Result<u64> {
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (amount, unit) = s.split_at(split);
+    let amount: u64 = amount
+        .parse()
+        .with_context(|| format!("Invalid duration {s:?}"))?;
+    let scale = match unit {
+        "s" | "" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => bail!("Invalid duration unit {unit:?}"),
+    };
+    amount
+        .checked_mul(scale)
+        .with_context(|| format!("Duration too long: {s}"))
+}
+
+fn format_duration(secs: u64) -> String {
+    match secs {
+        s if s >= 24 * 60 * 60 => format!("{}d", s / (24 * 60 * 60)),
+        s if s >= 60 * 60 => format!("{}h", s / (60 * 60)),
+        s if s >= 60 => format!("{}m", s / 60),
+        s => format!("{s}s"),
+    }
+}
+
+fn read_json<T: DeserializeOwned + Default>(path: Option<&Path>) -> Result<T> {
+    let Some(path) = path else {
+        return Ok(T::default());
+    };
+    match std::fs::read_to_string(path) {
+        Ok(text) => {
+            serde_json::from_str(&text).with_context(|| format!("Invalid {}", path.display()))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(e).context(format!("Failed to read {}", path.display())),
+    }
+}
+
+fn write_json<T: Serialize>(path: Option<&Path>, value: &T) -> Result<()> {
+    let Some(path) = path else {
+        return Ok(());
+    };
+    let text = serde_json::to_string_pretty(value)?;
+    std::fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))
+}
@@ -0,0 +1,482 @@
+use std::{
+    collections::BTreeMap,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use log::LevelFilter;
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+
+use crate::{
+    access::{DEFAULT_BANS_PATH, DEFAULT_WHITELIST_PATH},
+    initial_handler::PROTOCOL_VERSION,
+    motd::Motd,
+    slots::DEFAULT_MAX_PLAYERS,
+};
+
+pub const DEFAULT_CONFIG_PATH: &str = "hob.toml";
+pub const DEFAULT_PERMISSIONS_PATH: &str = "permissions.json";
+pub const DEFAULT_LEVEL_PATH: &str = "level.dat";
+/// Prefix of the environment variables overriding the file, e.g. `HOB_NETWORK_BIND`.
+pub const ENV_PREFIX: &str = "HOB_";
+
+/// Everything a deployment may want to change, read from [`DEFAULT_CONFIG_PATH`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// `error`, `warn`, `info`, `debug`, `trace` or `off`.
+    pub log_level: String,
+    pub network: NetworkConfig,
+    pub players: PlayersConfig,
+    pub game: GameConfig,
+    pub level: LevelConfig,
+    pub motd: Motd,
+    pub files: FilesConfig,
+    pub rcon: RconConfig,
+    pub query: QueryConfig,
+    pub flood: FloodConfig,
+}
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            log_level: "info".to_owned(),
+            network: NetworkConfig::default(),
+            players: PlayersConfig::default(),
+            game: GameConfig::default(),
+            level: LevelConfig::default(),
+            motd: Motd::default(),
+            files: FilesConfig::default(),
+            rcon: RconConfig::default(),
+            query: QueryConfig::default(),
+            flood: FloodConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// Addresses to listen on, IPv4 or IPv6, e.g. `["0.0.0.0:19132", "[::]:19133"]`.
+    pub bind: Vec<SocketAddr>,
+    /// The only protocol clients may log in with.
+    pub protocol_version: i32,
+    /// Batches at least this large are compressed.
+    pub compression_threshold: u16,
+    /// Logged-in players waiting for the game to pick them up.
+    pub player_queue: usize,
+    /// Packets buffered from each client.
+    pub packet_queue: usize,
+    /// Encoded bytes waiting to be sent to a client before it counts as behind.
+    pub outbound_bytes: usize,
+    /// How long a client may stay behind before it is disconnected.
+    pub slow_client_secs: u64,
+}
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        NetworkConfig {
+            bind: vec![
+                (Ipv4Addr::UNSPECIFIED, 19132).into(),
+                (Ipv6Addr::UNSPECIFIED, 19133).into(),
+            ],
+            protocol_version: PROTOCOL_VERSION,
+            compression_threshold: 512,
+            player_queue: 32,
+            packet_queue: 32,
+            outbound_bytes: 4 << 20,
+            slow_client_secs: 15,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlayersConfig {
+    pub max: usize,
+    /// Slots out of `max` only operators may take.
+    pub reserved: usize,
+    pub duplicate_login: DuplicateLoginPolicy,
+}
+impl Default for PlayersConfig {
+    fn default() -> Self {
+        PlayersConfig {
+            max: DEFAULT_MAX_PLAYERS,
+            reserved: 0,
+            duplicate_login: DuplicateLoginPolicy::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    /// Ticks per second.
+    pub tps: u32,
+}
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig { tps: 20 }
+    }
+}
+
+/// The world. `generator`, `seed` and `flat_world_layers` only apply when the world is
+/// created; from then on they are read from `files.level`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LevelConfig {
+    pub generator: LevelGenerator,
+    /// A random one when unset.
+    pub seed: Option<i64>,
+    /// Layers of a flat world, in the JSON of `FlatWorldLayers` in level.dat. The default
+    /// layers when unset.
+    pub flat_world_layers: Option<String>,
+    pub game_mode: LevelGameMode,
+    /// Upper bound for the chunk radius clients ask for.
+    pub view_distance: i32,
+    pub movement_authority: LevelMovementAuthority,
+}
+impl Default for LevelConfig {
+    fn default() -> Self {
+        LevelConfig {
+            generator: LevelGenerator::default(),
+            seed: None,
+            flat_world_layers: None,
+            game_mode: LevelGameMode::default(),
+            view_distance: 10,
+            movement_authority: LevelMovementAuthority::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LevelGenerator {
+    #[default]
+    Flat,
+    Overworld,
+    Void,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LevelGameMode {
+    #[default]
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+/// Who decides where players are.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LevelMovementAuthority {
+    /// Positions reported by the client are taken as they are.
+    #[default]
+    Client,
+    /// Input is validated; cheating clients are reset with `MovePlayer`.
+    Server,
+    /// Like `server`, but clients replay their input from the correction on.
+    ServerWithRewind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilesConfig {
+    pub permissions: PathBuf,
+    pub whitelist: PathBuf,
+    pub bans: PathBuf,
+    /// Seed, generator and spawn point of the world, in Bedrock's level.dat format.
+    pub level: PathBuf,
+}
+impl Default for FilesConfig {
+    fn default() -> Self {
+        FilesConfig {
+            permissions: DEFAULT_PERMISSIONS_PATH.into(),
+            whitelist: DEFAULT_WHITELIST_PATH.into(),
+            bans: DEFAULT_BANS_PATH.into(),
+            level: DEFAULT_LEVEL_PATH.into(),
+        }
+    }
+}
+
+/// Remote administration over the Source RCON protocol.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RconConfig {
+    pub enabled: bool,
+    pub bind: SocketAddr,
+    /// Required when enabled.
+    pub password: String,
+    /// Commands each connection may run per second, in bursts of up to as many.
+    pub commands_per_second: u32,
+    /// Wrong passwords an address may send before it is locked out.
+    pub max_auth_failures: u32,
+    pub lockout_secs: u64,
+}
+impl Default for RconConfig {
+    fn default() -> Self {
+        RconConfig {
+            enabled: false,
+            bind: (Ipv4Addr::LOCALHOST, 25575).into(),
+            password: String::new(),
+            commands_per_second: 5,
+            max_auth_failures: 3,
+            lockout_secs: 300,
+        }
+    }
+}
+
+/// GameSpy4 status queries, as used by server lists and monitoring.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QueryConfig {
+    pub enabled: bool,
+    /// Addresses of sockets of its own; when empty, queries are answered on the game ports.
+    pub bind: Vec<SocketAddr>,
+}
+
+/// Limits on what a single address or connection may send.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FloodConfig {
+    /// Connections an address may open per minute, in bursts of up to as many.
+    pub connections_per_minute: u32,
+    /// Logins being verified at once; verification is costly, so more are turned away.
+    pub max_pending_logins: usize,
+    /// Clients relayed to the RakNet listeners at once, each taking a socket.
+    pub max_relays: usize,
+    /// Packets a connection may send per second, in bursts of up to as many.
+    pub packets_per_second: u32,
+    pub bytes_per_second: u32,
+    /// Offences within `ban_secs` that get an address banned for `ban_secs`; 0 never bans.
+    pub ban_after: u32,
+    pub ban_secs: u64,
+}
+impl Default for FloodConfig {
+    fn default() -> Self {
+        FloodConfig {
+            connections_per_minute: 20,
+            max_pending_logins: 32,
+            max_relays: 1024,
+            packets_per_second: 500,
+            bytes_per_second: 1 << 20,
+            ban_after: 3,
+            ban_secs: 300,
+        }
+    }
+}
+
+/// What to do when an account logs in while it already has a session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateLoginPolicy {
+    /// Disconnect the existing session with `LoggedInOtherLocation` and let the new one in.
+    #[default]
+    KickOld,
+    /// Keep the existing session and turn the new login away.
+    RejectNew,
+}
+impl DuplicateLoginPolicy {
+    pub fn inverse(self) -> Self {
+        match self {
+            DuplicateLoginPolicy::KickOld => DuplicateLoginPolicy::RejectNew,
+            DuplicateLoginPolicy::RejectNew => DuplicateLoginPolicy::KickOld,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Reads `path`, writing the defaults there first if it doesn't exist, then applies
+    /// `HOB_*` environment overrides and validates the result.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let text = toml::to_string_pretty(&ServerConfig::default())?;
+                std::fs::write(path, &text)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                text
+            }
+            Err(e) => return Err(e).context(format!("Failed to read {}", path.display())),
+        };
+        Self::parse(&text, std::env::vars())
+            .with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    /// Parses `text`, overridden by the `HOB_*` entries of `env`.
+    pub fn parse(text: &str, env: impl IntoIterator<Item = (String, String)>) -> Result<Self> {
+        let config: ServerConfig = toml::from_str(text)?;
+        let mut table = Table::try_from(&config)?;
+        let env: Vec<_> = env
+            .into_iter()
+            .filter(|(key, _)| key.starts_with(ENV_PREFIX))
+            .collect();
+        apply_env(&mut table, ENV_PREFIX, &env)?;
+        let config: ServerConfig = Value::Table(table).try_into()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        self.log_level()?;
+        let network = &self.network;
+        if network.bind.is_empty() {
+            bail!("network.bind needs at least one address");
+        }
+        if network.protocol_version <= 0 {
+            bail!("network.protocol_version must be positive");
+        }
+        if network.player_queue == 0 || network.packet_queue == 0 {
+            bail!("network.player_queue and network.packet_queue must be at least 1");
+        }
+        if network.outbound_bytes == 0 || network.slow_client_secs == 0 {
+            bail!("network.outbound_bytes and network.slow_client_secs must be at least 1");
+        }
+        if self.players.max == 0 {
+            bail!("players.max must be at least 1");
+        }
+        if self.players.reserved > self.players.max {
+            bail!(
+                "players.reserved ({}) can't exceed players.max ({})",
+                self.players.reserved,
+                self.players.max
+            );
+        }
+        if !(1..=1000).contains(&self.game.tps) {
+            bail!("game.tps must be between 1 and 1000");
+        }
+        if !(1..=96).contains(&self.level.view_distance) {
+            bail!("level.view_distance must be between 1 and 96");
+        }
+        if self.rcon.enabled && self.rcon.password.is_empty() {
+            bail!("rcon.password must be set to enable rcon");
+        }
+        if self.rcon.commands_per_second == 0 || self.rcon.max_auth_failures == 0 {
+            bail!("rcon.commands_per_second and rcon.max_auth_failures must be at least 1");
+        }
+        let flood = &self.flood;
+        if flood.connections_per_minute == 0
+            || flood.max_pending_logins == 0
+            || flood.max_relays == 0
+            || flood.packets_per_second == 0
+            || flood.bytes_per_second == 0
+        {
+            bail!("flood limits must be at least 1");
+        }
+        Ok(())
+    }
+
+    pub fn log_level(&self) -> Result<LevelFilter> {
+        self.log_level
+            .parse()
+            .with_context(|| format!("Invalid log_level {:?}", self.log_level))
+    }
+}
+
+/// Type of a setting as given in an environment variable.
+#[derive(Debug, Clone, Copy)]
+enum EnvType {
+    String,
+    Integer,
+    Float,
+    Boolean,
+    /// Comma separated.
+    List(&'static EnvType),
+}
+
+/// Settings whose type the serialized defaults don't show: options that are unset and lists
+/// that are empty.
+const DECLARED_ENV_TYPES: &[(&str, EnvType)] = &[
+    ("level.seed", EnvType::Integer),
+    ("level.flat_world_layers", EnvType::String),
+    ("query.bind", EnvType::List(&EnvType::String)),
+];
+
+/// Every setting that can be overridden, by its path in the file, e.g. `players.max`.
+fn env_types() -> Result<BTreeMap<String, EnvType>> {
+    fn walk(table: &Table, path: &str, types: &mut BTreeMap<String, EnvType>) {
+        for (key, value) in table {
+            let path = match path {
+                "" => key.clone(),
+                _ => format!("{path}.{key}"),
+            };
+            let (value, list) = match value {
+                Value::Table(inner) => {
+                    walk(inner, &path, types);
+                    continue;
+                }
+                Value::Array(items) => match items.first() {
+                    Some(item) => (item, true),
+                    None => continue,
+                },
+                value => (value, false),
+            };
+            let ty = match value {
+                Value::String(_) => &EnvType::String,
+                Value::Integer(_) => &EnvType::Integer,
+                Value::Float(_) => &EnvType::Float,
+                Value::Boolean(_) => &EnvType::Boolean,
+                Value::Array(_) | Value::Datetime(_) | Value::Table(_) => continue,
+            };
+            let ty = if list { EnvType::List(ty) } else { *ty };
+            types.insert(path, ty);
+        }
+    }
+    let mut types = BTreeMap::new();
+    walk(&Table::try_from(ServerConfig::default())?, "", &mut types);
+    types.extend(
+        DECLARED_ENV_TYPES
+            .iter()
+            .map(|(path, ty)| (path.to_string(), *ty)),
+    );
+    Ok(types)
+}
+
+/// Applies the variables of `env`, each named after the path of a setting in upper case,
+/// e.g. `players.max` is `HOB_PLAYERS_MAX`. Variables that name no setting are refused, as
+/// unknown keys in the file are.
+fn apply_env(table: &mut Table, prefix: &str, env: &[(String, String)]) -> Result<()> {
+    let types = env_types()?;
+    for (name, text) in env {
+        let Some((path, ty)) = types.iter().find(|(path, _)| {
+            name.strip_prefix(prefix) == Some(&path.replace('.', "_").to_uppercase())
+        }) else {
+            bail!("Unknown setting {name}");
+        };
+        let value = parse_env(*ty, text).with_context(|| format!("Invalid {name}={text:?}"))?;
+        let (sections, key) = match path.rsplit_once('.') {
+            Some((sections, key)) => (sections.split('.').collect(), key),
+            None => (Vec::new(), path.as_str()),
+        };
+        let mut section = &mut *table;
+        for name in sections {
+            let inner = section
+                .entry(name)
+                .or_insert_with(|| Value::Table(Table::new()));
+            let Value::Table(inner) = inner else {
+                bail!("{name} is not a section");
+            };
+            section = inner;
+        }
+        section.insert(key.to_owned(), value);
+    }
+    Ok(())
+}
+
+fn parse_env(ty: EnvType, text: &str) -> Result<Value> {
+    Ok(match ty {
+        EnvType::String => Value::String(text.to_owned()),
+        EnvType::Integer => Value::Integer(text.trim().parse()?),
+        EnvType::Float => Value::Float(text.trim().parse()?),
+        EnvType::Boolean => Value::Boolean(text.trim().parse()?),
+        EnvType::List(_) if text.trim().is_empty() => Value::Array(Vec::new()),
+        EnvType::List(item) => Value::Array(
+            text.split(',')
+                .map(|v| parse_env(*item, v.trim()))
+                .collect::<Result<_>>()?,
+        ),
+    })
+}
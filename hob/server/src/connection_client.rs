@@ -1,61 +1,120 @@
-use std::{net::SocketAddr, sync::Arc};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 
 use anyhow::Result;
 use hob_protocol::{decode::Decoder, encode::Encoder, packet::PacketKind};
 use log::debug;
 use proto_bytes::BytesMut;
-use rust_raknet::RaknetSocket;
 use tokio::{
     runtime::Runtime,
-    sync::mpsc::{self, Receiver, Sender},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        oneshot,
+    },
 };
 
 use crate::{
+    access::AccessControl,
+    config::NetworkConfig,
+    flood::{PacketBudget, PendingLogin},
+    frontend::RelayGuard,
     initial_handler::{login_process, LoginResult},
-    into_anyhow,
+    outbound::{self, OutboundReceiver, OutboundSender},
     player_registry::PlayerRegistry,
+    slots::{PlayerSlot, PlayerSlots},
+    transport::{Closed, Transport},
 };
 
+/// How long a closing connection may take to deliver its last packets.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Why the reader or writer of a logged-in connection stopped.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisconnectReason {
+    /// The client closed the connection.
+    ClientClosed,
+    /// The game dropped its end of the packet channels, e.g. after a kick.
+    ServerClosed,
+    /// Reading, decoding or sending failed.
+    Error(String),
+    /// The client went over its packet or byte budget.
+    Flooding(String),
+}
+
 pub struct ConnectionClient {
     pub reader: Reader,
     pub writer: Writer,
     pub address: SocketAddr,
+    pub network: Arc<NetworkConfig>,
     pub packet_from_client: Receiver<PacketKind>,
-    pub packet_to_client: Sender<PacketKind>,
+    pub packet_to_client: OutboundSender,
     pub player_registry: Sender<PlayerRegistry>,
+    pub access: Arc<AccessControl>,
+    pub slots: Arc<PlayerSlots>,
+    /// Taken during login and held until the connection closes.
+    pub slot: Option<PlayerSlot>,
+    /// The frontend relay the client is connected through, released along with `slot`.
+    pub relay: Option<RelayGuard>,
+    /// Held until the login finishes, either way.
+    pub pending_login: Option<PendingLogin>,
     pub runtime: Arc<Runtime>,
 }
 impl ConnectionClient {
+    /// `address` is the client's own, as `socket` may be connected through the frontend.
     pub fn new(
-        socket: RaknetSocket,
+        socket: Arc<dyn Transport>,
+        address: SocketAddr,
+        network: Arc<NetworkConfig>,
         player_registry: Sender<PlayerRegistry>,
+        access: Arc<AccessControl>,
+        slots: Arc<PlayerSlots>,
         runtime: Arc<Runtime>,
     ) -> Self {
-        let socket = Arc::new(socket);
-        let (packet_to_client_tx, packet_to_client_rx) = mpsc::channel(32);
-        let (packet_from_client_tx, packet_from_client_rx) = mpsc::channel(32);
+        let slow_timeout = Duration::from_secs(network.slow_client_secs);
+        let (packet_to_client_tx, packet_to_client_rx) =
+            outbound::channel(network.outbound_bytes, slow_timeout);
+        let (packet_from_client_tx, packet_from_client_rx) = mpsc::channel(network.packet_queue);
         let reader = Reader::new(socket.clone(), packet_from_client_tx);
         let writer = Writer::new(socket.clone(), packet_to_client_rx);
 
         Self {
             reader,
             writer,
-            address: socket.peer_addr().unwrap(),
+            address,
+            network,
             packet_from_client: packet_from_client_rx,
             packet_to_client: packet_to_client_tx,
             player_registry,
+            access,
+            slots,
+            slot: None,
+            relay: None,
+            pending_login: None,
             runtime,
         }
     }
+    /// Counts the connection as `login` until it has logged in, and holds the client to
+    /// `budget` from the first packet on.
+    pub fn limit(mut self, login: PendingLogin, budget: PacketBudget) -> Self {
+        self.pending_login = Some(login);
+        self.reader.budget = Some(budget);
+        self
+    }
+    pub fn relayed(mut self, relay: Option<RelayGuard>) -> Self {
+        self.relay = relay;
+        self
+    }
     pub fn start(mut self) {
         let runtime_ref = Arc::clone(&self.runtime);
         runtime_ref.spawn(async move {
             let result = login_process(&mut self).await;
-            if result.is_err() {
-                debug!("login failed: {}, {:?}", self.address, result);
-                return;
+            self.pending_login = None;
+            match result {
+                Ok(result) => self.proceed(result).await,
+                Err(e) => {
+                    debug!("login failed: {}, {:?}", self.address, e);
+                    self.writer.close().await;
+                }
             }
-            self.proceed(result.unwrap()).await;
         });
     }
 
@@ -67,39 +126,79 @@ impl ConnectionClient {
             packet_from_client,
             packet_to_client,
             player_registry,
+            slot,
+            relay,
             runtime,
+            ..
         } = self;
 
         match result {
-            LoginResult::Success(skin, userdata) => {
+            LoginResult::Success(skin, userdata, pending) => {
+                // Packets that came in the same batch as the handshake belong to the game.
+                // Nothing reads the queue yet, so any beyond its capacity are dropped.
+                for packet in pending {
+                    let _ = reader.packet_from_client.try_send(packet);
+                }
+                let (disconnect_tx, disconnect_rx) = oneshot::channel();
                 let player = PlayerRegistry {
                     skin,
                     user: userdata,
                     address,
                     packet_from_client,
                     packet_to_client,
+                    disconnect: disconnect_rx,
                 };
-                player_registry.send(player).await.unwrap();
-                Self::split(reader, writer, runtime);
+                if player_registry.send(player).await.is_err() {
+                    debug!("server is not accepting players: {}", address);
+                    return;
+                }
+                Self::split(reader, writer, runtime, slot, relay, disconnect_tx);
             }
             LoginResult::Failed(e) => {
-                debug!("login failed: {}, {:?}", self.address, e);
+                debug!("login failed: {}, {:?}", address, e);
+                // The client was just sent why; dropping the socket could lose that.
+                writer.close().await;
             }
         }
     }
 
-    pub fn split(reader: Reader, writer: Writer, runtime: Arc<Runtime>) {
-        let reader = runtime.spawn(async move { reader.run().await });
-        let writer = runtime.spawn(async move { writer.run().await });
+    /// Runs the reader and writer until either stops, then closes the socket, frees the
+    /// player's slot and relay and reports the reason through `disconnect`.
+    ///
+    /// A reader stopping because the game let go of the player, as after a kick, leaves the
+    /// writer up to [`FLUSH_TIMEOUT`] to deliver what the game queued last, e.g. the
+    /// disconnect packet.
+    pub fn split(
+        reader: Reader,
+        writer: Writer,
+        runtime: Arc<Runtime>,
+        slot: Option<PlayerSlot>,
+        relay: Option<RelayGuard>,
+        disconnect: oneshot::Sender<DisconnectReason>,
+    ) {
+        let socket = Arc::clone(&reader.socket);
+        let mut reader = runtime.spawn(async move { reader.run().await });
+        let mut writer = runtime.spawn(async move { writer.run().await });
         runtime.spawn(async move {
-            tokio::select! {
-                e = reader => {
+            let finished = tokio::select! {
+                e = &mut reader => {
                     debug!("reader finished: {:?}", e);
+                    if matches!(e, Ok(DisconnectReason::ServerClosed)) {
+                        let _ = tokio::time::timeout(FLUSH_TIMEOUT, &mut writer).await;
+                    }
+                    writer.abort();
+                    e
                 },
-                e = writer => {
-                    debug!("writer finished {:?}", e);
+                e = &mut writer => {
+                    reader.abort();
+                    debug!("writer finished: {:?}", e);
+                    e
                 },
-            }
+            };
+            let reason = finished.unwrap_or_else(|e| DisconnectReason::Error(e.to_string()));
+            let _ = socket.close().await;
+            drop((slot, relay));
+            let _ = disconnect.send(reason);
         });
     }
     pub async fn read(&mut self) -> Result<Vec<PacketKind>> {
@@ -119,60 +218,112 @@ impl ConnectionClient {
 }
 
 pub struct Reader {
-    socket: Arc<RaknetSocket>,
+    socket: Arc<dyn Transport>,
     decoder: Decoder,
     packet_from_client: Sender<PacketKind>,
+    budget: Option<PacketBudget>,
 }
 impl Reader {
-    pub fn new(socket: Arc<RaknetSocket>, packet_from_client: Sender<PacketKind>) -> Self {
+    pub fn new(socket: Arc<dyn Transport>, packet_from_client: Sender<PacketKind>) -> Self {
         Self {
             socket,
             decoder: Decoder::default(),
             packet_from_client,
+            budget: None,
         }
     }
 
-    pub async fn run(mut self) -> Result<()> {
+    pub async fn run(mut self) -> DisconnectReason {
         loop {
-            let packets = self.read().await?;
+            let buffer = match self.socket.recv().await {
+                Ok(v) => v,
+                Err(e) if e.is::<Closed>() => return DisconnectReason::ClientClosed,
+                Err(e) => return DisconnectReason::Error(format!("{e:#}")),
+            };
+            if let Err(e) = self.take_bytes(buffer.len()) {
+                return DisconnectReason::Flooding(e.to_string());
+            }
+            let packets = match self.decoder.decode(&mut BytesMut::from(&buffer[..])) {
+                Ok(v) => v,
+                Err(e) => return DisconnectReason::Error(e.to_string()),
+            };
+            if let Err(e) = self.take_packets(packets.len()) {
+                return DisconnectReason::Flooding(e.to_string());
+            }
             for packet in packets {
-                self.packet_from_client.send(packet).await?;
+                if self.packet_from_client.send(packet).await.is_err() {
+                    return DisconnectReason::ServerClosed;
+                }
             }
         }
     }
 
     pub async fn read(&mut self) -> Result<Vec<PacketKind>> {
-        let buffer = self.socket.recv().await.map_err(into_anyhow)?;
-        self.decoder.decode(&mut BytesMut::from(&buffer[..]))
+        let buffer = self.socket.recv().await?;
+        self.take_bytes(buffer.len())?;
+        let packets = self.decoder.decode(&mut BytesMut::from(&buffer[..]))?;
+        self.take_packets(packets.len())?;
+        Ok(packets)
+    }
+
+    fn take_bytes(&mut self, bytes: usize) -> Result<()> {
+        match self.budget.as_mut() {
+            Some(budget) => budget.take_bytes(bytes),
+            None => Ok(()),
+        }
+    }
+    fn take_packets(&mut self, packets: usize) -> Result<()> {
+        match self.budget.as_mut() {
+            Some(budget) => budget.take_packets(packets),
+            None => Ok(()),
+        }
     }
 }
 
 pub struct Writer {
-    socket: Arc<RaknetSocket>,
+    socket: Arc<dyn Transport>,
     encoder: Encoder,
-    packet_to_client: Receiver<PacketKind>,
+    packet_to_client: OutboundReceiver,
 }
 impl Writer {
-    pub fn new(socket: Arc<RaknetSocket>, packet_to_client: Receiver<PacketKind>) -> Self {
+    pub fn new(socket: Arc<dyn Transport>, packet_to_client: OutboundReceiver) -> Self {
         Self {
             socket,
             encoder: Encoder::default(),
             packet_to_client,
         }
     }
-    pub async fn run(mut self) -> Result<()> {
-        loop {
-            if let Some(v) = self.packet_to_client.recv().await {
-                self.write(v).await?;
+    /// Sends queued packets until the game drops its sender, then flushes what is left.
+    ///
+    /// Transports queue whatever they are given, so each batch is flushed before the next
+    /// is taken: packets for a slow client then back up in the outbound queue, where the game
+    /// sees them.
+    pub async fn run(mut self) -> DisconnectReason {
+        while let Some(packets) = self.packet_to_client.recv_all().await {
+            for packet in packets {
+                let buffer = self.encoder.encode_raw(&packet);
+                if let Err(e) = self.send(&buffer).await {
+                    return DisconnectReason::Error(e.to_string());
+                }
+            }
+            if let Err(e) = self.socket.flush().await {
+                return DisconnectReason::Error(format!("{e:#}"));
             }
         }
+        let _ = tokio::time::timeout(FLUSH_TIMEOUT, self.socket.flush()).await;
+        DisconnectReason::ServerClosed
     }
     pub async fn write(&mut self, packet: PacketKind) -> Result<()> {
         let buffer = self.encoder.encode(packet);
-        self.socket
-            .send(&buffer, rust_raknet::Reliability::ReliableOrdered)
-            .await
-            .map_err(into_anyhow)?;
-        Ok(())
+        self.send(&buffer).await
+    }
+    /// Gives the client up to [`FLUSH_TIMEOUT`] to receive what was written, e.g. why its
+    /// login failed, then closes the socket.
+    pub async fn close(&self) {
+        let _ = tokio::time::timeout(FLUSH_TIMEOUT, self.socket.flush()).await;
+        let _ = self.socket.close().await;
+    }
+    async fn send(&self, buffer: &[u8]) -> Result<()> {
+        self.socket.send(buffer).await
     }
 }
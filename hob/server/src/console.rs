@@ -0,0 +1,207 @@
+use std::{
+    io::Write,
+    sync::{Arc, Mutex, RwLock},
+};
+
+use log::{debug, warn};
+use rustyline::{
+    completion::{Completer, Pair},
+    error::ReadlineError,
+    highlight::Highlighter,
+    hint::Hinter,
+    history::DefaultHistory,
+    validate::Validator,
+    Context, Editor, ExternalPrinter, Helper,
+};
+use tokio::{
+    runtime::Runtime,
+    sync::mpsc::{self, Receiver, Sender},
+};
+
+const PROMPT: &str = "> ";
+/// Lines typed but not yet picked up by the game.
+const LINE_QUEUE: usize = 16;
+const HISTORY_SIZE: usize = 500;
+
+/// Prints above the input line while the console is reading, so output doesn't garble it.
+static PRINTER: Mutex<Option<Box<dyn ExternalPrinter + Send>>> = Mutex::new(None);
+
+/// Writes `text` to stdout, around the line being edited if there is one.
+pub fn print(text: String) {
+    if let Some(printer) = PRINTER.lock().unwrap().as_mut() {
+        if printer.print(text.clone()).is_ok() {
+            return;
+        }
+    }
+    let mut stdout = std::io::stdout().lock();
+    let _ = stdout.write_all(text.as_bytes());
+    let _ = stdout.flush();
+}
+
+/// Words tab completion offers, kept up to date by the game.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompletionSet {
+    /// Command names and aliases.
+    pub commands: Vec<String>,
+    pub players: Vec<String>,
+}
+impl CompletionSet {
+    /// Completes the word before `pos` in `line`: a command for the first word, a player name
+    /// for the rest. Returns where the word starts and the candidates, sorted.
+    pub fn complete(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let (start, words) = match line[..start].trim().is_empty() {
+            true if line.starts_with('/') => (1, &self.commands),
+            true => (start, &self.commands),
+            false => (start, &self.players),
+        };
+        let prefix = line[start..].to_lowercase();
+        let mut candidates: Vec<_> = words
+            .iter()
+            .filter(|word| word.to_lowercase().starts_with(&prefix))
+            .cloned()
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+        (start, candidates)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Completions(Arc<RwLock<CompletionSet>>);
+impl Completions {
+    pub fn get(&self) -> CompletionSet {
+        self.0.read().unwrap().clone()
+    }
+    pub fn complete(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        self.0.read().unwrap().complete(line, pos)
+    }
+    /// Replaces the set, unless it is unchanged.
+    pub fn update(&self, set: CompletionSet) {
+        if *self.0.read().unwrap() != set {
+            *self.0.write().unwrap() = set;
+        }
+    }
+}
+
+struct ConsoleHelper(Completions);
+impl Completer for ConsoleHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let (start, words) = self.0.complete(line, pos);
+        let candidates = words
+            .into_iter()
+            .map(|word| Pair {
+                replacement: format!("{word} "),
+                display: word,
+            })
+            .collect();
+        Ok((start, candidates))
+    }
+}
+impl Hinter for ConsoleHelper {
+    type Hint = String;
+}
+impl Highlighter for ConsoleHelper {}
+impl Validator for ConsoleHelper {}
+impl Helper for ConsoleHelper {}
+
+/// Lines typed into the console, for the game to run as commands.
+pub struct ConsoleInput {
+    pub lines: Receiver<String>,
+    pub completions: Completions,
+}
+impl ConsoleInput {
+    /// An input fed by `lines` instead of stdin.
+    pub fn new(lines: Receiver<String>) -> Self {
+        ConsoleInput {
+            lines,
+            completions: Completions::default(),
+        }
+    }
+}
+
+/// Reads stdin with line editing, history and completion on a blocking thread of `runtime`.
+/// Ctrl-C sends `stop`; end of input closes the console but leaves the server running.
+pub fn start(runtime: &Runtime) -> ConsoleInput {
+    let (sender, lines) = mpsc::channel(LINE_QUEUE);
+    let input = ConsoleInput::new(lines);
+    let completions = input.completions.clone();
+    runtime.spawn_blocking(move || {
+        if let Err(e) = read_lines(sender, completions) {
+            warn!("Console closed: {e}");
+        }
+        PRINTER.lock().unwrap().take();
+    });
+    input
+}
+
+fn read_lines(sender: Sender<String>, completions: Completions) -> rustyline::Result<()> {
+    terminal::save();
+    let config = rustyline::Config::builder()
+        .max_history_size(HISTORY_SIZE)?
+        .auto_add_history(true)
+        .build();
+    let mut editor: Editor<ConsoleHelper, DefaultHistory> = Editor::with_config(config)?;
+    editor.set_helper(Some(ConsoleHelper(completions)));
+    // Fails when stdin isn't a terminal, in which case there is no input line to protect.
+    if let Ok(printer) = editor.create_external_printer() {
+        *PRINTER.lock().unwrap() = Some(Box::new(printer));
+    }
+    loop {
+        let line = match editor.readline(PROMPT) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => "stop".to_owned(),
+            Err(ReadlineError::Eof) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        debug!("Console: {line}");
+        if sender.blocking_send(line).is_err() {
+            return Ok(());
+        }
+    }
+}
+
+/// Puts the terminal back the way it was before the console started. The reader may still be
+/// waiting for input in raw mode when the server exits.
+pub fn restore_terminal() {
+    terminal::restore();
+}
+
+#[cfg(unix)]
+mod terminal {
+    use std::sync::Mutex;
+
+    static ORIGINAL: Mutex<Option<libc::termios>> = Mutex::new(None);
+
+    pub fn save() {
+        let mut termios = std::mem::MaybeUninit::uninit();
+        // SAFETY: tcgetattr only writes to `termios`, which is initialized when it succeeds.
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, termios.as_mut_ptr()) } == 0 {
+            *ORIGINAL.lock().unwrap() = Some(unsafe { termios.assume_init() });
+        }
+    }
+
+    pub fn restore() {
+        if let Some(termios) = ORIGINAL.lock().unwrap().as_ref() {
+            // SAFETY: `termios` came from tcgetattr on the same descriptor.
+            unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, termios) };
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod terminal {
+    pub fn save() {}
+    pub fn restore() {}
+}
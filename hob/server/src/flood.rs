@@ -0,0 +1,249 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Result};
+use log::warn;
+
+use crate::config::FloodConfig;
+
+/// Tracked addresses beyond which idle entries are forgotten.
+const PRUNE_AT: usize = 1024;
+
+/// Token bucket holding at most `burst` tokens, refilled by that many every `period`.
+#[derive(Debug)]
+pub struct Budget {
+    burst: f64,
+    per_second: f64,
+    tokens: f64,
+    last: Instant,
+}
+impl Budget {
+    pub fn new(burst: u32, period: Duration) -> Self {
+        Budget {
+            burst: burst as f64,
+            per_second: burst as f64 / period.as_secs_f64(),
+            tokens: burst as f64,
+            last: Instant::now(),
+        }
+    }
+    /// Takes `amount` tokens if there are that many.
+    pub fn take(&mut self, amount: u32) -> bool {
+        self.refill();
+        if self.tokens < amount as f64 {
+            return false;
+        }
+        self.tokens -= amount as f64;
+        true
+    }
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let refill = now.duration_since(self.last).as_secs_f64() * self.per_second;
+        self.tokens = (self.tokens + refill).min(self.burst);
+        self.last = now;
+    }
+    /// Whether it has been idle long enough to be full again.
+    fn is_full(&mut self) -> bool {
+        self.refill();
+        self.tokens >= self.burst
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Strikes {
+    count: u32,
+    since: Instant,
+}
+
+/// Limits shared by every listener: connection attempts per address, open relays, logins
+/// in progress, and temporary bans of addresses that keep going over them.
+#[derive(Debug)]
+pub struct FloodGuard {
+    config: FloodConfig,
+    attempts: Mutex<HashMap<IpAddr, Budget>>,
+    strikes: Mutex<HashMap<IpAddr, Strikes>>,
+    /// Banned addresses and when their ban ends.
+    banned: Mutex<HashMap<IpAddr, Instant>>,
+    logins: Arc<AtomicUsize>,
+    relays: Arc<AtomicUsize>,
+}
+impl Default for FloodGuard {
+    fn default() -> Self {
+        FloodGuard::new(FloodConfig::default())
+    }
+}
+impl FloodGuard {
+    pub fn new(config: FloodConfig) -> Self {
+        FloodGuard {
+            config,
+            attempts: Mutex::default(),
+            strikes: Mutex::default(),
+            banned: Mutex::default(),
+            logins: Arc::default(),
+            relays: Arc::default(),
+        }
+    }
+
+    pub fn is_banned(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        let mut banned = self.banned.lock().unwrap();
+        let now = Instant::now();
+        banned.retain(|_, until| *until > now);
+        banned.contains_key(&ip)
+    }
+
+    /// Counts a connection attempt from `ip`. Attempts from banned addresses or beyond
+    /// `connections_per_minute` are refused, the latter also counting as an offence.
+    pub fn allow_connection(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        if self.is_banned(ip) {
+            return false;
+        }
+        let allowed = {
+            let mut attempts = self.attempts.lock().unwrap();
+            if attempts.len() >= PRUNE_AT {
+                attempts.retain(|_, budget| !budget.is_full());
+            }
+            let per_minute = self.config.connections_per_minute;
+            attempts
+                .entry(ip)
+                .or_insert_with(|| Budget::new(per_minute, Duration::from_secs(60)))
+                .take(1)
+        };
+        if !allowed {
+            self.offend(ip, "connecting too often");
+        }
+        allowed
+    }
+
+    /// Counts a relay for a client at `ip` until the returned guard is dropped, unless
+    /// `max_relays` are open already. Opening one counts as a connection attempt.
+    pub fn try_open_relay(&self, ip: IpAddr) -> Option<OpenRelay> {
+        if self.open_relays() >= self.config.max_relays || !self.allow_connection(ip) {
+            return None;
+        }
+        count_up(&self.relays, self.config.max_relays)?;
+        Some(OpenRelay(Arc::clone(&self.relays)))
+    }
+    pub fn open_relays(&self) -> usize {
+        self.relays.load(Ordering::Relaxed)
+    }
+
+    /// Counts a login in progress until the returned guard is dropped, unless
+    /// `max_pending_logins` are already.
+    pub fn try_begin_login(&self) -> Option<PendingLogin> {
+        count_up(&self.logins, self.config.max_pending_logins)?;
+        Some(PendingLogin(Arc::clone(&self.logins)))
+    }
+    /// Logins verifying or waiting for the client right now.
+    pub fn pending_logins(&self) -> usize {
+        self.logins.load(Ordering::Relaxed)
+    }
+
+    /// The packet and byte budgets of a new connection from `ip`.
+    pub fn packet_budget(self: &Arc<Self>, ip: IpAddr) -> PacketBudget {
+        let second = Duration::from_secs(1);
+        PacketBudget {
+            ip: ip.to_canonical(),
+            packets: Budget::new(self.config.packets_per_second, second),
+            bytes: Budget::new(self.config.bytes_per_second, second),
+            guard: Arc::clone(self),
+        }
+    }
+
+    /// Records an offence by `ip`, banning it for `ban_secs` once it has `ban_after` of
+    /// them within that time.
+    pub fn offend(&self, ip: IpAddr, why: &str) {
+        let ip = ip.to_canonical();
+        let (after, length) = (
+            self.config.ban_after,
+            Duration::from_secs(self.config.ban_secs),
+        );
+        if after == 0 || length.is_zero() {
+            warn!("{ip} is flooding: {why}");
+            return;
+        }
+        let count = {
+            let mut strikes = self.strikes.lock().unwrap();
+            strikes.retain(|_, s| s.since.elapsed() < length);
+            let entry = strikes.entry(ip).or_insert(Strikes {
+                count: 0,
+                since: Instant::now(),
+            });
+            entry.count += 1;
+            entry.since = Instant::now();
+            entry.count
+        };
+        if count < after {
+            warn!("{ip} is flooding: {why}");
+            return;
+        }
+        self.strikes.lock().unwrap().remove(&ip);
+        self.banned
+            .lock()
+            .unwrap()
+            .insert(ip, Instant::now() + length);
+        warn!("Banned {ip} for {}s for flooding: {why}", length.as_secs());
+    }
+}
+
+/// Adds one to `counter` unless it is at `max`.
+fn count_up(counter: &AtomicUsize, max: usize) -> Option<()> {
+    counter
+        .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |n| {
+            (n < max).then_some(n + 1)
+        })
+        .ok()
+        .map(drop)
+}
+
+/// A relay to a listener, no longer counted once dropped.
+#[derive(Debug)]
+pub struct OpenRelay(Arc<AtomicUsize>);
+impl Drop for OpenRelay {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// A login in progress, no longer counted once dropped.
+#[derive(Debug)]
+pub struct PendingLogin(Arc<AtomicUsize>);
+impl Drop for PendingLogin {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// What one connection may send per second.
+#[derive(Debug)]
+pub struct PacketBudget {
+    ip: IpAddr,
+    packets: Budget,
+    bytes: Budget,
+    guard: Arc<FloodGuard>,
+}
+impl PacketBudget {
+    /// Takes a received batch of `bytes` out of the byte budget.
+    pub fn take_bytes(&mut self, bytes: usize) -> Result<()> {
+        if !self.bytes.take(bytes.try_into().unwrap_or(u32::MAX)) {
+            self.guard.offend(self.ip, "sending too many bytes");
+            bail!("Sent more than {} bytes per second", self.bytes.burst);
+        }
+        Ok(())
+    }
+    /// Takes the packets decoded from a batch out of the packet budget.
+    pub fn take_packets(&mut self, packets: usize) -> Result<()> {
+        if !self.packets.take(packets.try_into().unwrap_or(u32::MAX)) {
+            self.guard.offend(self.ip, "sending too many packets");
+            bail!("Sent more than {} packets per second", self.packets.burst);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,284 @@
+use std::{
+    collections::HashMap,
+    net::{Ipv4Addr, SocketAddr},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use proto_bytes::{BufMut, BytesMut};
+use tokio::{net::UdpSocket, sync::Notify};
+
+use crate::{
+    flood::FloodGuard,
+    query::{is_query, Query},
+};
+
+const UNCONNECTED_PING: u8 = 0x01;
+const UNCONNECTED_PING_OPEN_CONNECTIONS: u8 = 0x02;
+const UNCONNECTED_PONG: u8 = 0x1c;
+const OPEN_CONNECTION_REQUEST_1: u8 = 0x05;
+pub const MAGIC: [u8; 16] = [
+    0x00, 0xff, 0xff, 0x00, 0xfe, 0xfe, 0xfe, 0xfe, 0xfd, 0xfd, 0xfd, 0xfd, 0x12, 0x34, 0x56, 0x78,
+];
+/// How long a relay may go without traffic either way until RakNet accepts its session.
+/// RakNet itself gives up after 60s.
+const RELAY_IDLE: Duration = Duration::from_secs(75);
+
+/// Builds the current pong string on every ping.
+pub type PongFn = Arc<dyn Fn() -> String + Send + Sync>;
+
+/// Returns the timestamp of an unconnected ping.
+pub fn parse_ping(buf: &[u8]) -> Option<i64> {
+    match buf {
+        [UNCONNECTED_PING | UNCONNECTED_PING_OPEN_CONNECTIONS, rest @ ..] if rest.len() >= 24 => {
+            (rest[8..24] == MAGIC).then(|| i64::from_be_bytes(rest[..8].try_into().unwrap()))
+        }
+        _ => None,
+    }
+}
+
+/// Whether `buf` starts a RakNet connection, the only datagram a relay is opened for.
+pub fn is_open_connection_request(buf: &[u8]) -> bool {
+    matches!(buf, [OPEN_CONNECTION_REQUEST_1, rest @ ..] if rest.starts_with(&MAGIC))
+}
+
+pub fn encode_pong(time: i64, guid: u64, motd: &str) -> BytesMut {
+    let mut bytes = BytesMut::with_capacity(35 + motd.len());
+    bytes.put_u8(UNCONNECTED_PONG);
+    bytes.put_i64(time);
+    bytes.put_u64(guid);
+    bytes.put_slice(&MAGIC);
+    bytes.put_u16(motd.len() as u16);
+    bytes.put_slice(motd.as_bytes());
+    bytes
+}
+
+/// A client and the state of its relay.
+type RelayedClient = (SocketAddr, Arc<RelayState>);
+
+/// Maps the loopback addresses RakNet sees back to the clients behind them.
+#[derive(Debug, Clone, Default)]
+pub struct ClientAddrs(Arc<Mutex<HashMap<SocketAddr, RelayedClient>>>);
+impl ClientAddrs {
+    /// The client behind `relay`, or `relay` itself if it isn't one.
+    pub fn resolve(&self, relay: SocketAddr) -> SocketAddr {
+        self.0
+            .lock()
+            .unwrap()
+            .get(&relay)
+            .map_or(relay, |(client, _)| *client)
+    }
+    /// Like [`Self::resolve`], and hands the accepted session the relay the first time, so
+    /// that it stays open until the session lets go of it rather than until it goes idle.
+    pub fn claim(&self, relay: SocketAddr) -> (SocketAddr, Option<RelayGuard>) {
+        match self.0.lock().unwrap().get(&relay) {
+            Some((client, state)) if !state.claimed.swap(true, Ordering::Relaxed) => {
+                (*client, Some(RelayGuard(state.clone())))
+            }
+            Some((client, _)) => (*client, None),
+            None => (relay, None),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct RelayState {
+    claimed: AtomicBool,
+    closed: Notify,
+}
+
+/// Keeps the relay of an accepted session open; it closes once this is dropped.
+#[derive(Debug)]
+pub struct RelayGuard(Arc<RelayState>);
+impl Drop for RelayGuard {
+    fn drop(&mut self) {
+        self.0.closed.notify_one();
+    }
+}
+
+struct Relay {
+    socket: Arc<UdpSocket>,
+    /// Milliseconds since the frontend started.
+    last_seen: Arc<AtomicU64>,
+}
+
+/// Public UDP socket in front of a RakNet listener bound to loopback.
+///
+/// rust-raknet copies its pong when it starts listening, so `set_full_motd` can't change it
+/// afterwards. The frontend answers unconnected pings itself and relays everything else
+/// through one loopback socket per client, until the session releases its [`RelayGuard`].
+pub struct Frontend {
+    socket: Arc<UdpSocket>,
+    upstream: SocketAddr,
+    pong: PongFn,
+    guid: u64,
+    relays: Arc<Mutex<HashMap<SocketAddr, Relay>>>,
+    clients: ClientAddrs,
+    /// Once set, clients without a relay are ignored.
+    stopping: Arc<AtomicBool>,
+    /// Answers GameSpy4 queries arriving on the game port.
+    query: Option<Arc<Query>>,
+    /// Limits relays per address and in total; banned clients get none.
+    flood: Arc<FloodGuard>,
+    started: Instant,
+}
+impl Frontend {
+    pub async fn bind(
+        addr: SocketAddr,
+        upstream: SocketAddr,
+        guid: u64,
+        pong: PongFn,
+    ) -> Result<Self> {
+        let socket = UdpSocket::bind(addr)
+            .await
+            .with_context(|| format!("Failed to bind {addr}"))?;
+        Ok(Frontend {
+            socket: Arc::new(socket),
+            upstream,
+            pong,
+            guid,
+            relays: Arc::default(),
+            clients: ClientAddrs::default(),
+            stopping: Arc::default(),
+            query: None,
+            flood: Arc::default(),
+            started: Instant::now(),
+        })
+    }
+    /// Stops opening relays for new clients once `stopping` is set.
+    pub fn stop_on(mut self, stopping: Arc<AtomicBool>) -> Self {
+        self.stopping = stopping;
+        self
+    }
+    pub fn with_query(mut self, query: Option<Arc<Query>>) -> Self {
+        self.query = query;
+        self
+    }
+    pub fn with_flood(mut self, flood: Arc<FloodGuard>) -> Self {
+        self.flood = flood;
+        self
+    }
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.socket.local_addr()?)
+    }
+    pub fn clients(&self) -> ClientAddrs {
+        self.clients.clone()
+    }
+
+    pub async fn run(self) {
+        let mut buf = [0u8; 2048];
+        loop {
+            let (len, client) = match self.socket.recv_from(&mut buf).await {
+                Ok(v) => v,
+                // Windows reports ICMP port unreachable from earlier sends as errors.
+                Err(e) => {
+                    debug!("frontend recv failed: {e}");
+                    continue;
+                }
+            };
+            let datagram = &buf[..len];
+            if let Some(time) = parse_ping(datagram) {
+                let pong = encode_pong(time, self.guid, &(self.pong)());
+                let _ = self.socket.send_to(&pong, client).await;
+                continue;
+            }
+            if let Some(query) = self.query.as_ref().filter(|_| is_query(datagram)) {
+                let host = self.socket.local_addr().unwrap_or(client);
+                if let Some(reply) = query.handle(datagram, client, host) {
+                    let _ = self.socket.send_to(&reply, client).await;
+                }
+                continue;
+            }
+            let relay = match self.relay(client, datagram) {
+                Ok(Some(v)) => v,
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!("Failed to relay {client}: {e:#}");
+                    continue;
+                }
+            };
+            let _ = relay.send(datagram).await;
+        }
+    }
+
+    fn elapsed(started: Instant) -> u64 {
+        started.elapsed().as_millis() as u64
+    }
+
+    /// The relay of `client`. One is only opened for a connection request, and only as
+    /// long as the frontend isn't stopping and the flood guard allows it.
+    fn relay(&self, client: SocketAddr, datagram: &[u8]) -> Result<Option<Arc<UdpSocket>>> {
+        let now = Self::elapsed(self.started);
+        let mut relays = self.relays.lock().unwrap();
+        if let Some(relay) = relays.get(&client) {
+            relay.last_seen.store(now, Ordering::Relaxed);
+            return Ok(Some(relay.socket.clone()));
+        }
+        if !is_open_connection_request(datagram) || self.stopping.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        let Some(open) = self.flood.try_open_relay(client.ip()) else {
+            debug!("Refused a relay for {client}");
+            return Ok(None);
+        };
+        let socket = std::net::UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))?;
+        socket.connect(self.upstream)?;
+        socket.set_nonblocking(true)?;
+        let socket = Arc::new(UdpSocket::from_std(socket)?);
+        let local = socket.local_addr()?;
+        let last_seen = Arc::new(AtomicU64::new(now));
+        let state = Arc::new(RelayState::default());
+        relays.insert(
+            client,
+            Relay {
+                socket: socket.clone(),
+                last_seen: last_seen.clone(),
+            },
+        );
+        self.clients
+            .0
+            .lock()
+            .unwrap()
+            .insert(local, (client, state.clone()));
+
+        let front = self.socket.clone();
+        let (relays, clients, started) = (self.relays.clone(), self.clients.clone(), self.started);
+        let upstream = socket.clone();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 2048];
+            loop {
+                let received = tokio::select! {
+                    _ = state.closed.notified() => break,
+                    v = tokio::time::timeout(RELAY_IDLE, upstream.recv(&mut buf)) => v,
+                };
+                match received {
+                    Ok(Ok(len)) => {
+                        last_seen.store(Self::elapsed(started), Ordering::Relaxed);
+                        let _ = front.send_to(&buf[..len], client).await;
+                    }
+                    Ok(Err(e)) => {
+                        debug!("relay of {client} failed: {e}");
+                        break;
+                    }
+                    Err(_) if state.claimed.load(Ordering::Relaxed) => {}
+                    Err(_) => {
+                        let idle = Self::elapsed(started)
+                            .saturating_sub(last_seen.load(Ordering::Relaxed));
+                        if idle >= RELAY_IDLE.as_millis() as u64 {
+                            break;
+                        }
+                    }
+                }
+            }
+            relays.lock().unwrap().remove(&client);
+            clients.0.lock().unwrap().remove(&local);
+            drop(open);
+        });
+        Ok(Some(socket))
+    }
+}
@@ -1,93 +1,280 @@
+use std::{collections::VecDeque, fmt, time::Duration};
+
 use anyhow::{bail, Error, Result};
 use hob_protocol::packet::{
-    disconnect::DisconnectPacket,
+    disconnect::{DisconnectFailReason, DisconnectPacket},
     handshake::{shared_secret, ServerToClientHandshakePacket},
     login::{verify_login, verify_skin, ExtraUserdata, LoginPacket, SkinData},
     network_settings::{CompressionAlgorithmType, NetworkSettingsPacket},
     play_status::PlayStatusPacket,
+    request_network_setting::RequestNetworkSettingPacket,
+    resource_pack_info::ResourcePacksInfoPacket,
     PacketKind,
 };
+use log::debug;
+use tokio::time::{timeout_at, Instant};
 
 use crate::connection_client::ConnectionClient;
 
 #[derive(Debug)]
 pub enum LoginResult {
-    Success(Box<SkinData>, ExtraUserdata),
+    /// Logged in; the packets are ones the client sent after the handshake, for the game.
+    Success(Box<SkinData>, ExtraUserdata, Vec<PacketKind>),
     Failed(Error),
 }
 
-static PROTOCOL_VERSION: i32 = 649;
+/// Protocol version of the default config.
+pub const PROTOCOL_VERSION: i32 = 649;
 
-pub async fn login_process(connection: &mut ConnectionClient) -> Result<LoginResult> {
-    if let Err(e) = handle_request(connection).await {
-        return Ok(LoginResult::Failed(e));
+/// Steps of a login, in order. The connection task drives everything up to the handshake;
+/// the game drives resource packs and spawning once it has the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LoginState {
+    AwaitingNetworkSettings,
+    AwaitingLogin,
+    AwaitingHandshake,
+    ResourcePacks,
+    Spawning,
+}
+impl LoginState {
+    /// How long the client may take to finish this step.
+    pub fn timeout(self) -> Duration {
+        Duration::from_secs(match self {
+            LoginState::AwaitingNetworkSettings => 10,
+            LoginState::AwaitingLogin => 10,
+            LoginState::AwaitingHandshake => 10,
+            LoginState::ResourcePacks => 60,
+            LoginState::Spawning => 30,
+        })
+    }
+    /// Sent to clients that take longer than [`LoginState::timeout`].
+    pub fn timeout_reason(self) -> DisconnectFailReason {
+        match self {
+            LoginState::AwaitingNetworkSettings
+            | LoginState::AwaitingLogin
+            | LoginState::AwaitingHandshake => DisconnectFailReason::ConnNegotiationTimeout,
+            LoginState::ResourcePacks | LoginState::Spawning => {
+                DisconnectFailReason::LoadingStateTimeout
+            }
+        }
     }
+    /// The step `packet` answers, for packets that drive a login.
+    pub fn of(packet: &PacketKind) -> Option<LoginState> {
+        match packet {
+            PacketKind::RequestNetworkSetting(_) => Some(LoginState::AwaitingNetworkSettings),
+            PacketKind::Login(_) => Some(LoginState::AwaitingLogin),
+            PacketKind::ClientToServerHandshake(_) => Some(LoginState::AwaitingHandshake),
+            PacketKind::ResourcePackClientResponse(_) => Some(LoginState::ResourcePacks),
+            _ => None,
+        }
+    }
+}
 
-    match handle_login(connection).await {
-        Ok((skin, userdata)) => Ok(LoginResult::Success(skin, userdata)),
-        Err(e) => {
-            connection
-                .write(DisconnectPacket::from("disconnectionScreen.notAuthenticated").into())
-                .await?;
-            Ok(LoginResult::Failed(e))
+/// A login turned away, with what the client is told.
+#[derive(Debug)]
+pub struct LoginRejected(pub DisconnectPacket);
+impl LoginRejected {
+    pub fn server_full() -> Self {
+        LoginRejected(DisconnectPacket::new(
+            DisconnectFailReason::ServerFull,
+            "disconnectionScreen.serverFull",
+        ))
+    }
+    fn reason(reason: DisconnectFailReason) -> Self {
+        LoginRejected(reason.into())
+    }
+}
+impl fmt::Display for LoginRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let LoginRejected(packet) = self;
+        match packet.message.as_deref() {
+            Some(message) => write!(f, "login rejected: {message}"),
+            None => write!(f, "login rejected: {:?}", packet.reason),
         }
     }
 }
+impl std::error::Error for LoginRejected {}
 
-pub async fn handle_request(connection: &mut ConnectionClient) -> Result<()> {
-    let packets = connection.read().await?;
-    let PacketKind::RequestNetworkSetting(request) = &packets[0] else {
-        bail!("login_process packet missmatch,expected:RequestNetworkSetting")
-    };
-    match request.client_protocol {
-        n if n < PROTOCOL_VERSION => {
-            connection
-                .write(PlayStatusPacket::FailedClient.into())
-                .await?
+/// The connection side of a login: the current step and the packets read but not yet used.
+struct Login<'a> {
+    connection: &'a mut ConnectionClient,
+    state: LoginState,
+    pending: VecDeque<PacketKind>,
+}
+impl Login<'_> {
+    /// Waits for the packet answering the current step. Packets of earlier steps or that
+    /// don't drive the login are skipped; one of a later step means the client is out of
+    /// order.
+    async fn next(&mut self) -> Result<PacketKind> {
+        let deadline = Instant::now() + self.state.timeout();
+        loop {
+            while let Some(packet) = self.pending.pop_front() {
+                match LoginState::of(&packet) {
+                    Some(state) if state == self.state => return Ok(packet),
+                    Some(state) if state > self.state => {
+                        let reason = match packet {
+                            PacketKind::Login(_) => DisconnectFailReason::LoginPacketNoRequest,
+                            _ => DisconnectFailReason::UnexpectedPacket,
+                        };
+                        return Err(LoginRejected::reason(reason).into());
+                    }
+                    _ => debug!(
+                        "[{}] Skipped {packet} while {:?}",
+                        self.address(),
+                        self.state
+                    ),
+                }
+            }
+            let batch = match timeout_at(deadline, self.connection.read()).await {
+                Ok(batch) => batch.map_err(|e| {
+                    debug!("[{}] Unreadable batch: {e:#}", self.address());
+                    LoginRejected::reason(DisconnectFailReason::BadPacket)
+                })?,
+                Err(_) => return Err(LoginRejected::reason(self.state.timeout_reason()).into()),
+            };
+            self.pending.extend(batch);
         }
-        n if n > PROTOCOL_VERSION => {
-            connection
-                .write(PlayStatusPacket::FailedSpawn.into())
-                .await?
+    }
+
+    fn address(&self) -> std::net::SocketAddr {
+        self.connection.address
+    }
+
+    async fn run(&mut self) -> Result<(Box<SkinData>, ExtraUserdata)> {
+        let PacketKind::RequestNetworkSetting(request) = self.next().await? else {
+            bail!("unexpected login state");
+        };
+        network_settings(self.connection, &request).await?;
+
+        self.state = LoginState::AwaitingLogin;
+        let PacketKind::Login(login) = self.next().await? else {
+            bail!("unexpected login state");
+        };
+        let (skin, user_data) = handle_login(self.connection, login).await?;
+
+        self.state = LoginState::AwaitingHandshake;
+        self.next().await?;
+        let connection = &mut *self.connection;
+        connection
+            .write(PlayStatusPacket::LoginSuccess.into())
+            .await?;
+        connection
+            .write(ResourcePacksInfoPacket::default().into())
+            .await?;
+        self.state = LoginState::ResourcePacks;
+        Ok((skin, user_data))
+    }
+}
+
+pub async fn login_process(connection: &mut ConnectionClient) -> Result<LoginResult> {
+    let mut login = Login {
+        connection,
+        state: LoginState::AwaitingNetworkSettings,
+        pending: VecDeque::new(),
+    };
+    let e = match login.run().await {
+        Ok((skin, userdata)) => {
+            let pending = login.pending.into_iter().collect();
+            return Ok(LoginResult::Success(skin, userdata, pending));
         }
-        _ => {
-            let network_setting = NetworkSettingsPacket {
-                compression_threshold: 512,
-                compression_algorithm: CompressionAlgorithmType::Deflate,
-                client_throttle: false,
-                client_throttle_threshold: 0,
-                client_throttle_scalar: 0.0,
+        Err(e) => e,
+    };
+    let connection = login.connection;
+    let (packet, e) = match e.downcast::<LoginRejected>() {
+        Ok(rejected) => {
+            let e = Error::msg(rejected.to_string());
+            let LoginRejected(packet) = rejected;
+            let status = match packet.reason {
+                // Clients show their own screens for these.
+                DisconnectFailReason::ServerFull => Some(PlayStatusPacket::FailedServerFull),
+                DisconnectFailReason::OutdatedClient => Some(PlayStatusPacket::FailedClient),
+                DisconnectFailReason::OutdatedServer => Some(PlayStatusPacket::FailedSpawn),
+                _ => None,
             };
-            connection.write(network_setting.into()).await?;
-            connection.enable_compression();
-            return Ok(());
+            if let Some(status) = status {
+                connection.write(status.into()).await?;
+            }
+            (packet, e)
         }
+        Err(e) => (
+            DisconnectPacket::new(
+                DisconnectFailReason::NotAuthenticated,
+                "disconnectionScreen.notAuthenticated",
+            ),
+            e,
+        ),
+    };
+    connection.write(packet.into()).await?;
+    Ok(LoginResult::Failed(e))
+}
+
+async fn network_settings(
+    connection: &mut ConnectionClient,
+    request: &RequestNetworkSettingPacket,
+) -> Result<()> {
+    let protocol_version = connection.network.protocol_version;
+    if request.client_protocol < protocol_version {
+        return Err(LoginRejected::reason(DisconnectFailReason::OutdatedClient).into());
     }
-    bail!("protocol version missmatch");
+    if request.client_protocol > protocol_version {
+        return Err(LoginRejected::reason(DisconnectFailReason::OutdatedServer).into());
+    }
+    let network_setting = NetworkSettingsPacket {
+        compression_threshold: connection.network.compression_threshold,
+        compression_algorithm: CompressionAlgorithmType::Deflate,
+        client_throttle: false,
+        client_throttle_threshold: 0,
+        client_throttle_scalar: 0.0,
+    };
+    connection.write(network_setting.into()).await?;
+    connection.enable_compression();
+    Ok(())
 }
 
-pub async fn handle_login(
+async fn handle_login(
     connection: &mut ConnectionClient,
+    login: LoginPacket,
 ) -> Result<(Box<SkinData>, ExtraUserdata)> {
-    let packet = connection.read().await?;
-    if let Some(PacketKind::Login(login)) = packet.into_iter().next() {
-        let LoginProcess {
-            skin,
-            secret_key,
-            token,
-            user_data,
-        } = connection
-            .runtime
-            .spawn_blocking(|| LoginProcess::verify(login))
-            .await??;
-        connection
-            .write(ServerToClientHandshakePacket { token }.into())
-            .await?;
-        connection.enable_encryption(&secret_key);
-        return Ok((skin, user_data));
+    // Turn players away before the costly verification when even reserved slots are
+    // taken. Otherwise only a verified identity can tell whether they may use one.
+    let slots = connection.slots.clone();
+    let mut slot = slots.try_acquire(false);
+    if slot.is_none() && slots.is_full() {
+        return Err(LoginRejected::server_full().into());
+    }
+    let LoginProcess {
+        skin,
+        secret_key,
+        token,
+        user_data,
+    } = connection
+        .runtime
+        .spawn_blocking(|| LoginProcess::verify(login))
+        .await??;
+    if let Some(packet) = connection.access.check(
+        &user_data.xuid,
+        &user_data.display_name,
+        connection.address.ip(),
+    ) {
+        return Err(LoginRejected(packet).into());
     }
-    bail!("login_process packet missmatch,expected:Login")
+    if slot.is_none() {
+        slot = slots
+            .is_privileged(&user_data.xuid, &user_data.display_name)
+            .then(|| slots.try_acquire(true))
+            .flatten();
+        if slot.is_none() {
+            return Err(LoginRejected::server_full().into());
+        }
+    }
+    connection.slot = slot;
+    connection
+        .write(ServerToClientHandshakePacket { token }.into())
+        .await?;
+    connection.enable_encryption(&secret_key);
+    Ok((skin, user_data))
 }
+
 struct LoginProcess {
     skin: Box<SkinData>,
     secret_key: [u8; 32],
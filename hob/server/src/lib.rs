@@ -1,31 +1,108 @@
+pub mod access;
+pub mod config;
 pub mod connection_client;
+pub mod console;
+pub mod flood;
+pub mod frontend;
 pub mod initial_handler;
 pub mod listener;
 pub mod logging;
+pub mod motd;
+pub mod outbound;
 pub mod player_registry;
+pub mod query;
+pub mod rcon;
+pub mod slots;
+pub mod transport;
 
+use access::AccessControl;
 use anyhow::{anyhow, Result};
-use listener::Listener;
-use std::{fmt::Debug, sync::Arc};
+use config::ServerConfig;
+use flood::FloodGuard;
+use listener::{Listener, ListenerShared};
+use motd::Motd;
+use query::{Query, QueryStatus};
+use std::{
+    fmt::Debug,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+};
 use tokio::{
     runtime::Runtime,
     sync::mpsc::{self, Receiver},
 };
 
 use player_registry::PlayerRegistry;
+use slots::PlayerSlots;
 
 #[derive(Debug)]
 pub struct Server {
     pub player_registry: Receiver<PlayerRegistry>,
+    pub access: Arc<AccessControl>,
+    pub slots: Arc<PlayerSlots>,
+    /// Sent to the server list; changes show up on the next ping.
+    pub motd: Arc<RwLock<Motd>>,
+    /// Set once the server shuts down; listeners then turn new connections away.
+    pub stopping: Arc<AtomicBool>,
+    /// Players and plugins reported to queries.
+    pub query: Arc<RwLock<QueryStatus>>,
 }
 impl Server {
-    pub async fn create(runtime: Arc<Runtime>) -> Result<Self> {
-        let (player_registry_tx, player_registry_rx) = mpsc::channel(32);
-        Listener::start(Arc::clone(&runtime), player_registry_tx).await?;
+    pub async fn create(runtime: Arc<Runtime>, config: &ServerConfig) -> Result<Self> {
+        let (player_registry_tx, player_registry_rx) = mpsc::channel(config.network.player_queue);
+        let access = Arc::new(AccessControl::load(
+            &config.files.whitelist,
+            &config.files.bans,
+        )?);
+        let slots = Arc::new(PlayerSlots::new(
+            config.players.max,
+            config.players.reserved,
+        ));
+        let motd = Arc::new(RwLock::new(Motd {
+            protocol_version: config.network.protocol_version,
+            ..config.motd.clone()
+        }));
+        let stopping = Arc::new(AtomicBool::new(false));
+        let status = Arc::new(RwLock::new(QueryStatus::default()));
+        let query = config.query.enabled.then(|| {
+            let query = Query::new(Arc::clone(&motd), Arc::clone(&slots), Arc::clone(&status));
+            Arc::new(query)
+        });
+        let on_game_port = query.clone().filter(|_| config.query.bind.is_empty());
+        Listener::start(ListenerShared {
+            network: Arc::new(config.network.clone()),
+            player_registry: player_registry_tx,
+            access: Arc::clone(&access),
+            slots: Arc::clone(&slots),
+            motd: Arc::clone(&motd),
+            stopping: Arc::clone(&stopping),
+            query: on_game_port,
+            flood: Arc::new(FloodGuard::new(config.flood.clone())),
+            runtime: Arc::clone(&runtime),
+        })
+        .await?;
+        if let Some(query) = query.filter(|_| !config.query.bind.is_empty()) {
+            let host = SocketAddr::new(config.network.bind[0].ip(), motd.read().unwrap().port_v4);
+            for &addr in config.query.bind.iter() {
+                query::serve(addr, host, Arc::clone(&query), &runtime).await?;
+            }
+        }
         Ok(Server {
             player_registry: player_registry_rx,
+            access,
+            slots,
+            motd,
+            stopping,
+            query: status,
         })
     }
+    /// Stops accepting connections. Players already connected are left alone.
+    pub fn stop_accepting(&self) {
+        self.stopping.store(true, Ordering::Relaxed);
+    }
     pub fn accept_players(&mut self, max: usize) -> Vec<PlayerRegistry> {
         let mut players = Vec::with_capacity(max);
         for _ in 0..max {
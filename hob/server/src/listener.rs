@@ -1,30 +1,124 @@
-use std::sync::Arc;
+use std::{
+    future,
+    net::{Ipv4Addr, SocketAddr},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    thread,
+};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use log::{debug, info, warn};
 use rust_raknet::{RaknetListener, RaknetSocket};
-use tokio::{runtime::Runtime, sync::mpsc::Sender};
+use tokio::{
+    runtime::{Builder, Handle, Runtime},
+    sync::mpsc::Sender,
+};
 
-use crate::{connection_client::ConnectionClient, into_anyhow, player_registry::PlayerRegistry};
+use crate::{
+    access::AccessControl,
+    config::NetworkConfig,
+    connection_client::ConnectionClient,
+    flood::FloodGuard,
+    frontend::{ClientAddrs, Frontend},
+    into_anyhow,
+    motd::Motd,
+    player_registry::PlayerRegistry,
+    query::Query,
+    slots::PlayerSlots,
+};
+
+/// Handles every listener passes on to its connections.
+#[derive(Clone)]
+pub struct ListenerShared {
+    pub network: Arc<NetworkConfig>,
+    pub player_registry: Sender<PlayerRegistry>,
+    pub access: Arc<AccessControl>,
+    pub slots: Arc<PlayerSlots>,
+    pub motd: Arc<RwLock<Motd>>,
+    /// Set once the server shuts down.
+    pub stopping: Arc<AtomicBool>,
+    /// Answers queries on the game ports when set.
+    pub query: Option<Arc<Query>>,
+    pub flood: Arc<FloodGuard>,
+    pub runtime: Arc<Runtime>,
+}
 
 pub struct Listener {
     listener: RaknetListener,
-    player_registry: Sender<PlayerRegistry>,
-    runtime: Arc<Runtime>,
+    clients: ClientAddrs,
+    shared: ListenerShared,
 }
 impl Listener {
-    pub async fn start(
-        runtime: Arc<Runtime>,
-        player_registry: Sender<PlayerRegistry>,
-    ) -> Result<()> {
-        let mut listener = RaknetListener::bind(&"0.0.0.0:19132".parse()?)
-            .await
-            .map_err(into_anyhow)?;
+    /// Starts a listener on every address of `network.bind`, all feeding `player_registry`.
+    /// Addresses that can't be bound are skipped, as long as one can.
+    pub async fn start(shared: ListenerShared) -> Result<()> {
+        {
+            let mut motd = shared.motd.write().unwrap();
+            let port = |v4: bool| {
+                let addr = shared.network.bind.iter().find(|addr| addr.is_ipv4() == v4);
+                addr.map_or(0, |addr| addr.port())
+            };
+            if motd.port_v4 == 0 {
+                motd.port_v4 = port(true);
+            }
+            if motd.port_v6 == 0 {
+                motd.port_v6 = port(false);
+            }
+        }
+        let binder = RaknetBinder::start()?;
+        let mut bound = 0;
+        for &addr in shared.network.bind.iter() {
+            match Listener::bind(addr, &binder, shared.clone()).await {
+                Ok(()) => {
+                    info!("Listening on {addr}");
+                    bound += 1;
+                }
+                Err(e) => warn!("Not listening on {addr}: {e:#}"),
+            }
+        }
+        if bound == 0 {
+            bail!("None of the bind addresses could be bound");
+        }
+        Ok(())
+    }
+
+    async fn bind(addr: SocketAddr, binder: &RaknetBinder, shared: ListenerShared) -> Result<()> {
+        let mut listener = binder.bind().await?;
         listener.listen().await;
 
+        let raknet_motd = listener.get_motd().await;
+        let guid = {
+            // Every listener answers with the GUID of the first one.
+            let mut motd = shared.motd.write().unwrap();
+            if motd.guid == 0 {
+                motd.guid = raknet_guid(&raknet_motd);
+            }
+            motd.guid
+        };
+        let (motd, slots) = (Arc::clone(&shared.motd), Arc::clone(&shared.slots));
+        let frontend = Frontend::bind(
+            addr,
+            listener.local_addr().map_err(into_anyhow)?,
+            guid,
+            Arc::new(move || {
+                let motd = motd.read().unwrap();
+                motd.pong(slots.online(), slots.max())
+            }),
+        )
+        .await?
+        .stop_on(Arc::clone(&shared.stopping))
+        .with_query(shared.query.clone())
+        .with_flood(Arc::clone(&shared.flood));
+        let clients = frontend.clients();
+        shared.runtime.spawn(frontend.run());
+
+        let runtime = Arc::clone(&shared.runtime);
         let listener = Listener {
             listener,
-            player_registry,
-            runtime: Arc::clone(&runtime),
+            clients,
+            shared,
         };
         runtime.spawn(async move {
             listener.run().await;
@@ -39,8 +133,74 @@ impl Listener {
         }
     }
     async fn accept(&mut self, socket: RaknetSocket) {
-        let connection =
-            ConnectionClient::new(socket, self.player_registry.clone(), self.runtime.clone());
+        // Sessions that got through right before shutting down are closed straight away.
+        if self.shared.stopping.load(Ordering::Relaxed) {
+            let _ = socket.close().await;
+            return;
+        }
+        let Ok(peer) = socket.peer_addr() else {
+            return;
+        };
+        let shared = &self.shared;
+        // Dropped on the way out, or with the session's slot once it runs.
+        let (address, relay) = self.clients.claim(peer);
+        // Attempts were counted when the frontend opened the relay.
+        if shared.flood.is_banned(address.ip()) {
+            debug!("Refused connection from banned {address}");
+            let _ = socket.close().await;
+            return;
+        }
+        let Some(login) = shared.flood.try_begin_login() else {
+            debug!("Too many logins in progress, refused {address}");
+            let _ = socket.close().await;
+            return;
+        };
+        let connection = ConnectionClient::new(
+            Arc::new(socket),
+            address,
+            shared.network.clone(),
+            shared.player_registry.clone(),
+            shared.access.clone(),
+            shared.slots.clone(),
+            shared.runtime.clone(),
+        )
+        .limit(login, shared.flood.packet_budget(address.ip()))
+        .relayed(relay);
         connection.start();
     }
 }
+
+/// Binds the RakNet listeners that frontends relay to, on a runtime of its own.
+///
+/// `RaknetListener::bind` spawns a drop watcher that calls `notify_one` on a `Notify` and
+/// then waits on it, while `bind` waits on the same `Notify` for that notification. When
+/// another worker runs the watcher before `bind` is waiting, the watcher takes its own
+/// permit, closes the listener and `bind` never returns. On a single thread the watcher
+/// only runs once `bind` is waiting. The listeners' UDP sockets stay registered with this
+/// runtime, so its thread runs for as long as the process does.
+struct RaknetBinder(Handle);
+impl RaknetBinder {
+    fn start() -> Result<Self> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        let handle = runtime.handle().clone();
+        thread::Builder::new()
+            .name("raknet".into())
+            .spawn(move || runtime.block_on(future::pending::<()>()))?;
+        Ok(RaknetBinder(handle))
+    }
+    async fn bind(&self) -> Result<RaknetListener> {
+        let loopback: SocketAddr = (Ipv4Addr::LOCALHOST, 0).into();
+        self.0
+            .spawn(async move { RaknetListener::bind(&loopback).await })
+            .await?
+            .map_err(into_anyhow)
+    }
+}
+
+/// The GUID in the default pong of rust-raknet, `MCPE;name;protocol;version;0;max;guid;...`.
+fn raknet_guid(motd: &str) -> u64 {
+    motd.split(';')
+        .nth(6)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0)
+}
@@ -1,18 +1,44 @@
+use std::io::IsTerminal;
+
 use log4rs::{
-    append::console::ConsoleAppender,
+    append::Append,
     config::{Appender, Root},
-    encode::pattern::PatternEncoder,
+    encode::{
+        pattern::PatternEncoder,
+        writer::{ansi::AnsiWriter, simple::SimpleWriter},
+        Encode,
+    },
     Config,
 };
 
-use log::{debug, LevelFilter};
+use log::{debug, LevelFilter, Record};
+
+use crate::console;
+
+/// Writes to stdout through the console, so logs don't cut into a line being typed.
+#[derive(Debug)]
+struct ConsoleAppender {
+    encoder: PatternEncoder,
+    colored: bool,
+}
+impl Append for ConsoleAppender {
+    fn append(&self, record: &Record) -> anyhow::Result<()> {
+        let mut text = Vec::new();
+        match self.colored {
+            true => self.encoder.encode(&mut AnsiWriter(&mut text), record)?,
+            false => self.encoder.encode(&mut SimpleWriter(&mut text), record)?,
+        }
+        console::print(String::from_utf8_lossy(&text).into_owned());
+        Ok(())
+    }
+    fn flush(&self) {}
+}
 
 pub fn setup(level: LevelFilter) {
-    let stdio = ConsoleAppender::builder()
-        .encoder(Box::new(PatternEncoder::new(
-            "{([{d(%Y-%m-%d %H:%M:%S:%3f)} {h({l})}]):31} - {message}\n",
-        )))
-        .build();
+    let stdio = ConsoleAppender {
+        encoder: PatternEncoder::new("{([{d(%Y-%m-%d %H:%M:%S:%3f)} {h({l})}]):31} - {message}\n"),
+        colored: std::io::stdout().is_terminal(),
+    };
 
     let config = Config::builder()
         .appender(Appender::builder().build("stdio", Box::new(stdio)))
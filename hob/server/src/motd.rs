@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+use crate::initial_handler::PROTOCOL_VERSION;
+
+pub const GAME_VERSION: &str = "1.20.60";
+
+/// What the server list shows, sent in every unconnected pong.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Motd {
+    pub name: String,
+    /// Second line, shown as the world name.
+    pub sub_motd: String,
+    /// Follows `network.protocol_version` of the config.
+    #[serde(skip)]
+    pub protocol_version: i32,
+    pub game_version: String,
+    pub game_mode: String,
+    pub game_mode_id: u8,
+    /// `0` uses the GUID of the RakNet listener.
+    pub guid: u64,
+    /// `0` uses the port the listener is bound to.
+    pub port_v4: u16,
+    pub port_v6: u16,
+}
+impl Default for Motd {
+    fn default() -> Self {
+        Motd {
+            name: "Hob".to_owned(),
+            sub_motd: "Hob".to_owned(),
+            protocol_version: PROTOCOL_VERSION,
+            game_version: GAME_VERSION.to_owned(),
+            game_mode: "Survival".to_owned(),
+            game_mode_id: 1,
+            guid: 0,
+            port_v4: 0,
+            port_v6: 0,
+        }
+    }
+}
+impl Motd {
+    /// The pong string for `online` of `max` players.
+    pub fn pong(&self, online: usize, max: usize) -> String {
+        // Fields are separated by `;`, so it can't appear inside them.
+        let field = |v: &str| v.replace(';', " ");
+        format!(
+            "MCPE;{};{};{};{};{};{};{};{};{};{};{};",
+            field(&self.name),
+            self.protocol_version,
+            field(&self.game_version),
+            online,
+            max,
+            self.guid,
+            field(&self.sub_motd),
+            field(&self.game_mode),
+            self.game_mode_id,
+            self.port_v4,
+            self.port_v6,
+        )
+    }
+}
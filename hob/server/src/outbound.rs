@@ -0,0 +1,228 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use hob_protocol::packet::PacketKind;
+use proto_bytes::{Bytes, BytesMut};
+use tokio::sync::Notify;
+
+/// What gives way when a client falls behind. Packets still go out in the order they were
+/// queued; reordering them would let e.g. `PlayStatus` overtake the chunks it announces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Priority {
+    /// Always queued, whatever the backlog.
+    Critical,
+    /// Queued up to twice the budget; a client that needs more is too slow to keep.
+    Normal,
+    /// Skipped once the queue is half full, as a later one supersedes it.
+    Droppable,
+}
+impl Priority {
+    pub fn of(packet: &PacketKind) -> Priority {
+        match packet {
+            PacketKind::Disconnect(_)
+            | PacketKind::PlayStatus(_)
+            | PacketKind::NetworkSettings(_)
+            | PacketKind::ServerToClientHandshake(_)
+            | PacketKind::ResourcePacksInfo(_)
+            | PacketKind::ResourcePacksStack(_)
+            | PacketKind::StartGame(_) => Priority::Critical,
+            PacketKind::MovePlayer(_) => Priority::Droppable,
+            _ => Priority::Normal,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutboundError {
+    /// The connection is gone.
+    Closed,
+    /// The client is too far behind to take a packet it can't do without.
+    TooSlow,
+}
+impl fmt::Display for OutboundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutboundError::Closed => write!(f, "connection closed"),
+            OutboundError::TooSlow => write!(f, "client too slow"),
+        }
+    }
+}
+impl std::error::Error for OutboundError {}
+
+/// Depth and history of one outbound queue.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OutboundStats {
+    pub queued_packets: usize,
+    pub queued_bytes: usize,
+    /// Most bytes queued at once.
+    pub peak_bytes: usize,
+    pub sent_packets: u64,
+    pub dropped_packets: u64,
+}
+
+#[derive(Debug, Default)]
+struct Queue {
+    packets: VecDeque<Bytes>,
+    stats: OutboundStats,
+    /// When the queue went over budget, while it still is.
+    behind_since: Option<Instant>,
+    sender_closed: bool,
+    receiver_closed: bool,
+}
+
+#[derive(Debug)]
+struct Shared {
+    queue: Mutex<Queue>,
+    ready: Notify,
+    max_bytes: usize,
+    slow_timeout: Duration,
+}
+
+/// A queue of encoded packets to one client, holding about `max_bytes`. A client that stays
+/// over that for `slow_timeout` counts as too slow.
+pub fn channel(max_bytes: usize, slow_timeout: Duration) -> (OutboundSender, OutboundReceiver) {
+    let shared = Arc::new(Shared {
+        queue: Mutex::default(),
+        ready: Notify::new(),
+        max_bytes,
+        slow_timeout,
+    });
+    (
+        OutboundSender(Arc::clone(&shared)),
+        OutboundReceiver(shared),
+    )
+}
+
+/// The game's end of an outbound queue. Dropping it lets the writer flush and close.
+#[derive(Debug)]
+pub struct OutboundSender(Arc<Shared>);
+impl OutboundSender {
+    /// Encodes and queues `packet` as its [`Priority`] allows. Droppable packets that don't
+    /// fit are counted as dropped, not reported.
+    pub fn send(&self, packet: PacketKind) -> Result<(), OutboundError> {
+        let priority = Priority::of(&packet);
+        let mut body = BytesMut::new();
+        if let Err(e) = packet.encode(&mut body) {
+            log::error!("Failed to encode {packet}: {e:#}");
+            return Ok(());
+        }
+        let Shared {
+            queue, max_bytes, ..
+        } = &*self.0;
+        let mut queue = queue.lock().unwrap();
+        if queue.receiver_closed {
+            return Err(OutboundError::Closed);
+        }
+        let bytes = queue.stats.queued_bytes + body.len();
+        match priority {
+            Priority::Droppable if bytes > max_bytes / 2 => {
+                queue.stats.dropped_packets += 1;
+                return Ok(());
+            }
+            Priority::Normal if bytes > max_bytes * 2 => return Err(OutboundError::TooSlow),
+            _ => {}
+        }
+        queue.packets.push_back(body.freeze());
+        let stats = &mut queue.stats;
+        stats.queued_packets += 1;
+        stats.queued_bytes = bytes;
+        stats.peak_bytes = stats.peak_bytes.max(bytes);
+        if bytes > *max_bytes && queue.behind_since.is_none() {
+            queue.behind_since = Some(Instant::now());
+        }
+        drop(queue);
+        self.0.ready.notify_one();
+        Ok(())
+    }
+
+    /// Whether the queue has been over budget for longer than the client is given.
+    pub fn is_too_slow(&self) -> bool {
+        let queue = self.0.queue.lock().unwrap();
+        queue
+            .behind_since
+            .is_some_and(|since| since.elapsed() > self.0.slow_timeout)
+    }
+
+    /// Drops everything queued, e.g. so a disconnect goes out next.
+    pub fn clear(&self) {
+        let mut queue = self.0.queue.lock().unwrap();
+        let Queue {
+            packets,
+            stats,
+            behind_since,
+            ..
+        } = &mut *queue;
+        stats.dropped_packets += packets.len() as u64;
+        stats.queued_packets = 0;
+        stats.queued_bytes = 0;
+        packets.clear();
+        *behind_since = None;
+    }
+
+    pub fn stats(&self) -> OutboundStats {
+        self.0.queue.lock().unwrap().stats
+    }
+}
+impl Drop for OutboundSender {
+    fn drop(&mut self) {
+        self.0.queue.lock().unwrap().sender_closed = true;
+        self.0.ready.notify_one();
+    }
+}
+
+/// The writer's end of an outbound queue.
+#[derive(Debug)]
+pub struct OutboundReceiver(Arc<Shared>);
+impl OutboundReceiver {
+    /// Waits for packets and takes all of them. `None` once the sender is gone and every
+    /// packet has been taken.
+    pub async fn recv_all(&mut self) -> Option<Vec<Bytes>> {
+        loop {
+            if let Some(packets) = self.take(usize::MAX) {
+                return Some(packets);
+            }
+            if self.0.queue.lock().unwrap().sender_closed {
+                return None;
+            }
+            self.0.ready.notified().await;
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Option<Bytes> {
+        self.take(1)?.pop()
+    }
+
+    /// Takes the next packet and decodes it again, for tests and tools. Packets without a
+    /// decoder, as most clientbound ones are, are taken but come back as `None`.
+    pub fn try_recv_packet(&mut self) -> Option<PacketKind> {
+        let body = self.try_recv()?;
+        PacketKind::decode(&mut BytesMut::from(&body[..])).ok()
+    }
+
+    fn take(&mut self, max: usize) -> Option<Vec<Bytes>> {
+        let mut queue = self.0.queue.lock().unwrap();
+        if queue.packets.is_empty() {
+            return None;
+        }
+        let count = max.min(queue.packets.len());
+        let packets: Vec<_> = queue.packets.drain(..count).collect();
+        let taken: usize = packets.iter().map(|p| p.len()).sum();
+        let stats = &mut queue.stats;
+        stats.queued_packets -= count;
+        stats.queued_bytes -= taken;
+        stats.sent_packets += count as u64;
+        if stats.queued_bytes <= self.0.max_bytes {
+            queue.behind_since = None;
+        }
+        Some(packets)
+    }
+}
+impl Drop for OutboundReceiver {
+    fn drop(&mut self) {
+        self.0.queue.lock().unwrap().receiver_closed = true;
+    }
+}
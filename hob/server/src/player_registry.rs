@@ -4,7 +4,9 @@ use hob_protocol::packet::{
     login::{ExtraUserdata, SkinData},
     PacketKind,
 };
-use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::sync::{mpsc::Receiver, oneshot};
+
+use crate::{connection_client::DisconnectReason, outbound::OutboundSender};
 
 #[derive(Debug)]
 pub struct PlayerRegistry {
@@ -12,5 +14,7 @@ pub struct PlayerRegistry {
     pub user: ExtraUserdata,
     pub address: SocketAddr,
     pub packet_from_client: Receiver<PacketKind>,
-    pub packet_to_client: Sender<PacketKind>,
+    pub packet_to_client: OutboundSender,
+    /// Resolves once the connection has been closed.
+    pub disconnect: oneshot::Receiver<DisconnectReason>,
 }
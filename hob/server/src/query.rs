@@ -0,0 +1,205 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::BuildHasher,
+    net::SocketAddr,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use log::{debug, info};
+use proto_bytes::{BufMut, BytesMut};
+use tokio::{net::UdpSocket, runtime::Runtime};
+
+use crate::{motd::Motd, slots::PlayerSlots};
+
+/// Every query request starts with these.
+pub const QUERY_MAGIC: [u8; 2] = [0xfe, 0xfd];
+pub const HANDSHAKE: u8 = 0x09;
+pub const STAT: u8 = 0x00;
+/// A challenge token stays valid for one to two of these.
+pub const TOKEN_LIFETIME: Duration = Duration::from_secs(30);
+/// Precedes the key/value section of a full stat.
+const FULL_STAT_PADDING: &[u8] = b"splitnum\x00\x80\x00";
+/// Precedes the player names of a full stat.
+const PLAYERS_HEADER: &[u8] = b"\x01player_\x00\x00";
+const ENGINE: &str = concat!("Hob ", env!("CARGO_PKG_VERSION"));
+
+/// What the game reports through the query, kept up to date by it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryStatus {
+    /// Names of the players that have joined.
+    pub players: Vec<String>,
+    pub plugins: Vec<String>,
+}
+
+pub fn is_query(buf: &[u8]) -> bool {
+    buf.starts_with(&QUERY_MAGIC)
+}
+
+/// Keys challenge tokens to client addresses, changing the key every [`TOKEN_LIFETIME`].
+struct Tokens {
+    current: RandomState,
+    previous: RandomState,
+    rotated: Instant,
+}
+impl Tokens {
+    fn new() -> Self {
+        Tokens {
+            current: RandomState::new(),
+            previous: RandomState::new(),
+            rotated: Instant::now(),
+        }
+    }
+    fn rotate(&mut self) {
+        if self.rotated.elapsed() >= TOKEN_LIFETIME {
+            self.previous = std::mem::replace(&mut self.current, RandomState::new());
+            self.rotated = Instant::now();
+        }
+    }
+    fn token(key: &RandomState, client: SocketAddr) -> i32 {
+        key.hash_one(client) as i32
+    }
+    fn issue(&mut self, client: SocketAddr) -> i32 {
+        self.rotate();
+        Self::token(&self.current, client)
+    }
+    fn check(&mut self, client: SocketAddr, token: i32) -> bool {
+        self.rotate();
+        token == Self::token(&self.current, client) || token == Self::token(&self.previous, client)
+    }
+}
+
+/// Answers GameSpy4 queries: a handshake handing out a challenge token, then basic or full
+/// stats for requests carrying it.
+pub struct Query {
+    motd: Arc<RwLock<Motd>>,
+    slots: Arc<PlayerSlots>,
+    status: Arc<RwLock<QueryStatus>>,
+    tokens: Mutex<Tokens>,
+}
+impl Query {
+    pub fn new(
+        motd: Arc<RwLock<Motd>>,
+        slots: Arc<PlayerSlots>,
+        status: Arc<RwLock<QueryStatus>>,
+    ) -> Self {
+        Query {
+            motd,
+            slots,
+            status,
+            tokens: Mutex::new(Tokens::new()),
+        }
+    }
+
+    /// The reply to `request` from `client`, reporting `host` as the server address. Requests
+    /// that are malformed or carry a stale token get none.
+    pub fn handle(&self, request: &[u8], client: SocketAddr, host: SocketAddr) -> Option<BytesMut> {
+        let rest = request.strip_prefix(&QUERY_MAGIC)?;
+        let (&kind, rest) = rest.split_first()?;
+        let session = i32::from_be_bytes(rest.get(..4)?.try_into().unwrap());
+        let payload = &rest[4..];
+
+        let mut reply = BytesMut::new();
+        reply.put_u8(kind);
+        reply.put_i32(session);
+        match kind {
+            HANDSHAKE => {
+                let token = self.tokens.lock().unwrap().issue(client);
+                put_string(&mut reply, &token.to_string());
+            }
+            STAT => {
+                let token = i32::from_be_bytes(payload.get(..4)?.try_into().unwrap());
+                if !self.tokens.lock().unwrap().check(client, token) {
+                    return None;
+                }
+                // A full stat request pads the token with four more bytes.
+                match payload.len() >= 8 {
+                    true => self.full_stat(&mut reply, host),
+                    false => self.basic_stat(&mut reply, host),
+                }
+            }
+            _ => return None,
+        }
+        Some(reply)
+    }
+
+    fn basic_stat(&self, reply: &mut BytesMut, host: SocketAddr) {
+        let motd = self.motd.read().unwrap();
+        let status = self.status.read().unwrap();
+        put_string(reply, &motd.name);
+        put_string(reply, "SMP");
+        put_string(reply, &motd.sub_motd);
+        put_string(reply, &status.players.len().to_string());
+        put_string(reply, &self.slots.max().to_string());
+        reply.put_u16_le(host.port());
+        put_string(reply, &host.ip().to_string());
+    }
+
+    fn full_stat(&self, reply: &mut BytesMut, host: SocketAddr) {
+        let motd = self.motd.read().unwrap();
+        let status = self.status.read().unwrap();
+        let plugins = match status.plugins.is_empty() {
+            true => ENGINE.to_owned(),
+            false => format!("{ENGINE}: {}", status.plugins.join("; ")),
+        };
+        reply.put_slice(FULL_STAT_PADDING);
+        for (key, value) in [
+            ("hostname", motd.name.clone()),
+            ("gametype", "SMP".to_owned()),
+            ("game_id", "MINECRAFTPE".to_owned()),
+            ("version", motd.game_version.clone()),
+            ("server_engine", ENGINE.to_owned()),
+            ("plugins", plugins),
+            ("map", motd.sub_motd.clone()),
+            ("numplayers", status.players.len().to_string()),
+            ("maxplayers", self.slots.max().to_string()),
+            ("hostport", host.port().to_string()),
+            ("hostip", host.ip().to_string()),
+        ] {
+            put_string(reply, key);
+            put_string(reply, &value);
+        }
+        reply.put_u8(0);
+        reply.put_slice(PLAYERS_HEADER);
+        for name in status.players.iter() {
+            put_string(reply, name);
+        }
+        reply.put_u8(0);
+    }
+}
+
+fn put_string(buf: &mut BytesMut, value: &str) {
+    // Strings are null terminated, so they can't contain one.
+    buf.put_slice(value.replace('\0', "").as_bytes());
+    buf.put_u8(0);
+}
+
+/// Answers queries on a socket of their own at `addr`, reporting `host` as the server.
+pub async fn serve(
+    addr: SocketAddr,
+    host: SocketAddr,
+    query: Arc<Query>,
+    runtime: &Runtime,
+) -> Result<()> {
+    let socket = UdpSocket::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind query on {addr}"))?;
+    info!("Answering queries on {addr}");
+    runtime.spawn(async move {
+        let mut buf = [0u8; 64];
+        loop {
+            let (len, client) = match socket.recv_from(&mut buf).await {
+                Ok(v) => v,
+                Err(e) => {
+                    debug!("query recv failed: {e}");
+                    continue;
+                }
+            };
+            if let Some(reply) = query.handle(&buf[..len], client, host) {
+                let _ = socket.send_to(&reply, client).await;
+            }
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,317 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context, Result};
+use log::{info, warn};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    runtime::Runtime,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        oneshot,
+    },
+    time::{sleep, timeout},
+};
+
+use crate::{config::RconConfig, flood::Budget};
+
+/// Packet types of the Source RCON protocol. `EXEC_COMMAND` and `AUTH_RESPONSE` share a
+/// value; which one is meant depends on the direction.
+pub mod packet_type {
+    pub const RESPONSE_VALUE: i32 = 0;
+    pub const EXEC_COMMAND: i32 = 2;
+    pub const AUTH_RESPONSE: i32 = 2;
+    pub const AUTH: i32 = 3;
+}
+
+/// Largest packet a client may send, `size` field excluded.
+pub const MAX_PACKET_SIZE: usize = 4096;
+/// Longer command output is split over several responses.
+pub const MAX_RESPONSE_BODY: usize = MAX_PACKET_SIZE - 10;
+/// Time a connection has to authenticate.
+const AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time the game has to run a command before the client is told it timed out.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+/// Commands waiting for the game thread.
+const COMMAND_QUEUE: usize = 32;
+/// Pause after a failed accept; errors like running out of file descriptors don't pass
+/// at once.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(500);
+
+/// `size`, then `id`, `type` and a null terminated body, followed by an empty string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RconPacket {
+    pub id: i32,
+    pub kind: i32,
+    pub body: String,
+}
+impl RconPacket {
+    pub fn new(id: i32, kind: i32, body: impl Into<String>) -> Self {
+        RconPacket {
+            id,
+            kind,
+            body: body.into(),
+        }
+    }
+
+    pub async fn read(reader: &mut (impl AsyncRead + Unpin)) -> Result<Self> {
+        let size = reader.read_i32_le().await?;
+        if !(10..=MAX_PACKET_SIZE as i32).contains(&size) {
+            bail!("Invalid packet size {size}");
+        }
+        let mut buf = vec![0; size as usize];
+        reader.read_exact(&mut buf).await?;
+        let id = i32::from_le_bytes(buf[0..4].try_into().unwrap());
+        let kind = i32::from_le_bytes(buf[4..8].try_into().unwrap());
+        let body = &buf[8..];
+        let end = body
+            .iter()
+            .position(|&b| b == 0)
+            .context("Unterminated body")?;
+        Ok(RconPacket {
+            id,
+            kind,
+            body: String::from_utf8_lossy(&body[..end]).into_owned(),
+        })
+    }
+
+    pub async fn write(&self, writer: &mut (impl AsyncWrite + Unpin)) -> Result<()> {
+        let mut buf = Vec::with_capacity(self.body.len() + 14);
+        buf.extend_from_slice(&(self.body.len() as i32 + 10).to_le_bytes());
+        buf.extend_from_slice(&self.id.to_le_bytes());
+        buf.extend_from_slice(&self.kind.to_le_bytes());
+        buf.extend_from_slice(self.body.as_bytes());
+        buf.extend_from_slice(&[0, 0]);
+        writer.write_all(&buf).await?;
+        Ok(())
+    }
+}
+
+/// A command for the game thread to run; the output goes back through `reply`.
+pub struct RconCommand {
+    pub line: String,
+    pub reply: oneshot::Sender<String>,
+}
+
+/// Commands received over RCON, for the game to run.
+pub struct RconCommands(pub Receiver<RconCommand>);
+
+#[derive(Debug, Clone, Copy)]
+struct AuthFailures {
+    count: u32,
+    since: Instant,
+}
+
+struct Shared {
+    config: RconConfig,
+    commands: Sender<RconCommand>,
+    failures: Mutex<HashMap<IpAddr, AuthFailures>>,
+}
+impl Shared {
+    fn locked_out(&self, ip: IpAddr) -> bool {
+        let lockout = Duration::from_secs(self.config.lockout_secs);
+        let mut failures = self.failures.lock().unwrap();
+        failures.retain(|_, f| f.since.elapsed() < lockout);
+        failures
+            .get(&ip)
+            .is_some_and(|f| f.count >= self.config.max_auth_failures)
+    }
+    fn fail(&self, ip: IpAddr) {
+        let mut failures = self.failures.lock().unwrap();
+        let entry = failures.entry(ip).or_insert(AuthFailures {
+            count: 0,
+            since: Instant::now(),
+        });
+        entry.count += 1;
+        entry.since = Instant::now();
+    }
+    fn check_password(&self, password: &str) -> bool {
+        let (a, b) = (password.as_bytes(), self.config.password.as_bytes());
+        // Compares every byte, so the time taken doesn't tell how much of it was right.
+        a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+    }
+}
+
+/// Listens on `config.bind`. Commands arrive in the returned queue and must be answered
+/// from the game thread.
+pub async fn start(config: &RconConfig, runtime: &Runtime) -> Result<(RconCommands, SocketAddr)> {
+    let listener = TcpListener::bind(config.bind)
+        .await
+        .with_context(|| format!("Failed to bind rcon on {}", config.bind))?;
+    let addr = listener.local_addr()?;
+    info!("RCON listening on {addr}");
+    let (commands, queue) = mpsc::channel(COMMAND_QUEUE);
+    let shared = Arc::new(Shared {
+        config: config.clone(),
+        commands,
+        failures: Mutex::default(),
+    });
+    let handle = runtime.handle().clone();
+    runtime.spawn(async move {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(v) => v,
+                Err(e) => {
+                    warn!("RCON failed to accept a connection: {e}");
+                    sleep(ACCEPT_BACKOFF).await;
+                    continue;
+                }
+            };
+            let shared = Arc::clone(&shared);
+            handle.spawn(async move {
+                if let Err(e) = serve(stream, peer, shared).await {
+                    warn!("[RCON {peer}] {e:#}");
+                }
+            });
+        }
+    });
+    Ok((RconCommands(queue), addr))
+}
+
+async fn serve(mut stream: TcpStream, peer: SocketAddr, shared: Arc<Shared>) -> Result<()> {
+    if shared.locked_out(peer.ip()) {
+        return Ok(());
+    }
+    stream.set_nodelay(true)?;
+
+    let auth = timeout(AUTH_TIMEOUT, RconPacket::read(&mut stream))
+        .await
+        .context("Timed out waiting for auth")??;
+    if auth.kind != packet_type::AUTH {
+        bail!("Sent a command before authenticating");
+    }
+    if !shared.check_password(&auth.body) {
+        shared.fail(peer.ip());
+        RconPacket::new(-1, packet_type::AUTH_RESPONSE, "")
+            .write(&mut stream)
+            .await?;
+        bail!("Wrong password");
+    }
+    RconPacket::new(auth.id, packet_type::AUTH_RESPONSE, "")
+        .write(&mut stream)
+        .await?;
+    info!("[RCON {peer}] Authenticated");
+
+    let mut budget = Budget::new(shared.config.commands_per_second, Duration::from_secs(1));
+    loop {
+        let packet = match RconPacket::read(&mut stream).await {
+            Ok(packet) => packet,
+            Err(e) => match e.downcast_ref::<std::io::Error>() {
+                Some(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+                _ => return Err(e),
+            },
+        };
+        match packet.kind {
+            packet_type::EXEC_COMMAND => {
+                let output = match budget.take(1) {
+                    true => {
+                        info!("[RCON {peer}] {}", packet.body);
+                        run(&shared, packet.body).await
+                    }
+                    false => "Too many commands, slow down".to_owned(),
+                };
+                respond(&mut stream, packet.id, &output).await?;
+            }
+            // Clients send an empty response after a command and wait for it to be mirrored,
+            // so they know when a split response is complete.
+            packet_type::RESPONSE_VALUE => respond(&mut stream, packet.id, "").await?,
+            kind => bail!("Unexpected packet type {kind}"),
+        }
+    }
+}
+
+async fn run(shared: &Shared, line: String) -> String {
+    let (reply, output) = oneshot::channel();
+    if shared
+        .commands
+        .send(RconCommand { line, reply })
+        .await
+        .is_err()
+    {
+        return "The server is shutting down".to_owned();
+    }
+    match timeout(COMMAND_TIMEOUT, output).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(_)) => "The server is shutting down".to_owned(),
+        Err(_) => "The command timed out".to_owned(),
+    }
+}
+
+/// Sends `output` as one response, or several if it is too long for one.
+async fn respond(stream: &mut TcpStream, id: i32, output: &str) -> Result<()> {
+    let mut rest = output;
+    loop {
+        let mut end = rest.len().min(MAX_RESPONSE_BODY);
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (body, tail) = rest.split_at(end);
+        RconPacket::new(id, packet_type::RESPONSE_VALUE, body)
+            .write(stream)
+            .await?;
+        if tail.is_empty() {
+            return Ok(());
+        }
+        rest = tail;
+    }
+}
+
+/// A minimal RCON client, for tests and scripts.
+pub struct RconClient {
+    stream: TcpStream,
+    next_id: i32,
+}
+impl RconClient {
+    pub async fn connect(addr: SocketAddr) -> Result<Self> {
+        Ok(RconClient {
+            stream: TcpStream::connect(addr).await?,
+            next_id: 1,
+        })
+    }
+
+    fn id(&mut self) -> i32 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    /// Returns whether the server accepted `password`.
+    pub async fn authenticate(&mut self, password: &str) -> Result<bool> {
+        let id = self.id();
+        RconPacket::new(id, packet_type::AUTH, password)
+            .write(&mut self.stream)
+            .await?;
+        loop {
+            let packet = RconPacket::read(&mut self.stream).await?;
+            if packet.kind == packet_type::AUTH_RESPONSE {
+                return Ok(packet.id == id);
+            }
+        }
+    }
+
+    /// Runs `line` and returns its whole output, however many responses it took.
+    pub async fn command(&mut self, line: &str) -> Result<String> {
+        let (id, end) = (self.id(), self.id());
+        RconPacket::new(id, packet_type::EXEC_COMMAND, line)
+            .write(&mut self.stream)
+            .await?;
+        RconPacket::new(end, packet_type::RESPONSE_VALUE, "")
+            .write(&mut self.stream)
+            .await?;
+        let mut output = String::new();
+        loop {
+            let packet = RconPacket::read(&mut self.stream).await?;
+            if packet.id == end {
+                return Ok(output);
+            }
+            if packet.id != id {
+                bail!("Unexpected response id {}", packet.id);
+            }
+            output.push_str(&packet.body);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
+};
+
+pub const DEFAULT_MAX_PLAYERS: usize = 20;
+
+#[derive(Debug, Default)]
+struct Privileged {
+    xuids: HashSet<String>,
+    /// Lowercased names of players without a known XUID.
+    names: HashSet<String>,
+}
+
+/// Player slots shared by the login handlers. A slot is taken before the login is verified
+/// and held until the connection closes, so the count covers players still logging in.
+#[derive(Debug)]
+pub struct PlayerSlots {
+    max: AtomicUsize,
+    reserved: AtomicUsize,
+    online: Arc<AtomicUsize>,
+    privileged: RwLock<Privileged>,
+}
+impl Default for PlayerSlots {
+    fn default() -> Self {
+        PlayerSlots::new(DEFAULT_MAX_PLAYERS, 0)
+    }
+}
+impl PlayerSlots {
+    /// `reserved` of the `max` slots are kept for privileged players, i.e. operators.
+    pub fn new(max: usize, reserved: usize) -> Self {
+        PlayerSlots {
+            max: AtomicUsize::new(max),
+            reserved: AtomicUsize::new(reserved.min(max)),
+            online: Arc::default(),
+            privileged: RwLock::default(),
+        }
+    }
+    pub fn max(&self) -> usize {
+        self.max.load(Ordering::Relaxed)
+    }
+    pub fn reserved(&self) -> usize {
+        self.reserved.load(Ordering::Relaxed)
+    }
+    /// Players holding a slot, including those still logging in.
+    pub fn online(&self) -> usize {
+        self.online.load(Ordering::Relaxed)
+    }
+    /// Changes the limits. Players above the new maximum stay connected.
+    pub fn set_max(&self, max: usize, reserved: usize) {
+        self.max.store(max, Ordering::Relaxed);
+        self.reserved.store(reserved.min(max), Ordering::Relaxed);
+    }
+    /// Whether no slot is left, not even a reserved one.
+    pub fn is_full(&self) -> bool {
+        self.online() >= self.max()
+    }
+
+    /// Waits until every slot is given back, i.e. all connections have closed, or `timeout`
+    /// passes. Returns whether they were.
+    pub async fn wait_released(&self, timeout: Duration) -> bool {
+        let wait = async {
+            while self.online() > 0 {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        };
+        tokio::time::timeout(timeout, wait).await.is_ok()
+    }
+
+    /// Takes a slot, reserved ones only if `privileged`.
+    pub fn try_acquire(&self, privileged: bool) -> Option<PlayerSlot> {
+        let limit = match privileged {
+            true => self.max(),
+            false => self.max().saturating_sub(self.reserved()),
+        };
+        self.online
+            .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |n| {
+                (n < limit).then_some(n + 1)
+            })
+            .ok()?;
+        Some(PlayerSlot(Arc::clone(&self.online)))
+    }
+
+    /// Replaces the players allowed into reserved slots. Each is matched by XUID, or by name
+    /// when the XUID is empty.
+    pub fn set_privileged<'a>(&self, players: impl IntoIterator<Item = (&'a str, &'a str)>) {
+        let mut privileged = Privileged::default();
+        for (xuid, name) in players {
+            match xuid.is_empty() {
+                true => privileged.names.insert(name.to_lowercase()),
+                false => privileged.xuids.insert(xuid.to_owned()),
+            };
+        }
+        *self.privileged.write().unwrap() = privileged;
+    }
+    pub fn is_privileged(&self, xuid: &str, name: &str) -> bool {
+        let privileged = self.privileged.read().unwrap();
+        privileged.xuids.contains(xuid) || privileged.names.contains(&name.to_lowercase())
+    }
+}
+
+/// A taken slot, given back when dropped.
+#[derive(Debug)]
+pub struct PlayerSlot(Arc<AtomicUsize>);
+impl Drop for PlayerSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
@@ -0,0 +1,155 @@
+use std::{
+    fmt::{self, Debug},
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use anyhow::{Error, Result};
+use rust_raknet::{RaknetSocket, Reliability};
+use tokio::sync::{
+    mpsc::{self, UnboundedReceiver, UnboundedSender},
+    Notify,
+};
+
+use crate::into_anyhow;
+
+/// Boxed so that connections can hold any transport as `Arc<dyn Transport>`.
+pub type TransportFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// A reliable, ordered datagram connection to one client. Each datagram carries one batch.
+pub trait Transport: Send + Sync {
+    fn send<'a>(&'a self, datagram: &'a [u8]) -> TransportFuture<'a, ()>;
+    /// Waits for the next datagram. Fails with [`Closed`] once either side has closed.
+    fn recv(&self) -> TransportFuture<'_, Vec<u8>>;
+    /// Waits until the client has everything sent so far.
+    fn flush(&self) -> TransportFuture<'_, ()>;
+    fn close(&self) -> TransportFuture<'_, ()>;
+    fn peer_addr(&self) -> Result<SocketAddr>;
+}
+
+/// The connection was closed by either side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Closed;
+impl fmt::Display for Closed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "connection closed")
+    }
+}
+impl std::error::Error for Closed {}
+
+impl Transport for RaknetSocket {
+    fn send<'a>(&'a self, datagram: &'a [u8]) -> TransportFuture<'a, ()> {
+        Box::pin(async move {
+            RaknetSocket::send(self, datagram, Reliability::ReliableOrdered)
+                .await
+                .map_err(raknet_error)
+        })
+    }
+    fn recv(&self) -> TransportFuture<'_, Vec<u8>> {
+        Box::pin(async move { RaknetSocket::recv(self).await.map_err(raknet_error) })
+    }
+    fn flush(&self) -> TransportFuture<'_, ()> {
+        Box::pin(async move { RaknetSocket::flush(self).await.map_err(raknet_error) })
+    }
+    fn close(&self) -> TransportFuture<'_, ()> {
+        Box::pin(async move { RaknetSocket::close(self).await.map_err(raknet_error) })
+    }
+    fn peer_addr(&self) -> Result<SocketAddr> {
+        RaknetSocket::peer_addr(self).map_err(into_anyhow)
+    }
+}
+
+/// rust-raknet does not export its error type.
+fn raknet_error(e: impl Debug) -> Error {
+    match format!("{e:?}").as_str() {
+        "ConnectionClosed" => Closed.into(),
+        _ => into_anyhow(e),
+    }
+}
+
+/// One end of a connection over in-process channels, for running the login and the game
+/// without sockets. Datagrams arrive as soon as they are sent.
+#[derive(Debug)]
+pub struct MemoryTransport {
+    peer: SocketAddr,
+    to_peer: Mutex<Option<UnboundedSender<Vec<u8>>>>,
+    from_peer: tokio::sync::Mutex<UnboundedReceiver<Vec<u8>>>,
+    closed: AtomicBool,
+    closing: Notify,
+}
+impl MemoryTransport {
+    /// Connects an end at `a` to one at `b`; each reports the other's address as its peer.
+    pub fn pair(a: SocketAddr, b: SocketAddr) -> (MemoryTransport, MemoryTransport) {
+        let (a_tx, b_rx) = mpsc::unbounded_channel();
+        let (b_tx, a_rx) = mpsc::unbounded_channel();
+        (
+            MemoryTransport::new(b, a_tx, a_rx),
+            MemoryTransport::new(a, b_tx, b_rx),
+        )
+    }
+    fn new(
+        peer: SocketAddr,
+        to_peer: UnboundedSender<Vec<u8>>,
+        from_peer: UnboundedReceiver<Vec<u8>>,
+    ) -> Self {
+        MemoryTransport {
+            peer,
+            to_peer: Mutex::new(Some(to_peer)),
+            from_peer: tokio::sync::Mutex::new(from_peer),
+            closed: AtomicBool::new(false),
+            closing: Notify::new(),
+        }
+    }
+    fn check_open(&self) -> Result<()> {
+        match self.closed.load(Ordering::Acquire) {
+            true => Err(Closed.into()),
+            false => Ok(()),
+        }
+    }
+}
+impl Transport for MemoryTransport {
+    fn send<'a>(&'a self, datagram: &'a [u8]) -> TransportFuture<'a, ()> {
+        let sent = match &*self.to_peer.lock().unwrap() {
+            Some(to_peer) => to_peer.send(datagram.to_vec()).map_err(|_| Closed.into()),
+            None => Err(Closed.into()),
+        };
+        Box::pin(async move { sent })
+    }
+    fn recv(&self) -> TransportFuture<'_, Vec<u8>> {
+        Box::pin(async move {
+            // Created before the check so that a close in between still wakes it.
+            let closing = self.closing.notified();
+            self.check_open()?;
+            let mut from_peer = self.from_peer.lock().await;
+            tokio::select! {
+                datagram = from_peer.recv() => datagram.ok_or_else(|| Closed.into()),
+                _ = closing => {
+                    from_peer.close();
+                    Err(Closed.into())
+                }
+            }
+        })
+    }
+    fn flush(&self) -> TransportFuture<'_, ()> {
+        Box::pin(async move { self.check_open() })
+    }
+    fn close(&self) -> TransportFuture<'_, ()> {
+        self.closed.store(true, Ordering::Release);
+        // The peer sees the end of the channel once it has read what was sent, and can't
+        // send anything more. A `recv` holding the receiver closes it once woken.
+        self.to_peer.lock().unwrap().take();
+        if let Ok(mut from_peer) = self.from_peer.try_lock() {
+            from_peer.close();
+        }
+        self.closing.notify_waiters();
+        Box::pin(async { Ok(()) })
+    }
+    fn peer_addr(&self) -> Result<SocketAddr> {
+        Ok(self.peer)
+    }
+}
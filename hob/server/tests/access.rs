@@ -0,0 +1,124 @@
+use std::net::IpAddr;
+
+use hob_protocol::packet::disconnect::DisconnectFailReason;
+use hob_server::access::{parse_duration, unix_now, AccessControl, BanEntry, BanTarget, IpNet};
+
+fn ip(s: &str) -> IpAddr {
+    s.parse().unwrap()
+}
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("hob-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn cidr_blocks() {
+    let net: IpNet = "192.168.0.0/16".parse().unwrap();
+    assert!(net.contains(ip("192.168.4.20")));
+    assert!(net.contains(ip("::ffff:192.168.1.1")));
+    assert!(!net.contains(ip("192.169.0.1")));
+    assert_eq!(net.to_string(), "192.168.0.0/16");
+
+    let net: IpNet = "10.0.0.0/9".parse().unwrap();
+    assert!(net.contains(ip("10.127.255.255")));
+    assert!(!net.contains(ip("10.128.0.0")));
+
+    let single: IpNet = "2001:db8::1".parse().unwrap();
+    assert!(single.contains(ip("2001:db8::1")));
+    assert!(!single.contains(ip("2001:db8::2")));
+    assert!("10.0.0.0/33".parse::<IpNet>().is_err());
+    assert!("not an ip".parse::<IpNet>().is_err());
+}
+
+#[test]
+fn bans_reject_and_expire() {
+    let access = AccessControl::default();
+    let addr = ip("203.0.113.7");
+    assert!(access.check("100", "Steve", addr).is_none());
+
+    access
+        .ban(BanEntry {
+            target: BanTarget::Xuid("100".into()),
+            name: "Steve".into(),
+            reason: "griefing".into(),
+            expires: None,
+        })
+        .unwrap();
+    let packet = access.check("100", "Steve2", addr).unwrap();
+    assert!(matches!(packet.reason, DisconnectFailReason::Kicked));
+    assert!(packet.message.unwrap().contains("griefing"));
+
+    access
+        .ban(BanEntry {
+            target: BanTarget::Ip("203.0.113.0/24".parse().unwrap()),
+            name: String::new(),
+            reason: String::new(),
+            expires: Some(unix_now() + 60),
+        })
+        .unwrap();
+    assert!(access.check("200", "Alex", addr).is_some());
+    assert!(access.check("200", "Alex", ip("198.51.100.1")).is_none());
+
+    access
+        .ban(BanEntry {
+            target: BanTarget::Name("Herobrine".into()),
+            name: "Herobrine".into(),
+            reason: String::new(),
+            expires: Some(unix_now() - 1),
+        })
+        .unwrap();
+    assert!(access.check("", "herobrine", ip("198.51.100.1")).is_none());
+    assert_eq!(access.bans().len(), 2);
+
+    assert!(access.unban_player("steve").unwrap());
+    assert!(!access.unban_player("steve").unwrap());
+    assert!(access.check("100", "Steve", ip("198.51.100.1")).is_none());
+}
+
+#[test]
+fn whitelist_and_persistence() {
+    let (whitelist, bans) = (temp_path("whitelist.json"), temp_path("bans.json"));
+    let access = AccessControl::load(&whitelist, &bans).unwrap();
+    let addr = ip("198.51.100.1");
+    assert!(!access.whitelist_enabled());
+    assert!(access.whitelist_add("Steve", "100").unwrap());
+    assert!(!access.whitelist_add("steve", "").unwrap());
+    access.set_whitelist_enabled(true).unwrap();
+    access
+        .ban(BanEntry {
+            target: BanTarget::Name("Alex".into()),
+            name: "Alex".into(),
+            reason: String::new(),
+            expires: None,
+        })
+        .unwrap();
+
+    // Edits survive a restart.
+    let access = AccessControl::load(&whitelist, &bans).unwrap();
+    assert!(access.check("100", "Steve", addr).is_none());
+    let packet = access.check("300", "Notch", addr).unwrap();
+    assert!(matches!(packet.reason, DisconnectFailReason::NotAllowed));
+    assert!(matches!(
+        access.check("", "alex", addr).unwrap().reason,
+        DisconnectFailReason::Kicked
+    ));
+
+    assert!(access.whitelist_remove("Steve").unwrap());
+    assert!(access.check("100", "Steve", addr).is_some());
+    std::fs::write(&whitelist, r#"{"enabled": false}"#).unwrap();
+    access.reload().unwrap();
+    assert!(access.check("100", "Steve", addr).is_none());
+}
+
+#[test]
+fn durations() {
+    assert_eq!(parse_duration("45").unwrap(), 45);
+    assert_eq!(parse_duration("30m").unwrap(), 30 * 60);
+    assert_eq!(parse_duration("7d").unwrap(), 7 * 24 * 60 * 60);
+    assert!(parse_duration("soon").is_err());
+    assert!(parse_duration("3y").is_err());
+    let too_long = parse_duration("99999999999999w").unwrap_err();
+    assert!(too_long.to_string().starts_with("Duration too long"));
+}
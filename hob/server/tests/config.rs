@@ -0,0 +1,165 @@
+use hob_server::config::{
+    DuplicateLoginPolicy, LevelGameMode, LevelGenerator, LevelMovementAuthority, ServerConfig,
+};
+use log::LevelFilter;
+
+fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn defaults_round_trip() {
+    let config = ServerConfig::default();
+    let bind: Vec<_> = config.network.bind.iter().map(|a| a.to_string()).collect();
+    assert_eq!(bind, ["0.0.0.0:19132", "[::]:19133"]);
+    assert_eq!(config.network.protocol_version, 649);
+    assert_eq!(config.game.tps, 20);
+
+    let text = toml::to_string_pretty(&config).unwrap();
+    assert_eq!(ServerConfig::parse(&text, []).unwrap(), config);
+    assert_eq!(ServerConfig::parse("", []).unwrap(), config);
+}
+
+#[test]
+fn file_values_and_env_overrides() {
+    let text = r#"
+        log_level = "debug"
+
+        [network]
+        bind = ["127.0.0.1:19200"]
+        compression_threshold = 256
+
+        [players]
+        max = 50
+        reserved = 5
+        duplicate_login = "reject_new"
+
+        [level]
+        generator = "void"
+        seed = -42
+        game_mode = "creative"
+        movement_authority = "server_with_rewind"
+
+        [motd]
+        name = "Hob Survival"
+    "#;
+    let config = ServerConfig::parse(text, []).unwrap();
+    assert_eq!(config.log_level().unwrap(), LevelFilter::Debug);
+    assert_eq!(config.network.bind, ["127.0.0.1:19200".parse().unwrap()]);
+    assert_eq!(config.network.compression_threshold, 256);
+    assert_eq!(config.network.packet_queue, 32);
+    assert_eq!(config.players.max, 50);
+    assert_eq!(
+        config.players.duplicate_login,
+        DuplicateLoginPolicy::RejectNew
+    );
+    assert_eq!(config.level.generator, LevelGenerator::Void);
+    assert_eq!(config.level.seed, Some(-42));
+    assert_eq!(config.level.flat_world_layers, None);
+    assert_eq!(config.level.game_mode, LevelGameMode::Creative);
+    assert_eq!(config.level.view_distance, 10);
+    assert_eq!(
+        config.level.movement_authority,
+        LevelMovementAuthority::ServerWithRewind
+    );
+    assert_eq!(config.motd.name, "Hob Survival");
+    assert_eq!(config.motd.protocol_version, 649);
+
+    let config = ServerConfig::parse(
+        text,
+        env(&[
+            ("HOB_PLAYERS_MAX", "100"),
+            ("HOB_NETWORK_BIND", "0.0.0.0:19300, [::1]:19301"),
+            ("HOB_LOG_LEVEL", "warn"),
+            ("HOB_MOTD_SUB_MOTD", "Lobby"),
+            ("HOB_GAME_TPS", "40"),
+            ("OTHER_PLAYERS_MAX", "1"),
+        ]),
+    )
+    .unwrap();
+    assert_eq!(config.players.max, 100);
+    assert_eq!(config.players.reserved, 5);
+    let bind: Vec<_> = config.network.bind.iter().map(|a| a.port()).collect();
+    assert_eq!(bind, [19300, 19301]);
+    assert!(config.network.bind[1].is_ipv6());
+    assert_eq!(config.log_level().unwrap(), LevelFilter::Warn);
+    assert_eq!(config.motd.sub_motd, "Lobby");
+    assert_eq!(config.game.tps, 40);
+
+    let e = ServerConfig::parse(text, env(&[("HOB_PLAYERS_MAX", "lots")])).unwrap_err();
+    assert!(format!("{e:#}").contains("HOB_PLAYERS_MAX"));
+}
+
+#[test]
+fn env_overrides_unset_options_and_empty_lists() {
+    let config = ServerConfig::parse(
+        "",
+        env(&[
+            ("HOB_LEVEL_SEED", "42"),
+            ("HOB_LEVEL_FLAT_WORLD_LAYERS", "{\"biome_id\":1}"),
+            ("HOB_QUERY_BIND", "0.0.0.0:19134"),
+        ]),
+    )
+    .unwrap();
+    assert_eq!(config.level.seed, Some(42));
+    assert_eq!(
+        config.level.flat_world_layers.as_deref(),
+        Some("{\"biome_id\":1}")
+    );
+    assert_eq!(config.query.bind, ["0.0.0.0:19134".parse().unwrap()]);
+
+    // Typed as declared, not as the default's (missing) items.
+    let e = ServerConfig::parse("", env(&[("HOB_LEVEL_SEED", "random")])).unwrap_err();
+    assert!(format!("{e:#}").contains("HOB_LEVEL_SEED"));
+    let config = ServerConfig::parse(
+        "[query]\nbind = [\"0.0.0.0:1\"]",
+        env(&[("HOB_QUERY_BIND", "")]),
+    );
+    assert_eq!(config.unwrap().query.bind, []);
+}
+
+#[test]
+fn unknown_env_variables_are_rejected() {
+    let e = ServerConfig::parse("", env(&[("HOB_PLAYER_MAX", "100")])).unwrap_err();
+    assert!(
+        format!("{e:#}").contains("Unknown setting HOB_PLAYER_MAX"),
+        "{e:#}"
+    );
+    assert!(ServerConfig::parse("", env(&[("HOB_LEVEL", "void")])).is_err());
+}
+
+#[test]
+fn invalid_configs_are_rejected() {
+    for text in [
+        "[players]\nmax = 0",
+        "[players]\nmax = 4\nreserved = 5",
+        "[game]\ntps = 0",
+        "[level]\nview_distance = 0",
+        "[level]\ngenerator = \"nether\"",
+        "[level]\ngame_mode = \"fallback\"",
+        "[network]\npacket_queue = 0",
+        "log_level = \"loud\"",
+        "[network]\nbind = [\"nowhere\"]",
+        "[network]\nbind = []",
+        "[network]\nport = 19132",
+        "[players]\nduplicate_login = \"both\"",
+        "[rcon]\nenabled = true",
+        "[flood]\npackets_per_second = 0",
+        "[flood]\nmax_relays = 0",
+    ] {
+        assert!(ServerConfig::parse(text, []).is_err(), "{text}");
+    }
+}
+
+#[test]
+fn missing_file_gets_defaults_written() {
+    let path = std::env::temp_dir().join(format!("hob-{}-config.toml", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let config = ServerConfig::load(&path).unwrap();
+    assert_eq!(config, ServerConfig::parse("", std::env::vars()).unwrap());
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert!(written.contains("[network]"));
+    assert_eq!(ServerConfig::load(&path).unwrap(), config);
+}
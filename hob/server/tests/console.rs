@@ -0,0 +1,41 @@
+use hob_server::console::{CompletionSet, Completions};
+
+fn set() -> CompletionSet {
+    CompletionSet {
+        commands: ["stop", "op", "deop", "kick", "kill", "tp", "teleport"]
+            .map(String::from)
+            .to_vec(),
+        players: ["Steve", "Alex", "steve2"].map(String::from).to_vec(),
+    }
+}
+
+#[test]
+fn completes_commands_then_players() {
+    let set = set();
+    assert_eq!(
+        set.complete("ki", 2),
+        (0, vec!["kick".into(), "kill".into()])
+    );
+    assert_eq!(
+        set.complete("/t", 2),
+        (1, vec!["teleport".into(), "tp".into()])
+    );
+    assert_eq!(
+        set.complete("kick st", 7),
+        (5, vec!["Steve".into(), "steve2".into()])
+    );
+    assert_eq!(set.complete("tp Steve A", 10), (9, vec!["Alex".into()]));
+    // Only the text before the cursor counts.
+    assert_eq!(set.complete("op Alex", 2), (0, vec!["op".into()]));
+    assert_eq!(set.complete("nope", 4), (0, vec![]));
+}
+
+#[test]
+fn completions_are_shared() {
+    let completions = Completions::default();
+    let reader = completions.clone();
+    assert_eq!(reader.complete("st", 2).1, Vec::<String>::new());
+    completions.update(set());
+    assert_eq!(reader.get(), set());
+    assert_eq!(reader.complete("st", 2).1, vec!["stop".to_owned()]);
+}
@@ -0,0 +1,111 @@
+use std::{net::IpAddr, sync::Arc, time::Duration};
+
+use hob_server::{
+    config::FloodConfig,
+    flood::{Budget, FloodGuard},
+};
+
+fn guard(config: FloodConfig) -> Arc<FloodGuard> {
+    Arc::new(FloodGuard::new(config))
+}
+
+fn ip(s: &str) -> IpAddr {
+    s.parse().unwrap()
+}
+
+#[test]
+fn budget_refills_over_its_period() {
+    let mut budget = Budget::new(2, Duration::from_millis(100));
+    assert!(budget.take(2));
+    assert!(!budget.take(1));
+    std::thread::sleep(Duration::from_millis(60));
+    assert!(budget.take(1));
+    // Never holds more than one burst.
+    assert!(!Budget::new(2, Duration::from_secs(1)).take(3));
+}
+
+#[test]
+fn connections_are_limited_per_address() {
+    let guard = guard(FloodConfig {
+        connections_per_minute: 2,
+        ban_after: 0,
+        ..Default::default()
+    });
+    assert!(guard.allow_connection(ip("10.0.0.1")));
+    assert!(guard.allow_connection(ip("10.0.0.1")));
+    assert!(!guard.allow_connection(ip("10.0.0.1")));
+    assert!(guard.allow_connection(ip("10.0.0.2")));
+    // Without bans, going over only costs the attempt.
+    assert!(!guard.is_banned(ip("10.0.0.1")));
+}
+
+#[test]
+fn repeat_offenders_are_banned() {
+    let guard = guard(FloodConfig {
+        connections_per_minute: 1,
+        ban_after: 2,
+        ban_secs: 60,
+        ..Default::default()
+    });
+    let flooder = ip("10.0.0.1");
+    assert!(guard.allow_connection(flooder));
+    assert!(!guard.allow_connection(flooder));
+    assert!(!guard.is_banned(flooder));
+    assert!(!guard.allow_connection(flooder));
+    assert!(guard.is_banned(flooder));
+    // The same address over IPv6.
+    assert!(guard.is_banned(ip("::ffff:10.0.0.1")));
+    assert!(!guard.is_banned(ip("10.0.0.2")));
+}
+
+#[test]
+fn pending_logins_are_capped() {
+    let guard = guard(FloodConfig {
+        max_pending_logins: 2,
+        ..Default::default()
+    });
+    let first = guard.try_begin_login().unwrap();
+    let _second = guard.try_begin_login().unwrap();
+    assert!(guard.try_begin_login().is_none());
+    drop(first);
+    assert_eq!(guard.pending_logins(), 1);
+    assert!(guard.try_begin_login().is_some());
+}
+
+#[test]
+fn packet_budgets_report_offences() {
+    let guard = guard(FloodConfig {
+        packets_per_second: 10,
+        bytes_per_second: 1000,
+        ban_after: 2,
+        ..Default::default()
+    });
+    let client = ip("10.0.0.1");
+    let mut budget = guard.packet_budget(client);
+    budget.take_bytes(1000).unwrap();
+    budget.take_packets(10).unwrap();
+    assert!(budget.take_bytes(500).is_err());
+    assert!(!guard.is_banned(client));
+    assert!(budget.take_packets(5).is_err());
+    assert!(guard.is_banned(client));
+    assert!(!guard.allow_connection(client));
+}
+
+#[test]
+fn relays_count_as_connections() {
+    let guard = guard(FloodConfig {
+        connections_per_minute: 2,
+        max_relays: 2,
+        ban_after: 0,
+        ..Default::default()
+    });
+    let first = guard.try_open_relay(ip("10.0.0.1")).unwrap();
+    let _second = guard.try_open_relay(ip("10.0.0.1")).unwrap();
+    // Over the total, whoever asks.
+    assert!(guard.try_open_relay(ip("10.0.0.2")).is_none());
+    drop(first);
+    assert_eq!(guard.open_relays(), 1);
+    // Over the address's attempts, however many are open.
+    assert!(guard.try_open_relay(ip("10.0.0.1")).is_none());
+    assert!(guard.try_open_relay(ip("10.0.0.2")).is_some());
+}
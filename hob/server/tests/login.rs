@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use hob_protocol::packet::{
+    disconnect::DisconnectFailReason, handshake::ClientToServerHandshakePacket,
+    request_chunk_radius::RequestChunkRadiusPacket,
+    request_network_setting::RequestNetworkSettingPacket, PacketKind,
+};
+use hob_server::initial_handler::LoginState;
+
+#[test]
+fn login_steps_are_ordered() {
+    use LoginState::*;
+    let steps = [
+        AwaitingNetworkSettings,
+        AwaitingLogin,
+        AwaitingHandshake,
+        ResourcePacks,
+        Spawning,
+    ];
+    assert!(steps.windows(2).all(|w| w[0] < w[1]));
+    assert!(steps.iter().all(|s| s.timeout() > Duration::ZERO));
+}
+
+#[test]
+fn packets_map_to_their_step() {
+    let request = PacketKind::RequestNetworkSetting(RequestNetworkSettingPacket {
+        client_protocol: 649,
+    });
+    assert_eq!(
+        LoginState::of(&request),
+        Some(LoginState::AwaitingNetworkSettings)
+    );
+    let handshake = PacketKind::ClientToServerHandshake(ClientToServerHandshakePacket);
+    assert_eq!(
+        LoginState::of(&handshake),
+        Some(LoginState::AwaitingHandshake)
+    );
+    // Packets that don't drive the login may come along in the same batch.
+    let radius = PacketKind::RequestChunkRadius(RequestChunkRadiusPacket {
+        chunk_radius: 8,
+        max_chunk_radius: 8,
+    });
+    assert_eq!(LoginState::of(&radius), None);
+}
+
+#[test]
+fn timeouts_name_the_failing_phase() {
+    assert!(matches!(
+        LoginState::AwaitingLogin.timeout_reason(),
+        DisconnectFailReason::ConnNegotiationTimeout
+    ));
+    assert!(matches!(
+        LoginState::Spawning.timeout_reason(),
+        DisconnectFailReason::LoadingStateTimeout
+    ));
+}
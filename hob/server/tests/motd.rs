@@ -0,0 +1,174 @@
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use hob_server::{
+    config::FloodConfig,
+    flood::FloodGuard,
+    frontend::{encode_pong, is_open_connection_request, parse_ping, Frontend, MAGIC},
+    motd::Motd,
+    slots::PlayerSlots,
+};
+use tokio::{net::UdpSocket, time::timeout};
+
+fn ping(time: i64) -> Vec<u8> {
+    let mut ping = vec![0x01];
+    ping.extend_from_slice(&time.to_be_bytes());
+    ping.extend_from_slice(&MAGIC);
+    ping.extend_from_slice(&42u64.to_be_bytes());
+    ping
+}
+
+/// An `OpenConnectionRequest1` for RakNet protocol 11, padded to an MTU of 64.
+fn connection_request() -> Vec<u8> {
+    let mut request = vec![0x05];
+    request.extend_from_slice(&MAGIC);
+    request.push(11);
+    request.resize(64, 0);
+    request
+}
+
+async fn recv(socket: &UdpSocket) -> (Vec<u8>, std::net::SocketAddr) {
+    let mut buf = [0u8; 2048];
+    let (len, from) = timeout(Duration::from_secs(5), socket.recv_from(&mut buf))
+        .await
+        .expect("timed out")
+        .unwrap();
+    (buf[..len].to_vec(), from)
+}
+
+#[test]
+fn pong_fields() {
+    let motd = Motd {
+        name: "My; Server".to_owned(),
+        sub_motd: "Lobby".to_owned(),
+        guid: 1234,
+        port_v4: 19132,
+        port_v6: 19133,
+        ..Default::default()
+    };
+    assert_eq!(
+        motd.pong(3, 20),
+        "MCPE;My  Server;649;1.20.60;3;20;1234;Lobby;Survival;1;19132;19133;"
+    );
+
+    assert_eq!(parse_ping(&ping(77)), Some(77));
+    assert_eq!(parse_ping(&[0x01, 0, 0]), None);
+    let pong = encode_pong(77, 1234, "MCPE;x;");
+    assert_eq!(pong[0], 0x1c);
+    assert_eq!(&pong[1..9], &77i64.to_be_bytes());
+    assert_eq!(&pong[9..17], &1234u64.to_be_bytes());
+    assert_eq!(&pong[17..33], &MAGIC);
+    assert_eq!(&pong[33..35], &7u16.to_be_bytes());
+    assert_eq!(&pong[35..], b"MCPE;x;");
+
+    assert!(is_open_connection_request(&connection_request()));
+    assert!(!is_open_connection_request(b"\x05hello"));
+    assert!(!is_open_connection_request(&ping(77)));
+}
+
+#[tokio::test]
+async fn frontend_answers_pings_and_relays() {
+    let upstream = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let slots = Arc::new(PlayerSlots::new(10, 0));
+    let motd = Arc::new(RwLock::new(Motd::default()));
+    let pong = {
+        let (slots, motd) = (slots.clone(), motd.clone());
+        Arc::new(move || motd.read().unwrap().pong(slots.online(), slots.max()))
+    };
+    let frontend = Frontend::bind(
+        "127.0.0.1:0".parse().unwrap(),
+        upstream.local_addr().unwrap(),
+        99,
+        pong,
+    )
+    .await
+    .unwrap();
+    let front_addr = frontend.local_addr().unwrap();
+    let clients = frontend.clients();
+    tokio::spawn(frontend.run());
+
+    let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    client.send_to(&ping(1), front_addr).await.unwrap();
+    let (pong, _) = recv(&client).await;
+    assert!(String::from_utf8_lossy(&pong[35..]).starts_with("MCPE;Hob;649;1.20.60;0;10;"));
+
+    // The pong follows joins and edits without restarting anything.
+    let _slot = slots.try_acquire(false).unwrap();
+    motd.write().unwrap().name = "Renamed".to_owned();
+    client.send_to(&ping(2), front_addr).await.unwrap();
+    let (pong, _) = recv(&client).await;
+    assert!(String::from_utf8_lossy(&pong[35..]).starts_with("MCPE;Renamed;649;1.20.60;1;10;"));
+
+    // A connection request opens a relay, which RakNet sees as the client.
+    client
+        .send_to(&connection_request(), front_addr)
+        .await
+        .unwrap();
+    let (data, relay) = recv(&upstream).await;
+    assert_eq!(data, connection_request());
+    assert_eq!(clients.resolve(relay), client.local_addr().unwrap());
+    // Everything after that goes through it.
+    client.send_to(b"\x84frame", front_addr).await.unwrap();
+    assert_eq!(recv(&upstream).await, (b"\x84frame".to_vec(), relay));
+
+    upstream.send_to(b"\x06reply", relay).await.unwrap();
+    let (data, from) = recv(&client).await;
+    assert_eq!(data, b"\x06reply");
+    assert_eq!(from, front_addr);
+
+    // The accepted session owns the relay, which closes as soon as the session lets go.
+    let (address, guard) = clients.claim(relay);
+    assert_eq!(address, client.local_addr().unwrap());
+    assert!(guard.is_some());
+    assert!(clients.claim(relay).1.is_none());
+    drop(guard);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(clients.resolve(relay), relay);
+    client.send_to(b"\x84frame", front_addr).await.unwrap();
+    let mut buf = [0u8; 2048];
+    let more = timeout(Duration::from_millis(200), upstream.recv_from(&mut buf)).await;
+    assert!(more.is_err());
+}
+
+#[tokio::test]
+async fn relays_are_opened_sparingly() {
+    let upstream = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let flood = Arc::new(FloodGuard::new(FloodConfig {
+        max_relays: 2,
+        ban_after: 0,
+        ..Default::default()
+    }));
+    let frontend = Frontend::bind(
+        "127.0.0.1:0".parse().unwrap(),
+        upstream.local_addr().unwrap(),
+        99,
+        Arc::new(String::new),
+    )
+    .await
+    .unwrap()
+    .with_flood(Arc::clone(&flood));
+    let front_addr = frontend.local_addr().unwrap();
+    tokio::spawn(frontend.run());
+
+    // Stray datagrams from unknown addresses get no relay.
+    let stray = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    stray.send_to(b"\x84frame", front_addr).await.unwrap();
+    let mut clients = Vec::new();
+    for _ in 0..3 {
+        let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        client
+            .send_to(&connection_request(), front_addr)
+            .await
+            .unwrap();
+        clients.push(client);
+    }
+    // Only as many relays open as allowed; the third request is dropped.
+    recv(&upstream).await;
+    recv(&upstream).await;
+    let mut buf = [0u8; 2048];
+    let more = timeout(Duration::from_millis(200), upstream.recv_from(&mut buf)).await;
+    assert!(more.is_err());
+    assert_eq!(flood.open_relays(), 2);
+}
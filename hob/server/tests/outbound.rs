@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use hob_protocol::packet::{
+    disconnect::{DisconnectFailReason, DisconnectPacket},
+    level_chunk::LevelChunkPacket,
+    move_player::{MoveMode, MovePlayerPacket},
+    start_game::Dimension,
+    PacketKind,
+};
+use hob_server::outbound::{self, OutboundError, Priority};
+
+fn chunk(size: usize) -> PacketKind {
+    LevelChunkPacket {
+        chunk_x: 0,
+        chunk_z: 0,
+        dimension: Dimension::OverWorld,
+        sub_chunk_count: 0,
+        cache_enabled: false,
+        payload: vec![0; size],
+    }
+    .into()
+}
+
+fn movement() -> PacketKind {
+    MovePlayerPacket {
+        runtime_id: 1,
+        position: (0.0, 0.0, 0.0),
+        pitch: 0.0,
+        yaw: 0.0,
+        head_yaw: 0.0,
+        mode: MoveMode::Normal,
+        on_ground: true,
+        ridden_runtime_id: 0,
+        tick: 0,
+    }
+    .into()
+}
+
+fn kick() -> PacketKind {
+    DisconnectPacket::new(DisconnectFailReason::Kicked, "bye").into()
+}
+
+#[test]
+fn priorities() {
+    assert_eq!(Priority::of(&kick()), Priority::Critical);
+    assert_eq!(Priority::of(&chunk(1)), Priority::Normal);
+    assert_eq!(Priority::of(&movement()), Priority::Droppable);
+}
+
+#[test]
+fn packets_keep_their_order() {
+    let (sender, mut receiver) = outbound::channel(1 << 20, Duration::from_secs(15));
+    sender.send(chunk(100)).unwrap();
+    sender.send(kick()).unwrap();
+    assert_eq!(receiver.try_recv().unwrap()[0], 0x3a);
+    assert!(matches!(
+        receiver.try_recv_packet(),
+        Some(PacketKind::Disconnect(p)) if p.message.as_deref() == Some("bye")
+    ));
+    assert!(receiver.try_recv().is_none());
+    let stats = sender.stats();
+    assert_eq!((stats.queued_packets, stats.queued_bytes), (0, 0));
+    assert_eq!(stats.sent_packets, 2);
+}
+
+#[test]
+fn backlog_drops_then_refuses() {
+    let (sender, mut receiver) = outbound::channel(1000, Duration::from_secs(15));
+    sender.send(chunk(600)).unwrap();
+    // Over half the budget, movement is skipped.
+    sender.send(movement()).unwrap();
+    assert_eq!(sender.stats().dropped_packets, 1);
+    sender.send(chunk(600)).unwrap();
+    assert_eq!(sender.send(chunk(1000)), Err(OutboundError::TooSlow));
+    // A kick always gets in.
+    sender.send(kick()).unwrap();
+    let stats = sender.stats();
+    assert_eq!(stats.queued_packets, 3);
+    assert_eq!(stats.peak_bytes, stats.queued_bytes);
+
+    sender.clear();
+    assert_eq!(sender.stats().queued_bytes, 0);
+    assert_eq!(sender.stats().dropped_packets, 4);
+    assert!(receiver.try_recv().is_none());
+}
+
+#[test]
+fn staying_behind_is_too_slow() {
+    let (sender, mut receiver) = outbound::channel(1000, Duration::from_millis(50));
+    sender.send(chunk(1500)).unwrap();
+    assert!(!sender.is_too_slow());
+    std::thread::sleep(Duration::from_millis(80));
+    assert!(sender.is_too_slow());
+    // Catching up resets the clock.
+    receiver.try_recv().unwrap();
+    assert!(!sender.is_too_slow());
+}
+
+#[tokio::test]
+async fn closing_either_end() {
+    let (sender, mut receiver) = outbound::channel(1000, Duration::from_secs(15));
+    sender.send(kick()).unwrap();
+    drop(sender);
+    // What was queued is still delivered.
+    assert_eq!(receiver.recv_all().await.map(|p| p.len()), Some(1));
+    assert!(receiver.recv_all().await.is_none());
+
+    let (sender, receiver) = outbound::channel(1000, Duration::from_secs(15));
+    drop(receiver);
+    assert_eq!(sender.send(kick()), Err(OutboundError::Closed));
+}
+
+#[test]
+fn undecodable_packets_are_skipped() {
+    let (sender, mut receiver) = outbound::channel(1 << 20, Duration::from_secs(15));
+    sender.send(chunk(10)).unwrap();
+    sender.send(kick()).unwrap();
+    // Clientbound packets like chunks can't be read back.
+    assert!(receiver.try_recv_packet().is_none());
+    assert!(matches!(
+        receiver.try_recv_packet(),
+        Some(PacketKind::Disconnect(_))
+    ));
+}
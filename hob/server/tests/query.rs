@@ -0,0 +1,149 @@
+use std::{
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use hob_server::{
+    frontend::Frontend,
+    motd::Motd,
+    query::{Query, QueryStatus, HANDSHAKE, QUERY_MAGIC, STAT},
+    slots::PlayerSlots,
+};
+use tokio::net::UdpSocket;
+
+fn query() -> (Query, Arc<RwLock<QueryStatus>>) {
+    let motd = Motd {
+        name: "Hob Survival".into(),
+        sub_motd: "world".into(),
+        ..Motd::default()
+    };
+    let status = Arc::new(RwLock::new(QueryStatus {
+        players: vec!["Alex".into(), "Steve".into()],
+        plugins: vec!["HelloWorld".into()],
+    }));
+    let query = Query::new(
+        Arc::new(RwLock::new(motd)),
+        Arc::new(PlayerSlots::new(20, 0)),
+        Arc::clone(&status),
+    );
+    (query, status)
+}
+
+fn request(kind: u8, session: i32, payload: &[u8]) -> Vec<u8> {
+    [&QUERY_MAGIC[..], &[kind], &session.to_be_bytes(), payload].concat()
+}
+
+/// Splits a reply after its type and session into null terminated strings.
+fn strings(reply: &[u8]) -> Vec<String> {
+    reply[5..]
+        .split(|&b| b == 0)
+        .map(|s| String::from_utf8_lossy(s).into_owned())
+        .collect()
+}
+
+fn token(reply: &[u8]) -> [u8; 4] {
+    assert_eq!(reply[0], HANDSHAKE);
+    strings(reply)[0].parse::<i32>().unwrap().to_be_bytes()
+}
+
+#[test]
+fn handshake_then_stats() {
+    let (query, status) = query();
+    let client: SocketAddr = "192.0.2.1:5000".parse().unwrap();
+    let host: SocketAddr = "0.0.0.0:19132".parse().unwrap();
+
+    let reply = query
+        .handle(&request(HANDSHAKE, 7, &[]), client, host)
+        .unwrap();
+    assert_eq!(reply[1..5], 7i32.to_be_bytes());
+    let token = token(&reply);
+
+    let basic = query
+        .handle(&request(STAT, 7, &token), client, host)
+        .unwrap();
+    assert_eq!(basic[0], STAT);
+    let fields = strings(&basic);
+    assert_eq!(fields[..5], ["Hob Survival", "SMP", "world", "2", "20"]);
+    // The port is little endian, right before the address.
+    let port_at = basic.len() - "0.0.0.0".len() - 3;
+    assert_eq!(basic[port_at..port_at + 2], 19132u16.to_le_bytes());
+
+    status.write().unwrap().players.push("Herobrine".into());
+    let full_request = request(STAT, 7, &[&token[..], &[0; 4]].concat());
+    let full = query.handle(&full_request, client, host).unwrap();
+    let fields = strings(&full);
+    let value = |key: &str| {
+        let at = fields.iter().position(|f| f == key).unwrap();
+        fields[at + 1].clone()
+    };
+    assert_eq!(value("hostname"), "Hob Survival");
+    assert_eq!(value("map"), "world");
+    assert_eq!(value("numplayers"), "3");
+    assert_eq!(value("maxplayers"), "20");
+    assert_eq!(value("version"), "1.20.60");
+    assert!(value("plugins").ends_with(": HelloWorld"));
+    let players = fields.iter().position(|f| f == "\u{1}player_").unwrap();
+    assert_eq!(
+        fields[players + 2..players + 5],
+        ["Alex", "Steve", "Herobrine"]
+    );
+}
+
+#[test]
+fn tokens_are_checked() {
+    let (query, _) = query();
+    let client: SocketAddr = "192.0.2.1:5000".parse().unwrap();
+    let other: SocketAddr = "192.0.2.2:5000".parse().unwrap();
+    let host: SocketAddr = "0.0.0.0:19132".parse().unwrap();
+
+    let token = token(
+        &query
+            .handle(&request(HANDSHAKE, 1, &[]), client, host)
+            .unwrap(),
+    );
+    assert!(query
+        .handle(&request(STAT, 1, &token), other, host)
+        .is_none());
+    let wrong = (i32::from_be_bytes(token) ^ 1).to_be_bytes();
+    assert!(query
+        .handle(&request(STAT, 1, &wrong), client, host)
+        .is_none());
+    assert!(query.handle(&request(STAT, 1, &[]), client, host).is_none());
+    assert!(query
+        .handle(&[0xfe, 0xfd, 0x42, 0, 0, 0, 1], client, host)
+        .is_none());
+}
+
+#[tokio::test]
+async fn frontend_answers_queries() {
+    let upstream = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let (query, _) = query();
+    let frontend = Frontend::bind(
+        "127.0.0.1:0".parse().unwrap(),
+        upstream.local_addr().unwrap(),
+        1,
+        Arc::new(String::new),
+    )
+    .await
+    .unwrap()
+    .with_query(Some(Arc::new(query)));
+    let front_addr = frontend.local_addr().unwrap();
+    tokio::spawn(frontend.run());
+
+    let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let ask = |payload: Vec<u8>| {
+        let client = &client;
+        async move {
+            client.send_to(&payload, front_addr).await.unwrap();
+            let mut buf = [0u8; 1024];
+            let recv = tokio::time::timeout(Duration::from_secs(2), client.recv(&mut buf));
+            let len = recv.await.unwrap().unwrap();
+            buf[..len].to_vec()
+        }
+    };
+    let token = token(&ask(request(HANDSHAKE, 3, &[])).await);
+    let basic = ask(request(STAT, 3, &token)).await;
+    assert_eq!(strings(&basic)[0], "Hob Survival");
+    assert!(strings(&basic).last().unwrap().is_empty());
+}
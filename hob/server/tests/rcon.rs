@@ -0,0 +1,80 @@
+use std::net::SocketAddr;
+
+use hob_server::{
+    config::RconConfig,
+    rcon::{self, packet_type, RconClient, RconCommands, RconPacket, MAX_RESPONSE_BODY},
+};
+use tokio::runtime::Runtime;
+
+fn config(commands_per_second: u32) -> RconConfig {
+    RconConfig {
+        enabled: true,
+        bind: "127.0.0.1:0".parse().unwrap(),
+        password: "hunter2".into(),
+        commands_per_second,
+        max_auth_failures: 2,
+        lockout_secs: 60,
+    }
+}
+
+/// Starts RCON with a stand-in for the game thread that echoes commands back.
+async fn start(runtime: &Runtime, config: RconConfig) -> SocketAddr {
+    let (RconCommands(mut commands), addr) = rcon::start(&config, runtime).await.unwrap();
+    runtime.spawn(async move {
+        while let Some(command) = commands.recv().await {
+            let output = match command.line.as_str() {
+                "long" => "x".repeat(MAX_RESPONSE_BODY * 2 + 100),
+                line => format!("ran {line}"),
+            };
+            command.reply.send(output).unwrap();
+        }
+    });
+    addr
+}
+
+#[test]
+fn packets_round_trip() {
+    Runtime::new().unwrap().block_on(async {
+        let packet = RconPacket::new(7, packet_type::EXEC_COMMAND, "list");
+        let mut buf = Vec::new();
+        packet.write(&mut buf).await.unwrap();
+        assert_eq!(&buf[..4], &14i32.to_le_bytes());
+        assert_eq!(RconPacket::read(&mut buf.as_slice()).await.unwrap(), packet);
+
+        let oversized = [&5000i32.to_le_bytes()[..], &[0; 16]].concat();
+        assert!(RconPacket::read(&mut oversized.as_slice()).await.is_err());
+    });
+}
+
+#[test]
+fn client_runs_commands() {
+    let runtime = Runtime::new().unwrap();
+    runtime.block_on(async {
+        let addr = start(&runtime, config(2)).await;
+        let mut client = RconClient::connect(addr).await.unwrap();
+        assert!(client.authenticate("hunter2").await.unwrap());
+        assert_eq!(client.command("list").await.unwrap(), "ran list");
+
+        // Long output comes in several responses and is put back together.
+        let long = client.command("long").await.unwrap();
+        assert_eq!(long.len(), MAX_RESPONSE_BODY * 2 + 100);
+
+        let output = client.command("list").await.unwrap();
+        assert_eq!(output, "Too many commands, slow down");
+    });
+}
+
+#[test]
+fn wrong_passwords_lock_out() {
+    let runtime = Runtime::new().unwrap();
+    runtime.block_on(async {
+        let addr = start(&runtime, config(5)).await;
+        for _ in 0..2 {
+            let mut client = RconClient::connect(addr).await.unwrap();
+            assert!(!client.authenticate("password").await.unwrap());
+        }
+        // Even the right password is turned away now.
+        let mut client = RconClient::connect(addr).await.unwrap();
+        assert!(client.authenticate("hunter2").await.is_err());
+    });
+}
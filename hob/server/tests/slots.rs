@@ -0,0 +1,41 @@
+use hob_server::slots::PlayerSlots;
+
+#[test]
+fn slots_are_limited_and_freed() {
+    let slots = PlayerSlots::new(2, 0);
+    let a = slots.try_acquire(false).unwrap();
+    let _b = slots.try_acquire(false).unwrap();
+    assert_eq!(slots.online(), 2);
+    assert!(slots.is_full());
+    assert!(slots.try_acquire(false).is_none());
+    assert!(slots.try_acquire(true).is_none());
+
+    drop(a);
+    assert_eq!(slots.online(), 1);
+    assert!(slots.try_acquire(false).is_some());
+    assert_eq!(slots.online(), 1);
+}
+
+#[test]
+fn reserved_slots_admit_privileged_players() {
+    let slots = PlayerSlots::new(3, 1);
+    let _a = slots.try_acquire(false).unwrap();
+    let _b = slots.try_acquire(false).unwrap();
+    assert!(slots.try_acquire(false).is_none());
+    assert!(!slots.is_full());
+
+    slots.set_privileged([("100", "Steve"), ("", "Alex")]);
+    assert!(slots.is_privileged("100", "Someone"));
+    assert!(slots.is_privileged("200", "alex"));
+    assert!(!slots.is_privileged("300", "Notch"));
+
+    let _op = slots.try_acquire(true).unwrap();
+    assert!(slots.is_full());
+    assert!(slots.try_acquire(true).is_none());
+
+    slots.set_max(5, 1);
+    assert!(slots.try_acquire(false).is_some());
+    slots.set_max(1, 4);
+    assert_eq!(slots.reserved(), 1);
+    assert!(slots.try_acquire(true).is_none());
+}
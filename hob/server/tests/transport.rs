@@ -0,0 +1,411 @@
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use hob_protocol::packet::{
+    disconnect::{DisconnectFailReason, DisconnectPacket},
+    login::{ExtraUserdata, LoginPacket},
+    network_settings::NetworkSettingsPacket,
+    play_status::PlayStatusPacket,
+    request_chunk_radius::RequestChunkRadiusPacket,
+    request_network_setting::RequestNetworkSettingPacket,
+    PacketKind,
+};
+use hob_server::{
+    access::AccessControl,
+    config::NetworkConfig,
+    connection_client::{ConnectionClient, DisconnectReason},
+    flood::FloodGuard,
+    initial_handler::{LoginResult, PROTOCOL_VERSION},
+    player_registry::PlayerRegistry,
+    slots::PlayerSlots,
+    transport::{Closed, MemoryTransport, Transport, TransportFuture},
+};
+use tokio::{
+    runtime::Runtime,
+    sync::mpsc::{self, Receiver},
+    time::timeout,
+};
+
+const WAIT: Duration = Duration::from_secs(5);
+
+fn addrs() -> (SocketAddr, SocketAddr) {
+    (
+        "127.0.0.1:19132".parse().unwrap(),
+        "10.0.0.7:50000".parse().unwrap(),
+    )
+}
+
+/// Notes what a connection does with its transport, to check the order of sends and closing.
+struct Recording {
+    inner: MemoryTransport,
+    calls: Mutex<Vec<&'static str>>,
+}
+impl Recording {
+    fn new(inner: MemoryTransport) -> Self {
+        Recording {
+            inner,
+            calls: Mutex::default(),
+        }
+    }
+    fn note(&self, call: &'static str) {
+        self.calls.lock().unwrap().push(call);
+    }
+    /// The calls made once the connection has closed the transport.
+    async fn closed(&self) -> Vec<&'static str> {
+        let closed = async {
+            while !self.calls.lock().unwrap().contains(&"close") {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        };
+        timeout(WAIT, closed).await.unwrap();
+        self.calls.lock().unwrap().clone()
+    }
+}
+impl Transport for Recording {
+    fn send<'a>(&'a self, datagram: &'a [u8]) -> TransportFuture<'a, ()> {
+        self.note("send");
+        self.inner.send(datagram)
+    }
+    fn recv(&self) -> TransportFuture<'_, Vec<u8>> {
+        self.inner.recv()
+    }
+    fn flush(&self) -> TransportFuture<'_, ()> {
+        self.note("flush");
+        self.inner.flush()
+    }
+    fn close(&self) -> TransportFuture<'_, ()> {
+        self.note("close");
+        self.inner.close()
+    }
+    fn peer_addr(&self) -> anyhow::Result<SocketAddr> {
+        self.inner.peer_addr()
+    }
+}
+
+/// A connection over `transport`; the client side of a test uses one as well, since both
+/// ends speak the same batch format.
+fn connection(
+    transport: Arc<dyn Transport>,
+    runtime: &Arc<Runtime>,
+) -> (ConnectionClient, Receiver<PlayerRegistry>) {
+    let (registry_tx, registry_rx) = mpsc::channel(1);
+    let address = transport.peer_addr().unwrap();
+    let connection = ConnectionClient::new(
+        transport,
+        address,
+        Arc::new(NetworkConfig::default()),
+        registry_tx,
+        Arc::new(AccessControl::default()),
+        Arc::new(PlayerSlots::default()),
+        Arc::clone(runtime),
+    );
+    (connection, registry_rx)
+}
+
+async fn read(client: &mut ConnectionClient) -> Vec<PacketKind> {
+    timeout(WAIT, client.read()).await.unwrap().unwrap()
+}
+
+fn steve() -> ExtraUserdata {
+    ExtraUserdata {
+        xuid: "100".into(),
+        identity: "00000000-0000-0000-0000-000000000000".into(),
+        display_name: "Steve".into(),
+        title_id: "".into(),
+        sandbox_id: "RETAIL".into(),
+    }
+}
+
+fn request_network_settings(client_protocol: i32) -> PacketKind {
+    RequestNetworkSettingPacket { client_protocol }.into()
+}
+
+#[tokio::test]
+async fn memory_transport_delivers_and_closes() {
+    let (server_addr, client_addr) = addrs();
+    let (server, client) = MemoryTransport::pair(server_addr, client_addr);
+    assert_eq!(server.peer_addr().unwrap(), client_addr);
+    assert_eq!(client.peer_addr().unwrap(), server_addr);
+
+    client.send(b"one").await.unwrap();
+    client.send(b"two").await.unwrap();
+    client.close().await.unwrap();
+    // What was sent before closing still arrives.
+    assert_eq!(server.recv().await.unwrap(), b"one");
+    assert_eq!(server.recv().await.unwrap(), b"two");
+    assert!(server.recv().await.unwrap_err().is::<Closed>());
+    assert!(server.send(b"three").await.unwrap_err().is::<Closed>());
+    assert!(client.recv().await.unwrap_err().is::<Closed>());
+}
+
+#[tokio::test]
+async fn closing_wakes_a_waiting_recv() {
+    let (server_addr, client_addr) = addrs();
+    let (server, _client) = MemoryTransport::pair(server_addr, client_addr);
+    let server = Arc::new(server);
+    let waiting = tokio::spawn({
+        let server = Arc::clone(&server);
+        async move { server.recv().await }
+    });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    server.close().await.unwrap();
+    let received = timeout(WAIT, waiting).await.unwrap().unwrap();
+    assert!(received.unwrap_err().is::<Closed>());
+}
+
+#[test]
+fn unverified_login_is_turned_away() {
+    let runtime = Arc::new(Runtime::new().unwrap());
+    let flood = Arc::new(FloodGuard::default());
+    runtime.block_on(async {
+        let (server_addr, client_addr) = addrs();
+        let (server, client) = MemoryTransport::pair(server_addr, client_addr);
+        let (server, _players) = connection(Arc::new(server), &runtime);
+        let login = flood.try_begin_login().unwrap();
+        server
+            .limit(login, flood.packet_budget(client_addr.ip()))
+            .start();
+        let (mut client, _) = connection(Arc::new(client), &runtime);
+
+        client
+            .write(request_network_settings(PROTOCOL_VERSION))
+            .await
+            .unwrap();
+        let settings = read(&mut client).await;
+        assert!(matches!(
+            &settings[..],
+            [PacketKind::NetworkSettings(NetworkSettingsPacket { .. })]
+        ));
+        client.enable_compression();
+
+        // Only chains signed by Xbox Live are let in.
+        let login = LoginPacket {
+            protocol_version: PROTOCOL_VERSION,
+            identity: r#"{"chain":[]}"#.into(),
+            client: String::new(),
+        };
+        client.write(login.into()).await.unwrap();
+        let packets = read(&mut client).await;
+        assert!(matches!(
+            &packets[..],
+            [PacketKind::Disconnect(p)] if matches!(p.reason, DisconnectFailReason::NotAuthenticated)
+        ));
+        // The login stops counting once the connection task is done with it.
+        let finished = async {
+            while flood.pending_logins() > 0 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        };
+        timeout(WAIT, finished).await.unwrap();
+    });
+}
+
+#[test]
+fn outdated_client_is_told_so() {
+    let runtime = Arc::new(Runtime::new().unwrap());
+    runtime.block_on(async {
+        let (server_addr, client_addr) = addrs();
+        let (server, client) = MemoryTransport::pair(server_addr, client_addr);
+        connection(Arc::new(server), &runtime).0.start();
+        let (mut client, _) = connection(Arc::new(client), &runtime);
+
+        client
+            .write(request_network_settings(PROTOCOL_VERSION - 1))
+            .await
+            .unwrap();
+        let mut packets = read(&mut client).await;
+        packets.extend(read(&mut client).await);
+        assert!(matches!(
+            &packets[..],
+            [
+                PacketKind::PlayStatus(PlayStatusPacket::FailedClient),
+                PacketKind::Disconnect(p),
+            ] if matches!(p.reason, DisconnectFailReason::OutdatedClient)
+        ));
+    });
+}
+
+#[test]
+fn rejection_is_flushed_before_closing() {
+    let runtime = Arc::new(Runtime::new().unwrap());
+    runtime.block_on(async {
+        let (server_addr, client_addr) = addrs();
+        let (server, client) = MemoryTransport::pair(server_addr, client_addr);
+        let server = Arc::new(Recording::new(server));
+        connection(server.clone(), &runtime).0.start();
+        let (mut client, _) = connection(Arc::new(client), &runtime);
+
+        client
+            .write(request_network_settings(PROTOCOL_VERSION + 1))
+            .await
+            .unwrap();
+        // The play status and the disconnect, then the flush that makes sure they arrive.
+        assert_eq!(server.closed().await, ["send", "send", "flush", "close"]);
+        let mut packets = read(&mut client).await;
+        packets.extend(read(&mut client).await);
+        assert!(matches!(
+            &packets[..],
+            [
+                PacketKind::PlayStatus(PlayStatusPacket::FailedSpawn),
+                PacketKind::Disconnect(p),
+            ] if matches!(p.reason, DisconnectFailReason::OutdatedServer)
+        ));
+        assert!(client.read().await.unwrap_err().is::<Closed>());
+    });
+}
+
+#[test]
+fn full_server_tells_the_client() {
+    let runtime = Arc::new(Runtime::new().unwrap());
+    runtime.block_on(async {
+        let (server_addr, client_addr) = addrs();
+        let (server, client) = MemoryTransport::pair(server_addr, client_addr);
+        let server = Arc::new(Recording::new(server));
+        let (mut connection_client, _) = connection(server.clone(), &runtime);
+        let slots = Arc::new(PlayerSlots::new(1, 0));
+        let _taken = slots.try_acquire(false).unwrap();
+        connection_client.slots = slots;
+        connection_client.start();
+        let (mut client, _) = connection(Arc::new(client), &runtime);
+
+        client
+            .write(request_network_settings(PROTOCOL_VERSION))
+            .await
+            .unwrap();
+        read(&mut client).await;
+        client.enable_compression();
+        let login = LoginPacket {
+            protocol_version: PROTOCOL_VERSION,
+            identity: r#"{"chain":[]}"#.into(),
+            client: String::new(),
+        };
+        client.write(login.into()).await.unwrap();
+
+        assert_eq!(
+            server.closed().await,
+            ["send", "send", "send", "flush", "close"]
+        );
+        let mut packets = read(&mut client).await;
+        packets.extend(read(&mut client).await);
+        assert!(matches!(
+            &packets[..],
+            [
+                PacketKind::PlayStatus(PlayStatusPacket::FailedServerFull),
+                PacketKind::Disconnect(p),
+            ] if matches!(p.reason, DisconnectFailReason::ServerFull)
+        ));
+    });
+}
+
+#[test]
+fn logged_in_session_reaches_the_game() {
+    let runtime = Arc::new(Runtime::new().unwrap());
+    runtime.block_on(async {
+        let (server_addr, client_addr) = addrs();
+        let (server, client) = MemoryTransport::pair(server_addr, client_addr);
+        let (mut server, mut players) = connection(Arc::new(server), &runtime);
+        let client = Arc::new(client);
+        let (mut client_connection, _) = connection(client.clone(), &runtime);
+        let key = [7; 32];
+        for connection in [&mut server, &mut client_connection] {
+            connection.enable_compression();
+            connection.enable_encryption(&key);
+        }
+
+        // The client asked for its chunk radius right after the handshake.
+        let leftover = RequestChunkRadiusPacket {
+            chunk_radius: 8,
+            max_chunk_radius: 12,
+        };
+        let login = LoginResult::Success(Box::default(), steve(), vec![leftover.into()]);
+        server.proceed(login).await;
+        let mut player = timeout(WAIT, players.recv()).await.unwrap().unwrap();
+        assert_eq!(player.address, client_addr);
+        assert_eq!(player.user.display_name, "Steve");
+        assert!(matches!(
+            player.packet_from_client.try_recv(),
+            Ok(PacketKind::RequestChunkRadius(p)) if p.chunk_radius == 8
+        ));
+
+        // Both ways through compression and encryption.
+        player
+            .packet_to_client
+            .send(PlayStatusPacket::PlayerSpawn.into())
+            .unwrap();
+        assert!(matches!(
+            &read(&mut client_connection).await[..],
+            [PacketKind::PlayStatus(PlayStatusPacket::PlayerSpawn)]
+        ));
+        let request = RequestChunkRadiusPacket {
+            chunk_radius: 4,
+            max_chunk_radius: 12,
+        };
+        client_connection.write(request.into()).await.unwrap();
+        let received = timeout(WAIT, player.packet_from_client.recv()).await;
+        assert!(matches!(
+            received,
+            Ok(Some(PacketKind::RequestChunkRadius(p))) if p.chunk_radius == 4
+        ));
+
+        client.close().await.unwrap();
+        let reason = timeout(WAIT, &mut player.disconnect).await.unwrap();
+        assert_eq!(reason, Ok(DisconnectReason::ClientClosed));
+    });
+}
+
+#[test]
+fn kick_reaches_a_client_that_keeps_sending() {
+    let runtime = Arc::new(Runtime::new().unwrap());
+    runtime.block_on(async {
+        let (server_addr, client_addr) = addrs();
+        let (server, client) = MemoryTransport::pair(server_addr, client_addr);
+        let (server, mut players) = connection(Arc::new(server), &runtime);
+        let (client, _) = connection(Arc::new(client), &runtime);
+        server
+            .proceed(LoginResult::Success(Box::default(), steve(), vec![]))
+            .await;
+        let player = timeout(WAIT, players.recv()).await.unwrap().unwrap();
+
+        // Clients send input every tick, so the reader is first to notice the game letting
+        // go of the player; here even before the kick is queued.
+        let ConnectionClient {
+            mut reader,
+            mut writer,
+            ..
+        } = client;
+        runtime.spawn(async move {
+            loop {
+                let input = RequestChunkRadiusPacket {
+                    chunk_radius: 8,
+                    max_chunk_radius: 12,
+                };
+                if writer.write(input.into()).await.is_err() {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        });
+        let PlayerRegistry {
+            packet_from_client,
+            packet_to_client,
+            mut disconnect,
+            ..
+        } = player;
+        drop(packet_from_client);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let kick = DisconnectPacket::new(DisconnectFailReason::Kicked, "Kicked by an operator");
+        packet_to_client.send(kick.into()).unwrap();
+        drop(packet_to_client);
+
+        let packets = timeout(WAIT, reader.read()).await.unwrap().unwrap();
+        assert!(matches!(
+            &packets[..],
+            [PacketKind::Disconnect(p)] if matches!(p.reason, DisconnectFailReason::Kicked)
+        ));
+        let reason = timeout(WAIT, &mut disconnect).await.unwrap();
+        assert_eq!(reason, Ok(DisconnectReason::ServerClosed));
+    });
+}
@@ -1,9 +1,11 @@
 use anyhow::{Ok, Result};
 use hob_ecs::{
-    events::{packet_recv::PacketRecvEvent, player_join::PlayerJoinEvent}, player::components::connection::ConnectionStreamComponent, plugin::PluginSys, Game, WriteStorage
+    events::player_join::PlayerJoinEvent, plugin::PluginSys, Game,
+};
+use hob_server::{
+    config::{ServerConfig, DEFAULT_CONFIG_PATH},
+    console, logging, rcon, Server,
 };
-use hob_protocol::packet::{disconnect::DisconnectPacket, PacketKind};
-use hob_server::{logging, Server};
 use log::info;
 use std::{
     sync::{
@@ -15,7 +17,8 @@ use std::{
 use tokio::{runtime::Builder, time::Instant};
 
 fn main() -> Result<()> {
-    logging::setup(log::LevelFilter::Debug);
+    let config = ServerConfig::load(DEFAULT_CONFIG_PATH)?;
+    logging::setup(config.log_level()?);
 
     let runtime = Arc::new(
         Builder::new_multi_thread()
@@ -28,28 +31,66 @@ fn main() -> Result<()> {
             .build()
             .unwrap(),
     );
-    runtime.block_on(async {
-        pub const TPS: u32 = 20;
-        pub const TICK_MILLIS: u32 = 1000 / TPS;
-        pub const TICK_DURATION: Duration = Duration::from_millis(TICK_MILLIS as u64);
+    let result = runtime.block_on(async {
+        let tick_duration = Duration::from_secs(1) / config.game.tps;
 
-        let server = Server::create(Arc::clone(&runtime)).await.unwrap();
-        let mut game = Game::new(server);
+        let server = Server::create(Arc::clone(&runtime), &config).await?;
+        let slots = Arc::clone(&server.slots);
+        let mut game = Game::new(server, &config);
+        game.attach_console(console::start(&runtime));
+        if config.rcon.enabled {
+            let (commands, _) = rcon::start(&config.rcon, &runtime).await?;
+            game.attach_rcon(commands);
+        }
+        let shutdown = game.shutdown_handle();
+        tokio::spawn(async move {
+            wait_for_signal().await;
+            info!("Received stop signal");
+            shutdown.request(None);
+        });
         game.add_plugin(HelloWorld);
-        game.add_plugin(LoginState);
         info!("Server Created");
         loop {
+            if let Some(request) = game.shutdown_requested() {
+                game.shutdown(request.message.as_deref());
+                break;
+            }
             let start = Instant::now();
             game.handle();
             let elapsed = start.elapsed();
-            if elapsed <= TICK_DURATION {
-                tokio::time::sleep(TICK_DURATION - elapsed).await;
+            if elapsed <= tick_duration {
+                tokio::time::sleep(tick_duration - elapsed).await;
             } else {
-                log::warn!("Tick took too long: {:?}", elapsed - TICK_DURATION)
+                log::warn!("Tick took too long: {:?}", elapsed - tick_duration)
             }
         }
+        // Give the writers time to deliver the disconnects before the runtime goes away.
+        if !slots.wait_released(Duration::from_secs(5)).await {
+            log::warn!("{} connections didn't close in time", slots.online());
+        }
+        info!("Server stopped");
+        Ok(())
     });
-    Ok(())
+    console::restore_terminal();
+    // The console may still be blocked reading stdin; don't wait for it.
+    if let Some(runtime) = Arc::into_inner(runtime) {
+        runtime.shutdown_background();
+    }
+    result
+}
+
+async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut terminate = signal(SignalKind::terminate()).expect("SIGTERM handler");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
 }
 
 pub struct HelloWorld;
@@ -60,17 +101,3 @@ impl<'a> PluginSys<'a, PlayerJoinEvent> for HelloWorld {
         false
     }
 }
-
-pub struct LoginState;
-impl<'a> PluginSys<'a, PacketRecvEvent> for LoginState {
-    type SystemData = WriteStorage<'a,ConnectionStreamComponent>;
-    fn run(&mut self, event: &'a PacketRecvEvent, mut conns: Self::SystemData) -> bool {
-        if let PacketKind::ClientToServerHandshake(..) = event.packet {
-            let conn = conns.get_mut(event.entity).unwrap();
-            conn.send_packet(DisconnectPacket::from("bye"));
-            info!("Client logged in");
-            return true
-        }
-        false
-    }
-}